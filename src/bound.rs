@@ -1,5 +1,6 @@
 use num_traits::{Float, NumAssign};
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BoundType {
     AxisAlignBox,
     Sphere,
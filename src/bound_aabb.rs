@@ -3,6 +3,7 @@ use std::cmp;
 
 use crate::bound::Bound;
 use crate::bound::BoundType;
+use crate::bound_sphere::separation_sphere_aabb;
 use crate::shape::ShapeType;
 
 #[derive(Debug, Clone, Copy)]
@@ -78,7 +79,20 @@ where
                 }
             }
             ShapeType::Frustum => {
-                unimplemented!();
+                //8 corners of the frustum
+                assert!(vals.len() == 24);
+                let mut bound_lower = [T::infinity(); 3];
+                let mut bound_upper = [T::neg_infinity(); 3];
+                for corner in vals.chunks(3) {
+                    for i in 0..3 {
+                        bound_lower[i] = bound_lower[i].min(corner[i]);
+                        bound_upper[i] = bound_upper[i].max(corner[i]);
+                    }
+                }
+                AxisAlignedBBox {
+                    bound_lower,
+                    bound_upper,
+                }
             }
             _ => {
                 unimplemented!();
@@ -104,6 +118,27 @@ where
             .unwrap();
         longest
     }
+    ///parameter interval of ray ori + dir * t within the box, clipped to t >= 0
+    pub fn intersect_ray(&self, ori: &[T; 3], dir: &[T; 3]) -> Option<(T, T)> {
+        let mut t_near = T::zero();
+        let mut t_far = T::infinity();
+        for i in 0..3 {
+            if dir[i] == T::zero() {
+                if ori[i] < self.bound_lower[i] || ori[i] > self.bound_upper[i] {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (self.bound_lower[i] - ori[i]) / dir[i];
+            let t1 = (self.bound_upper[i] - ori[i]) / dir[i];
+            t_near = t_near.max(t0.min(t1));
+            t_far = t_far.min(t0.max(t1));
+            if t_near > t_far {
+                return None;
+            }
+        }
+        Some((t_near, t_far))
+    }
 }
 
 impl<T> Bound<T> for AxisAlignedBBox<T>
@@ -131,13 +166,43 @@ where
                 }
                 true
             }
-            _ => {
-                unimplemented!();
-            }
+            BoundType::Sphere => self.get_shortest_separation(other) <= T::zero(),
         }
     }
-    fn get_shortest_separation(&self, _other: &dyn Bound<T>) -> T {
-        unimplemented!();
+    //euclidean distance between the boxes if they are disjoint,
+    //otherwise the negated overlap along the axis of least penetration
+    fn get_shortest_separation(&self, other: &dyn Bound<T>) -> T {
+        match other.get_type() {
+            BoundType::AxisAlignBox => {
+                let a_bounds = self.get_bound_data();
+                let b_bounds = other.get_bound_data();
+
+                let a_lower = &a_bounds[0..3];
+                let a_upper = &a_bounds[3..6];
+                let b_lower = &b_bounds[0..3];
+                let b_upper = &b_bounds[3..6];
+
+                //gap along each axis, negative when the intervals overlap
+                //infinite bounds yield an infinitely negative gap
+                let mut gaps = [T::zero(); 3];
+                for i in 0..3 {
+                    gaps[i] = (b_lower[i] - a_upper[i]).max(a_lower[i] - b_upper[i]);
+                }
+
+                if gaps.iter().any(|x| *x > T::zero()) {
+                    gaps.iter()
+                        .filter(|x| **x > T::zero())
+                        .fold(T::zero(), |acc, x| acc + *x * *x)
+                        .sqrt()
+                } else {
+                    gaps.iter().fold(T::neg_infinity(), |acc, x| acc.max(*x))
+                }
+            }
+            BoundType::Sphere => {
+                let b = other.get_bound_data();
+                separation_sphere_aabb(&b[0..3], b[3], &self.bound_lower, &self.bound_upper)
+            }
+        }
     }
     fn get_bound_data(&self) -> [T; 32] {
         let mut arr = [T::zero(); 32];
@@ -149,15 +214,14 @@ where
         self.bound_lower = [T::infinity(); 3];
         self.bound_upper = [T::neg_infinity(); 3];
         for i in bounds {
-            match i.get_type() {
-                BoundType::AxisAlignBox => (),
-                _ => {
-                    unimplemented!();
-                }
-            }
             let b = i.get_bound_data();
-            let b_lower = &b[0..3];
-            let b_upper = &b[3..6];
+            let (b_lower, b_upper) = match i.get_type() {
+                BoundType::AxisAlignBox => ([b[0], b[1], b[2]], [b[3], b[4], b[5]]),
+                BoundType::Sphere => (
+                    [b[0] - b[3], b[1] - b[3], b[2] - b[3]],
+                    [b[0] + b[3], b[1] + b[3], b[2] + b[3]],
+                ),
+            };
             for j in 0..3 {
                 self.bound_lower[j] = self.bound_lower[j].min(b_lower[j]);
                 self.bound_upper[j] = self.bound_upper[j].max(b_upper[j]);
@@ -194,3 +258,53 @@ where
         }
     }
 }
+
+#[test]
+fn test_bound_aabb_separation_disjoint() {
+    let a = AxisAlignedBBox::new(ShapeType::Box, &[0f64, 0f64, 0f64, 1f64]);
+    let b = AxisAlignedBBox::new(ShapeType::Box, &[5f64, 0f64, 0f64, 1f64]);
+    assert_eq!(a.get_shortest_separation(&b), 3f64);
+    assert_eq!(b.get_shortest_separation(&a), 3f64);
+
+    let c = AxisAlignedBBox::new(ShapeType::Box, &[5f64, 6f64, 0.5f64, 1f64]);
+    assert_eq!(a.get_shortest_separation(&c), 5f64);
+}
+#[test]
+fn test_bound_aabb_separation_overlap() {
+    let a = AxisAlignedBBox::new(ShapeType::Box, &[0f64, 0f64, 0f64, 1f64]);
+    let b = AxisAlignedBBox::new(ShapeType::Box, &[1.5f64, 0.2f64, 0f64, 1f64]);
+    assert_eq!(a.get_shortest_separation(&b), -0.5f64);
+
+    let c = AxisAlignedBBox::new(ShapeType::Box, &[2f64, 0f64, 0f64, 1f64]);
+    assert_eq!(a.get_shortest_separation(&c), 0f64);
+}
+#[test]
+fn test_bound_aabb_separation_infinite() {
+    let a = AxisAlignedBBox::new(ShapeType::Box, &[0f64, 0f64, 0f64, 1f64]);
+    let plane = AxisAlignedBBox::new(ShapeType::Plane, &[0f64, 100f64, 0f64, 0f64, 1f64, 0f64]);
+    assert_eq!(a.get_shortest_separation(&plane), f64::NEG_INFINITY);
+    assert_eq!(plane.get_shortest_separation(&plane), f64::NEG_INFINITY);
+
+    //ray along +x starting past the box only extends away from it
+    let ray = AxisAlignedBBox::new(ShapeType::Ray, &[3f64, 0f64, 0f64, 1f64, 0f64, 0f64]);
+    assert_eq!(a.get_shortest_separation(&ray), 2f64);
+    assert_eq!(ray.get_shortest_separation(&a), 2f64);
+
+    //ray along -x starting past the box extends through it
+    let ray = AxisAlignedBBox::new(ShapeType::Ray, &[3f64, 0f64, 0f64, -1f64, 0f64, 0f64]);
+    assert_eq!(a.get_shortest_separation(&ray), -1f64);
+}
+#[test]
+fn test_bound_aabb_intersect_ray() {
+    let a = AxisAlignedBBox::new(ShapeType::Box, &[0f64, 0f64, 0f64, 1f64]);
+    assert_eq!(
+        a.intersect_ray(&[-3., 0.5, 0.], &[1., 0., 0.]),
+        Some((2., 4.))
+    );
+    assert_eq!(
+        a.intersect_ray(&[0., 0., 0.], &[0., 0., 2.]),
+        Some((0., 0.5))
+    );
+    assert_eq!(a.intersect_ray(&[-3., 1.5, 0.], &[1., 0., 0.]), None);
+    assert_eq!(a.intersect_ray(&[3., 0., 0.], &[1., 0., 0.]), None);
+}
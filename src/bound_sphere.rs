@@ -0,0 +1,317 @@
+use num_traits::{Float, NumAssign};
+
+use crate::bound::Bound;
+use crate::bound::BoundType;
+use crate::shape::ShapeType;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingSphere<T: NumAssign + Copy + Default + Float> {
+    pub center: [T; 3],
+    pub radius: T,
+}
+
+impl<T> BoundingSphere<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///vals: center and radius for Sphere, center and half size for Box, lower and upper corners for Rect,
+    ///origin and direction for Ray and Plane, and vertices enclosed by the sphere for any other shape type
+    pub fn new(shape_type: ShapeType, vals: &[T]) -> BoundingSphere<T> {
+        match shape_type {
+            ShapeType::Sphere => {
+                assert!(vals.len() == 4);
+                BoundingSphere {
+                    center: [vals[0], vals[1], vals[2]],
+                    radius: vals[3],
+                }
+            }
+            ShapeType::Box => {
+                assert!(vals.len() == 4);
+                BoundingSphere {
+                    center: [vals[0], vals[1], vals[2]],
+                    radius: vals[3] * T::from(3.).unwrap().sqrt(),
+                }
+            }
+            ShapeType::Rect => {
+                assert!(vals.len() == 6);
+                from_aabb(&vals[0..3], &vals[3..6])
+            }
+            ShapeType::Ray | ShapeType::Plane => {
+                //unbounded
+                assert!(vals.len() == 6);
+                BoundingSphere {
+                    center: [vals[0], vals[1], vals[2]],
+                    radius: T::infinity(),
+                }
+            }
+            _ => {
+                assert!(!vals.is_empty());
+                assert_eq!(vals.len() % 3, 0);
+                let mut lower = [T::infinity(); 3];
+                let mut upper = [T::neg_infinity(); 3];
+                for v in vals.chunks(3) {
+                    for i in 0..3 {
+                        lower[i] = lower[i].min(v[i]);
+                        upper[i] = upper[i].max(v[i]);
+                    }
+                }
+                from_aabb(&lower, &upper)
+            }
+        }
+    }
+}
+
+///circumscribed sphere of an axis aligned box, unbounded boxes give an infinite radius
+///centered at their finite bounds
+fn from_aabb<T>(lower: &[T], upper: &[T]) -> BoundingSphere<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let two = T::from(2.).unwrap();
+    if lower.iter().chain(upper.iter()).any(|x| !x.is_finite()) {
+        let mut center = [T::zero(); 3];
+        for i in 0..3 {
+            center[i] = match (lower[i].is_finite(), upper[i].is_finite()) {
+                (true, true) => (lower[i] + upper[i]) / two,
+                (true, false) => lower[i],
+                (false, true) => upper[i],
+                _ => T::zero(),
+            };
+        }
+        return BoundingSphere {
+            center,
+            radius: T::infinity(),
+        };
+    }
+    let mut center = [T::zero(); 3];
+    let mut r2 = T::zero();
+    for i in 0..3 {
+        center[i] = (lower[i] + upper[i]) / two;
+        let half = (upper[i] - lower[i]) / two;
+        r2 += half * half;
+    }
+    BoundingSphere {
+        center,
+        radius: r2.sqrt(),
+    }
+}
+
+///signed separation between a sphere and an axis aligned box,
+///negative values are the overlap along the axis of least penetration
+pub(crate) fn separation_sphere_aabb<T>(center: &[T], radius: T, lower: &[T], upper: &[T]) -> T
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let mut d2 = T::zero();
+    let mut inside = true;
+    for i in 0..3 {
+        let clamped = center[i].max(lower[i]).min(upper[i]);
+        let d = center[i] - clamped;
+        if d != T::zero() {
+            inside = false;
+        }
+        d2 += d * d;
+    }
+    if inside {
+        //center is inside of the box, push out through the nearest face
+        let mut nearest = T::infinity();
+        for i in 0..3 {
+            nearest = nearest.min(center[i] - lower[i]).min(upper[i] - center[i]);
+        }
+        -(nearest + radius)
+    } else {
+        d2.sqrt() - radius
+    }
+}
+
+impl<T> Bound<T> for BoundingSphere<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn get_type(&self) -> BoundType {
+        BoundType::Sphere
+    }
+    fn intersect(&self, other: &dyn Bound<T>) -> bool {
+        self.get_shortest_separation(other) <= T::zero()
+    }
+    //distance between the surfaces, negative when overlapping
+    fn get_shortest_separation(&self, other: &dyn Bound<T>) -> T {
+        let b = other.get_bound_data();
+        match other.get_type() {
+            BoundType::Sphere => {
+                let d2 = self
+                    .center
+                    .iter()
+                    .zip(b.iter())
+                    .fold(T::zero(), |acc, (x, y)| acc + (*x - *y) * (*x - *y));
+                d2.sqrt() - self.radius - b[3]
+            }
+            BoundType::AxisAlignBox => {
+                separation_sphere_aabb(&self.center, self.radius, &b[0..3], &b[3..6])
+            }
+        }
+    }
+    fn get_bound_data(&self) -> [T; 32] {
+        let mut arr = [T::zero(); 32];
+        arr[..3].clone_from_slice(&self.center[..3]);
+        arr[3] = self.radius;
+        arr
+    }
+    fn get_union(&mut self, bounds: &[&dyn Bound<T>]) {
+        self.center = [T::zero(); 3];
+        self.radius = T::neg_infinity();
+        for i in bounds {
+            let b = i.get_bound_data();
+            let s = match i.get_type() {
+                BoundType::Sphere => BoundingSphere {
+                    center: [b[0], b[1], b[2]],
+                    radius: b[3],
+                },
+                BoundType::AxisAlignBox => from_aabb(&b[0..3], &b[3..6]),
+            };
+            if self.radius == T::neg_infinity() {
+                *self = s;
+                continue;
+            }
+            let offset = [
+                s.center[0] - self.center[0],
+                s.center[1] - self.center[1],
+                s.center[2] - self.center[2],
+            ];
+            let d = offset.iter().fold(T::zero(), |acc, x| acc + *x * *x).sqrt();
+            if d + s.radius <= self.radius {
+                //already enclosed
+                continue;
+            }
+            if d + self.radius <= s.radius {
+                *self = s;
+                continue;
+            }
+            let r = (d + self.radius + s.radius) / T::from(2.).unwrap();
+            let t = (r - self.radius) / d;
+            for (c, o) in self.center.iter_mut().zip(offset.iter()) {
+                *c += *o * t;
+            }
+            self.radius = r;
+        }
+    }
+    fn get_centroid(&self) -> [T; 3] {
+        self.center
+    }
+}
+
+impl<T> Default for BoundingSphere<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn default() -> BoundingSphere<T> {
+        BoundingSphere {
+            center: [T::zero(); 3],
+            radius: T::infinity(),
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::bound_aabb::AxisAlignedBBox;
+
+#[test]
+fn test_bound_sphere_sphere() {
+    let a = BoundingSphere::new(ShapeType::Sphere, &[0f64, 0f64, 0f64, 2f64]);
+    let b = BoundingSphere::new(ShapeType::Sphere, &[0f64, 6f64, 0f64, 1f64]);
+    assert!(!a.intersect(&b));
+    assert_eq!(a.get_shortest_separation(&b), 3f64);
+
+    let c = BoundingSphere::new(ShapeType::Sphere, &[0f64, 2.5f64, 0f64, 1f64]);
+    assert!(a.intersect(&c));
+    assert!(c.intersect(&a));
+    assert_eq!(a.get_shortest_separation(&c), -0.5f64);
+}
+#[test]
+fn test_bound_sphere_shape_types() {
+    let a = BoundingSphere::new(ShapeType::Point, &[1f64, 2f64, 3f64]);
+    assert_eq!(a.center, [1f64, 2f64, 3f64]);
+    assert_eq!(a.radius, 0f64);
+
+    //vertices of a tri prism
+    let b = BoundingSphere::new(
+        ShapeType::TriPrism,
+        &[
+            0f64, 0., 0., 2., 0., 0., 2., 2., 0., 0., 0., 2., 2., 0., 2., 2., 2., 2.,
+        ],
+    );
+    assert_eq!(b.center, [1f64, 1f64, 1f64]);
+    assert!((b.radius - 3f64.sqrt()).abs() < 1e-12);
+
+    let c = BoundingSphere::new(ShapeType::Plane, &[0f64, 0., 0., 0., 1., 0.]);
+    assert!(c.intersect(&BoundingSphere::new(
+        ShapeType::Sphere,
+        &[1e9f64, 0., 0., 1.]
+    )));
+}
+#[test]
+fn test_bound_sphere_aabb() {
+    let a = BoundingSphere::new(ShapeType::Sphere, &[3f64, 4f64, 0f64, 1f64]);
+    let b = AxisAlignedBBox::new(ShapeType::Box, &[0f64, 0f64, 0f64, 1f64]);
+    assert!(!a.intersect(&b));
+    assert!(!b.intersect(&a));
+    assert!((a.get_shortest_separation(&b) - (13f64.sqrt() - 1f64)).abs() < 1e-12);
+    assert_eq!(a.get_shortest_separation(&b), b.get_shortest_separation(&a));
+
+    let c = BoundingSphere::new(ShapeType::Sphere, &[1.5f64, 0f64, 0f64, 1f64]);
+    assert!(c.intersect(&b));
+    assert!(b.intersect(&c));
+    assert_eq!(c.get_shortest_separation(&b), -0.5f64);
+
+    //center inside of the box
+    let d = BoundingSphere::new(ShapeType::Sphere, &[0.5f64, 0f64, 0f64, 0.25f64]);
+    assert_eq!(d.get_shortest_separation(&b), -0.75f64);
+}
+#[test]
+fn test_bound_sphere_infinite_aabb() {
+    let a = BoundingSphere::new(ShapeType::Sphere, &[3f64, 4f64, 0f64, 1f64]);
+    let plane = AxisAlignedBBox::new(ShapeType::Plane, &[0f64, 100f64, 0f64, 0f64, 1f64, 0f64]);
+    assert!(a.intersect(&plane));
+    assert_eq!(a.get_shortest_separation(&plane), f64::NEG_INFINITY);
+
+    let ray = AxisAlignedBBox::new(ShapeType::Ray, &[3f64, 0f64, 0f64, 0f64, 1f64, 0f64]);
+    assert!(a.intersect(&ray));
+    let ray = AxisAlignedBBox::new(ShapeType::Ray, &[3f64, 0f64, 0f64, 0f64, -1f64, 0f64]);
+    assert!(!a.intersect(&ray));
+    assert_eq!(a.get_shortest_separation(&ray), 3f64);
+}
+#[test]
+fn test_bound_sphere_union() {
+    let a = BoundingSphere::new(ShapeType::Sphere, &[0f64, 0f64, 0f64, 1f64]);
+    let b = BoundingSphere::new(ShapeType::Sphere, &[4f64, 0f64, 0f64, 1f64]);
+    let c = AxisAlignedBBox::new(ShapeType::Box, &[2f64, 0f64, 0f64, 0.5f64]);
+    let mut u = BoundingSphere::default();
+    u.get_union(&[&a, &b, &c]);
+    assert_eq!(u.get_centroid(), [2f64, 0f64, 0f64]);
+    assert_eq!(u.radius, 3f64);
+
+    let mut v = AxisAlignedBBox::default();
+    v.get_union(&[&a, &b]);
+    assert_eq!(v.bound_lower, [-1f64, -1f64, -1f64]);
+    assert_eq!(v.bound_upper, [5f64, 1f64, 1f64]);
+}
+#[test]
+fn test_bound_sphere_union_infinite_aabb() {
+    let a = BoundingSphere::new(ShapeType::Sphere, &[0f64, 0f64, 0f64, 1f64]);
+    let plane = AxisAlignedBBox::new(ShapeType::Plane, &[0f64, 1f64, 0f64, 0f64, 1f64, 0f64]);
+    let mut u = BoundingSphere::default();
+    u.get_union(&[&a, &plane]);
+    assert!(u.center.iter().all(|x| x.is_finite()));
+    assert_eq!(u.radius, f64::INFINITY);
+    assert!(u.intersect(&BoundingSphere::new(
+        ShapeType::Sphere,
+        &[1e9f64, 0., 0., 1.]
+    )));
+
+    //half infinite ray bound keeps its finite start
+    let ray = AxisAlignedBBox::new(ShapeType::Ray, &[3f64, 1f64, 2f64, 1f64, 0f64, 0f64]);
+    let mut v = BoundingSphere::default();
+    v.get_union(&[&ray, &a]);
+    assert_eq!(v.center, [3f64, 1f64, 2f64]);
+    assert_eq!(v.radius, f64::INFINITY);
+}
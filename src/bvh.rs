@@ -0,0 +1,223 @@
+//! bounding volume hierarchy over a fixed set of axis aligned bounds
+
+use crate::bound::Bound;
+use crate::bound_aabb::{Axis, AxisAlignedBBox};
+use num_traits::{Float, NumAssign};
+
+///maximum number of items held by a leaf of the hierarchy
+pub const LEAF_SIZE: usize = 4;
+
+///node of the hierarchy, leaves reference _count items starting at _start in _order
+#[derive(Debug, Clone)]
+pub struct BvhNode<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _bound: AxisAlignedBBox<T>,
+    pub _children: Option<(usize, usize)>,
+    pub _start: usize,
+    pub _count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bvh<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _nodes: Vec<BvhNode<T>>,
+    pub _order: Vec<usize>,
+    pub _bounds: Vec<AxisAlignedBBox<T>>,
+}
+
+impl<T> Bvh<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///builds the hierarchy over bounds of items, items are referred to by their index
+    pub fn init(bounds: &[AxisAlignedBBox<T>]) -> Bvh<T> {
+        let mut bvh = Bvh {
+            _nodes: vec![],
+            _order: (0..bounds.len()).collect(),
+            _bounds: bounds.to_vec(),
+        };
+        if !bounds.is_empty() {
+            bvh.build(0, bounds.len());
+        }
+        bvh
+    }
+
+    ///bound of all items, None if empty
+    pub fn get_bound(&self) -> Option<&AxisAlignedBBox<T>> {
+        self._nodes.first().map(|n| &n._bound)
+    }
+
+    ///recursively splits the items in _order[start..start + count] at the median
+    ///of their centroids along the longest axis, returns index of the created node
+    fn build(&mut self, start: usize, count: usize) -> usize {
+        let mut bound = AxisAlignedBBox::default();
+        {
+            let bounds: Vec<&dyn Bound<T>> = self._order[start..start + count]
+                .iter()
+                .map(|i| &self._bounds[*i] as &dyn Bound<T>)
+                .collect();
+            bound.get_union(&bounds);
+        }
+        let idx = self._nodes.len();
+        self._nodes.push(BvhNode {
+            _bound: bound,
+            _children: None,
+            _start: start,
+            _count: count,
+        });
+        if count <= LEAF_SIZE {
+            return idx;
+        }
+
+        let mut centroids = AxisAlignedBBox {
+            bound_lower: [T::infinity(); 3],
+            bound_upper: [T::neg_infinity(); 3],
+        };
+        for i in self._order[start..start + count].iter() {
+            let c = self._bounds[*i].get_centroid();
+            for (k, x) in c.iter().enumerate() {
+                centroids.bound_lower[k] = centroids.bound_lower[k].min(*x);
+                centroids.bound_upper[k] = centroids.bound_upper[k].max(*x);
+            }
+        }
+        let axis = match centroids.get_longest_axis().0 {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        };
+        let bounds = &self._bounds;
+        self._order[start..start + count].sort_by(|a, b| {
+            let ca = bounds[*a].get_centroid()[axis];
+            let cb = bounds[*b].get_centroid()[axis];
+            ca.partial_cmp(&cb).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let half = count / 2;
+        let left = self.build(start, half);
+        let right = self.build(start + half, count - half);
+        self._nodes[idx]._children = Some((left, right));
+        idx
+    }
+
+    ///indices of items whose bounds intersect the given bound
+    pub fn query_bound(&self, bound: &dyn Bound<T>) -> Vec<usize> {
+        let mut found = vec![];
+        let mut stack = if self._nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(n) = stack.pop() {
+            let node = &self._nodes[n];
+            if !node._bound.intersect(bound) {
+                continue;
+            }
+            match node._children {
+                Some((l, r)) => {
+                    stack.push(l);
+                    stack.push(r);
+                }
+                _ => {
+                    for i in self._order[node._start..node._start + node._count].iter() {
+                        if self._bounds[*i].intersect(bound) {
+                            found.push(*i);
+                        }
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    ///nearest hit of ray ori + dir * t, f tests the item of the given index and returns
+    ///the parameter of its hit along with any data of the hit,
+    ///subtrees further than the nearest hit found so far are skipped
+    pub fn query_ray<R, F>(&self, ori: &[T; 3], dir: &[T; 3], mut f: F) -> Option<(T, usize, R)>
+    where
+        F: FnMut(usize) -> Option<(T, R)>,
+    {
+        let mut best: Option<(T, usize, R)> = None;
+        let mut stack = if self._nodes.is_empty() {
+            vec![]
+        } else {
+            vec![0]
+        };
+        while let Some(n) = stack.pop() {
+            let node = &self._nodes[n];
+            match node._bound.intersect_ray(ori, dir) {
+                Some((t, _)) => {
+                    if let Some((t_best, _, _)) = best {
+                        if t > t_best {
+                            continue;
+                        }
+                    }
+                }
+                _ => continue,
+            }
+            match node._children {
+                Some((l, r)) => {
+                    stack.push(l);
+                    stack.push(r);
+                }
+                _ => {
+                    for i in self._order[node._start..node._start + node._count].iter() {
+                        if let Some((t, r)) = f(*i) {
+                            match best {
+                                Some((t_best, _, _)) if t_best <= t => {}
+                                _ => best = Some((t, *i, r)),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+}
+
+#[test]
+fn test_bvh_query() {
+    let bounds: Vec<AxisAlignedBBox<f64>> = (0..20)
+        .map(|i| AxisAlignedBBox {
+            bound_lower: [i as f64, 0., 0.],
+            bound_upper: [i as f64 + 0.5, 1., 1.],
+        })
+        .collect();
+    let a = Bvh::init(&bounds);
+    assert!(a._nodes.len() > 1);
+    let b = a.get_bound().expect("bvh bound unexpected");
+    assert_eq!(b.bound_lower, [0., 0., 0.]);
+    assert_eq!(b.bound_upper, [19.5, 1., 1.]);
+
+    //every item is referenced by exactly one leaf
+    let mut seen = [0; 20];
+    for n in a._nodes.iter().filter(|n| n._children.is_none()) {
+        assert!(n._count <= LEAF_SIZE);
+        for i in a._order[n._start..n._start + n._count].iter() {
+            seen[*i] += 1;
+        }
+    }
+    assert!(seen.iter().all(|x| *x == 1));
+
+    let q = AxisAlignedBBox {
+        bound_lower: [4.8, 0.5, 0.5],
+        bound_upper: [6.2, 2., 2.],
+    };
+    let mut found = a.query_bound(&q);
+    found.sort();
+    assert_eq!(found, vec![5, 6]);
+
+    //nearest item along the ray, entering each item at its lower x
+    let hit = a.query_ray(&[30., 0.5, 0.5], &[-1., 0., 0.], |i| {
+        Some((30. - bounds[i].bound_upper[0], ()))
+    });
+    assert_eq!(hit.map(|x| x.1), Some(19));
+    assert!(a
+        .query_ray(&[30., 1.5, 0.5], &[-1., 0., 0.], |_| Some((0., ())))
+        .is_none());
+    assert!(Bvh::<f64>::init(&[]).get_bound().is_none());
+}
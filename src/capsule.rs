@@ -0,0 +1,549 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::cuboid::*;
+use crate::error::CollideError;
+use crate::line::closest_points_segments;
+use crate::plane::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::rect_box::*;
+use crate::shape::*;
+use crate::sphere::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+///segment between 2 points swept by a sphere
+#[derive(Debug, Clone)]
+pub struct Capsule<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _a: Matrix<T, 4, 1>,
+    pub _b: Matrix<T, 4, 1>,
+    pub _radius: T,
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+impl<T> Capsule<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub fn init(a: &[T], b: &[T], r: T) -> Capsule<T> {
+        assert!(a.len() == 3);
+        assert!(b.len() == 3);
+        let mut vals = [T::zero(); 6];
+        for i in 0..3 {
+            vals[i] = a[i].min(b[i]) - r;
+            vals[i + 3] = a[i].max(b[i]) + r;
+        }
+        Capsule {
+            _a: Matrix::from([[a[0], a[1], a[2], T::one()]]).t(),
+            _b: Matrix::from([[b[0], b[1], b[2], T::one()]]).t(),
+            _radius: r,
+            _bound: AxisAlignedBBox::new(ShapeType::Rect, &vals),
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+
+    ///point on the segment closest to p
+    pub fn get_closest_point(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        let ab = self._b - self._a;
+        let l = ab.inner(&ab);
+        if l == T::zero() {
+            return self._a;
+        }
+        let t = ((*p - self._a).inner(&ab) / l).max(T::zero()).min(T::one());
+        self._a + ab * t
+    }
+
+    ///unit direction from q towards p, or any direction perpendicular to the segment if p lies on it
+    fn get_direction(&self, q: &Matrix<T, 4, 1>, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        let d = *p - *q;
+        let l = d.norm_l2();
+        if l != T::zero() {
+            return d / l;
+        }
+        let ab = self._b - self._a;
+        let x = Matrix::from([[T::one(), T::zero(), T::zero(), T::zero()]]).t();
+        let y = Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t();
+        let n = ab.cross(&x);
+        if n.norm_l2() > T::epsilon() * ab.norm_l2() {
+            n.normalize_l2()
+        } else if ab.norm_l2() != T::zero() {
+            ab.cross(&y).normalize_l2()
+        } else {
+            y
+        }
+    }
+
+    ///contact of the swept segment with a sphere of radius r centered at p closest to segment point q
+    fn get_contact_sphere(
+        &self,
+        q: &Matrix<T, 4, 1>,
+        p: &Matrix<T, 4, 1>,
+        r: T,
+    ) -> Option<Contact<T>> {
+        let d = (*p - *q).norm_l2();
+        if d > self._radius + r {
+            return None;
+        }
+        let n = self.get_direction(q, p);
+        Some(Contact {
+            _point_a: *q + n * self._radius,
+            _point_b: *p - n * r,
+            _normal: n,
+            _depth: self._radius + r - d,
+            _feature: None,
+        })
+    }
+
+    ///parameter interval of ray ori + dir * t inside of the capsule
+    fn intersect_ray_interval(
+        &self,
+        ori: &Matrix<T, 4, 1>,
+        dir: &Matrix<T, 4, 1>,
+    ) -> Option<(T, T)> {
+        let r2 = self._radius * self._radius;
+        let mut interval: Option<(T, T)> = None;
+        let mut merge = |t0: T, t1: T| {
+            if t0 <= t1 {
+                interval = match interval {
+                    Some((lo, hi)) => Some((lo.min(t0), hi.max(t1))),
+                    _ => Some((t0, t1)),
+                };
+            }
+        };
+        let dd = dir.inner(dir);
+
+        //end caps
+        for c in [self._a, self._b].iter() {
+            let m = *ori - *c;
+            let b = m.inner(dir);
+            let disc = b * b - dd * (m.inner(&m) - r2);
+            if disc >= T::zero() {
+                merge((-b - disc.sqrt()) / dd, (-b + disc.sqrt()) / dd);
+            }
+        }
+
+        //cylindrical body, clipped to the extent of the segment
+        let ab = self._b - self._a;
+        let l = ab.norm_l2();
+        if l != T::zero() {
+            let u = ab / l;
+            let m = *ori - self._a;
+            let (mu, du) = (m.inner(&u), dir.inner(&u));
+            let m_perp = m - u * mu;
+            let d_perp = *dir - u * du;
+            let a = d_perp.inner(&d_perp);
+            let b = m_perp.inner(&d_perp);
+            let c = m_perp.inner(&m_perp) - r2;
+            let radial = if a == T::zero() {
+                if c > T::zero() {
+                    None
+                } else {
+                    Some((T::neg_infinity(), T::infinity()))
+                }
+            } else {
+                let disc = b * b - a * c;
+                if disc < T::zero() {
+                    None
+                } else {
+                    Some(((-b - disc.sqrt()) / a, (-b + disc.sqrt()) / a))
+                }
+            };
+            let axial = if du == T::zero() {
+                if mu < T::zero() || mu > l {
+                    None
+                } else {
+                    Some((T::neg_infinity(), T::infinity()))
+                }
+            } else {
+                let (t0, t1) = (-mu / du, (l - mu) / du);
+                Some((t0.min(t1), t0.max(t1)))
+            };
+            if let (Some(x), Some(y)) = (radial, axial) {
+                merge(x.0.max(y.0), x.1.min(y.1));
+            }
+        }
+        interval
+    }
+
+    ///contact with an axis aligned box given by its bounds and corners,
+    ///a segment reaching into the box is pushed out through the face nearest to its part inside
+    fn intersect_aabb(
+        &self,
+        lower: &[T; 3],
+        upper: &[T; 3],
+        corners: &[Matrix<T, 4, 1>; 8],
+    ) -> Option<Contact<T>> {
+        let two = T::from(2.).unwrap();
+        let mut center = [T::zero(); 3];
+        let mut size = [T::zero(); 3];
+        let mut rel = [T::zero(); 3];
+        let mut dir = [T::zero(); 3];
+        for i in 0..3 {
+            center[i] = (lower[i] + upper[i]) / two;
+            size[i] = (upper[i] - lower[i]) / two;
+            rel[i] = self._a[[i, 0]] - center[i];
+            dir[i] = self._b[[i, 0]] - self._a[[i, 0]];
+        }
+        if let Some((near, far)) = clip_slab(&rel, &dir, &size) {
+            let (t0, t1) = (near.0.max(T::zero()), far.0.min(T::one()));
+            if t0 <= t1 {
+                //segment itself penetrates the box, push the part inside out through the nearest face
+                let p = [t0, t1].map(|t| self._a + (self._b - self._a) * t);
+                let mut best: Option<(T, usize, T, usize)> = None;
+                for i in 0..3 {
+                    for s in [T::one(), -T::one()] {
+                        let (k, v) = if p[0][[i, 0]] * s <= p[1][[i, 0]] * s {
+                            (0, p[0][[i, 0]] * s)
+                        } else {
+                            (1, p[1][[i, 0]] * s)
+                        };
+                        let depth = size[i] + self._radius - (v - center[i] * s);
+                        match best {
+                            Some((d, _, _, _)) if d <= depth => {}
+                            _ => best = Some((depth, i, s, k)),
+                        }
+                    }
+                }
+                let (depth, i, s, k) = best.unwrap();
+                let mut u = Matrix::zero();
+                u[[i, 0]] = s;
+                let deepest = p[k] - u * self._radius;
+                return Some(Contact {
+                    _point_a: deepest,
+                    _point_b: deepest + u * depth,
+                    _normal: u * -T::one(),
+                    _depth: depth,
+                    _feature: None,
+                });
+            }
+        }
+
+        //closest features are a segment end point against the box or the segment against a box edge
+        let mut best: Option<(T, Matrix<T, 4, 1>, Matrix<T, 4, 1>)> = None;
+        let mut update = |p: Matrix<T, 4, 1>, q: Matrix<T, 4, 1>| {
+            let d = (q - p).norm_l2();
+            match best {
+                Some((d_best, _, _)) if d_best <= d => {}
+                _ => best = Some((d, p, q)),
+            }
+        };
+        for p in [self._a, self._b].iter() {
+            let mut q = *p;
+            for i in 0..3 {
+                q[[i, 0]] = p[[i, 0]].max(lower[i]).min(upper[i]);
+            }
+            update(*p, q);
+        }
+        for (i, c) in corners.iter().enumerate() {
+            for axis in 0..3 {
+                if i & (1 << axis) == 0 {
+                    let (p, q) =
+                        closest_points_segments(&self._a, &self._b, c, &corners[i | (1 << axis)]);
+                    update(p, q);
+                }
+            }
+        }
+        let (d, p, q) = best.unwrap();
+        if d > self._radius {
+            return None;
+        }
+        let n = (q - p) / d;
+        Some(Contact {
+            _point_a: p + n * self._radius,
+            _point_b: q,
+            _normal: n,
+            _depth: self._radius - d,
+            _feature: None,
+        })
+    }
+}
+
+impl<T> AnyBase for Capsule<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for Capsule<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Capsule
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._radius.is_nan() || self._radius < T::zero() {
+            return Err(CollideError::DegenerateShape(ShapeType::Capsule));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let p = other_point._ori;
+                let q = self.get_closest_point(&p);
+                Ok(self.get_contact_sphere(&q, &p, T::zero()))
+            }
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
+                let p = other_sphere._ori;
+                let q = self.get_closest_point(&p);
+                Ok(self.get_contact_sphere(&q, &p, other_sphere._radius))
+            }
+            ShapeType::Capsule => {
+                let other_capsule: &Capsule<T> = downcast(other)?;
+                let (q, p) = closest_points_segments(
+                    &self._a,
+                    &self._b,
+                    &other_capsule._a,
+                    &other_capsule._b,
+                );
+                Ok(self.get_contact_sphere(&q, &p, other_capsule._radius))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                let (t0, t1) = match self.intersect_ray_interval(&other_ray._ori, &other_ray._dir) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                if t1 < T::zero() {
+                    return Ok(None);
+                }
+                //ray starting inside of the capsule hits on its way out
+                let t = if t0 >= T::zero() { t0 } else { t1 };
+                let hit = other_ray._ori + other_ray._dir * t;
+                let q = self.get_closest_point(&hit);
+                Ok(Some(Contact::init_point(hit, self.get_direction(&q, &hit))))
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                let n = other_plane._normal;
+                let s_a = (self._a - other_plane._offset).inner(&n);
+                let s_b = (self._b - other_plane._offset).inner(&n);
+                let (s_min, p_min) = if s_a <= s_b {
+                    (s_a, self._a)
+                } else {
+                    (s_b, self._b)
+                };
+                let (s_max, p_max) = if s_a <= s_b {
+                    (s_b, self._b)
+                } else {
+                    (s_a, self._a)
+                };
+                if s_min > self._radius || s_max < -self._radius {
+                    return Ok(None);
+                }
+                //the plane is pushed towards the side holding the lesser part of the capsule
+                let (p, s, normal) = if s_min + s_max >= T::zero() {
+                    (p_min, s_min, n * -T::one())
+                } else {
+                    (p_max, s_max, n)
+                };
+                Ok(Some(Contact {
+                    _point_a: p + normal * self._radius,
+                    _point_b: p - n * s,
+                    _normal: normal,
+                    _depth: self._radius + s * normal.inner(&n),
+                    _feature: None,
+                }))
+            }
+            ShapeType::Box => {
+                let other_box: &RectBox<T> = downcast(other)?;
+                let s = other_box._size;
+                let o = other_box._ori;
+                Ok(self.intersect_aabb(
+                    &[o[[0, 0]] - s, o[[1, 0]] - s, o[[2, 0]] - s],
+                    &[o[[0, 0]] + s, o[[1, 0]] + s, o[[2, 0]] + s],
+                    &other_box.get_corners(),
+                ))
+            }
+            ShapeType::Rect => {
+                let other_cuboid: &Cuboid<T> = downcast(other)?;
+                let s = other_cuboid._size;
+                let o = other_cuboid._ori;
+                Ok(self.intersect_aabb(
+                    &[o[[0, 0]] - s[0], o[[1, 0]] - s[1], o[[2, 0]] - s[2]],
+                    &[o[[0, 0]] + s[0], o[[1, 0]] + s[1], o[[2, 0]] + s[2]],
+                    &other_cuboid.get_corners(),
+                ))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() {
+            let end = if self._a.inner(v) >= self._b.inner(v) {
+                self._a
+            } else {
+                self._b
+            };
+            Some(end + v.normalize_l2() * self._radius)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for Capsule<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+#[test]
+fn test_capsule_support() {
+    let a = Capsule::init(&[0., 0., 0.], &[0., 2., 0.], 0.5);
+    assert_eq!(a._bound.bound_lower, [-0.5, -0.5, -0.5]);
+    assert_eq!(a._bound.bound_upper, [0.5, 2.5, 0.5]);
+    let s = a
+        .get_support(&Matrix::from([[0., 1., 0., 0.]]).t())
+        .expect("capsule support unexpected");
+    assert_eq!(s, Matrix::from([[0., 2.5, 0., 1.]]).t());
+
+    //works with gjk
+    let b = Sphere::init(&[0.9, 1., 0.], 0.5);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(true));
+    let b = Sphere::init(&[1.1, 1., 0.], 0.5);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(false));
+}
+#[test]
+fn test_intersect_capsule_point_sphere() {
+    let a = Capsule::init(&[0., 0., 0.], &[0., 2., 0.], 0.5);
+    let b = Point::init(&[0.3, 1., 0.]);
+    let c = a.get_intersect(&b).expect("capsule point unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.5, 1., 0., 1.]]).t(), 1e-12);
+    assert!(a.get_intersect(&Point::init(&[0., 2.6, 0.])).is_none());
+
+    let b = Sphere::init(&[0., 3., 0.], 0.6);
+    let c = a.get_intersect(&b).expect("capsule sphere unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("sphere capsule unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+}
+#[test]
+fn test_intersect_capsule_capsule() {
+    let a = Capsule::init(&[0., 0., 0.], &[0., 2., 0.], 0.5);
+    let b = Capsule::init(&[0.8, 1., -1.], &[0.8, 1., 1.], 0.5);
+    let c = a
+        .get_intersect(&b)
+        .expect("capsule capsule unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_b, &Matrix::from([[0.3, 1., 0., 1.]]).t(), 1e-12);
+
+    let b = Capsule::init(&[1.1, 1., -1.], &[1.1, 1., 1.], 0.5);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_capsule_ray() {
+    let a = Capsule::init(&[0., 0., 0.], &[0., 2., 0.], 0.5);
+    //body
+    let b = Ray::init(&[-5., 1., 0.], &[1., 0., 0.]);
+    let c = a.get_intersect(&b).expect("capsule ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-0.5, 1., 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+    //cap, along the axis
+    let b = Ray::init(&[0., 5., 0.], &[0., -1., 0.]);
+    let c = b.get_intersect(&a).expect("ray capsule unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 2.5, 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+    //starting inside
+    let b = Ray::init(&[0., 1., 0.], &[0., 0., 1.]);
+    let c = a.get_intersect(&b).expect("capsule ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 1., 0.5, 1.]]).t(), 1e-12);
+
+    let b = Ray::init(&[-5., 1., 0.6], &[1., 0., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+    let b = Ray::init(&[-5., 1., 0.], &[-1., 0., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_capsule_plane() {
+    let a = Capsule::init(&[0., 0., 0.], &[2., 1., 0.], 0.5);
+    let b = Plane::init(&[0., -0.3, 0.], &[0., 1., 0.]);
+    let c = a.get_intersect(&b).expect("capsule plane unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., -0.5, 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_b, &Matrix::from([[0., -0.3, 0., 1.]]).t(), 1e-12);
+
+    let b = Plane::init(&[0., -0.6, 0.], &[0., 1., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+    assert!(b.get_intersect(&a).is_none());
+}
+#[test]
+fn test_intersect_capsule_box() {
+    let a = Capsule::init(&[-3., 1.3, 0.], &[3., 1.3, 0.], 0.5);
+    let b = RectBox::init(&[0., 0., 0.], 1.);
+    let c = a.get_intersect(&b).expect("capsule box unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("box capsule unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+
+    //segment closest to a box edge
+    let a = Capsule::init(&[1.3, 1.3, -3.], &[1.3, 1.3, 3.], 0.5);
+    let c = a.get_intersect(&b).expect("capsule box unexpected miss");
+    assert!((c._depth - (0.5 - 0.3 * 2f64.sqrt())).abs() < 1e-12);
+    let a = Capsule::init(&[1.4, 1.4, -3.], &[1.4, 1.4, 3.], 0.5);
+    assert!(a.get_intersect(&b).is_none());
+
+    //segment through the box
+    let a = Capsule::init(&[-3., 0.8, 0.], &[3., 0.8, 0.], 0.5);
+    let c = a.get_intersect(&b).expect("capsule box unexpected miss");
+    assert!((c._depth - 0.7).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-1., 0.3, 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_b, &Matrix::from([[-1., 1., 0., 1.]]).t(), 1e-12);
+    //segment ending inside the box
+    let a = Capsule::init(&[0.7, 0., 0.2], &[3., 0., 0.2], 0.5);
+    let c = a.get_intersect(&b).expect("capsule box unexpected miss");
+    assert!((c._depth - 0.8).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("box capsule unexpected miss");
+    assert!((c._depth - 0.8).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+
+    let a = Capsule::init(&[0., 2., 0.], &[0., 4., 0.], 0.5);
+    let b = Cuboid::init(&[0., 0., 0.], &[1., 1.6, 1.]);
+    let c = a.get_intersect(&b).expect("capsule cuboid unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-12);
+}
+#[test]
+fn test_try_intersect_capsule_degenerate() {
+    let a = Capsule::init(&[0., 0., 0.], &[0., 2., 0.], -0.5);
+    let b = Point::init(&[0., 0., 0.]);
+    assert_eq!(
+        a.try_intersect(&b).err(),
+        Some(CollideError::DegenerateShape(ShapeType::Capsule))
+    );
+}
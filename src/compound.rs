@@ -0,0 +1,448 @@
+use crate::bound::{Bound, BoundType};
+use crate::bvh::Bvh;
+use crate::capsule::*;
+use crate::contact::Contact;
+use crate::dispatch;
+use crate::error::CollideError;
+use crate::line::*;
+use crate::plane::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::shape::*;
+use crate::sphere::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use lightmatrix::quatr::QuatR;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+#[cfg(test)]
+use crate::cuboid::*;
+#[cfg(test)]
+use crate::rect_box::*;
+
+///shape placed in the frame of a compound, given in its own local coordinates
+pub struct CompoundChild<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    pub _shape: Box<dyn Shape<T>>,
+    pub _ori: Matrix<T, 4, 1>,
+    pub _rot: QuatR<T>,
+    pub _axes: [Matrix<T, 4, 1>; 3],
+    pub _bound: AxisAlignedBBox<T>,
+}
+
+impl<T> CompoundChild<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    pub fn init(shape: Box<dyn Shape<T>>, offset: &[T], rot: QuatR<T>) -> CompoundChild<T> {
+        assert!(offset.len() == 3);
+        let m = rot.to_matrix();
+        let mut axes = [Matrix::zero(); 3];
+        for (j, axis) in axes.iter_mut().enumerate() {
+            *axis = Matrix::from([[m[[0, j]], m[[1, j]], m[[2, j]], T::zero()]]).t();
+        }
+        let mut child = CompoundChild {
+            _shape: shape,
+            _ori: Matrix::from([[offset[0], offset[1], offset[2], T::one()]]).t(),
+            _rot: rot,
+            _axes: axes,
+            _bound: AxisAlignedBBox::default(),
+        };
+        child._bound = child.get_world_bound();
+        child
+    }
+
+    ///coordinates of a world point in the child's frame
+    pub fn get_local(&self, p: &Matrix<T, 4, 1>) -> [T; 3] {
+        let d = *p - self._ori;
+        [
+            d.inner(&self._axes[0]),
+            d.inner(&self._axes[1]),
+            d.inner(&self._axes[2]),
+        ]
+    }
+
+    ///coordinates of a world direction in the child's frame
+    pub fn get_local_dir(&self, v: &Matrix<T, 4, 1>) -> [T; 3] {
+        [
+            v.inner(&self._axes[0]),
+            v.inner(&self._axes[1]),
+            v.inner(&self._axes[2]),
+        ]
+    }
+
+    ///world point or direction from a homogeneous vector in the child's frame
+    pub fn get_world(&self, l: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        let v = self._axes[0] * l[[0, 0]] + self._axes[1] * l[[1, 0]] + self._axes[2] * l[[2, 0]];
+        if l[[3, 0]] == T::zero() {
+            v
+        } else {
+            self._ori + v
+        }
+    }
+
+    ///world bound enclosing the transformed bound of the shape
+    fn get_world_bound(&self) -> AxisAlignedBBox<T> {
+        let b = self._shape.get_bound().get_bound_data();
+        let (lower, upper) = match self._shape.get_bound().get_type() {
+            BoundType::AxisAlignBox => ([b[0], b[1], b[2]], [b[3], b[4], b[5]]),
+            BoundType::Sphere => (
+                [b[0] - b[3], b[1] - b[3], b[2] - b[3]],
+                [b[0] + b[3], b[1] + b[3], b[2] + b[3]],
+            ),
+        };
+        if lower.iter().chain(upper.iter()).any(|x| !x.is_finite()) {
+            return AxisAlignedBBox::default();
+        }
+        let mut bound = AxisAlignedBBox {
+            bound_lower: [T::infinity(); 3],
+            bound_upper: [T::neg_infinity(); 3],
+        };
+        for i in 0..8 {
+            let mut c = Matrix::from([[T::zero(), T::zero(), T::zero(), T::one()]]).t();
+            for axis in 0..3 {
+                c[[axis, 0]] = if i & (1 << axis) == 0 {
+                    lower[axis]
+                } else {
+                    upper[axis]
+                };
+            }
+            let w = self.get_world(&c);
+            for axis in 0..3 {
+                bound.bound_lower[axis] = bound.bound_lower[axis].min(w[[axis, 0]]);
+                bound.bound_upper[axis] = bound.bound_upper[axis].max(w[[axis, 0]]);
+            }
+        }
+        bound
+    }
+
+    ///copy of a shape defined only by points, directions and radii, moved into the child's frame
+    fn get_local_shape(&self, other: &dyn Shape<T>) -> Result<Box<dyn Shape<T>>, CollideError> {
+        Ok(match other.get_type() {
+            ShapeType::Point => {
+                let s: &Point<T> = downcast(other)?;
+                Box::new(Point::init(&self.get_local(&s._ori)))
+            }
+            ShapeType::Ray => {
+                let s: &Ray<T> = downcast(other)?;
+                Box::new(Ray::init(
+                    &self.get_local(&s._ori),
+                    &self.get_local_dir(&s._dir),
+                ))
+            }
+            ShapeType::Sphere => {
+                let s: &Sphere<T> = downcast(other)?;
+                Box::new(Sphere::init(&self.get_local(&s._ori), s._radius))
+            }
+            ShapeType::Plane => {
+                let s: &Plane<T> = downcast(other)?;
+                Box::new(Plane::init(
+                    &self.get_local(&s._offset),
+                    &self.get_local_dir(&s._normal),
+                ))
+            }
+            ShapeType::Line => {
+                let s: &Line<T> = downcast(other)?;
+                Box::new(Line::init(&self.get_local(&s._a), &self.get_local(&s._b)))
+            }
+            ShapeType::Capsule => {
+                let s: &Capsule<T> = downcast(other)?;
+                Box::new(Capsule::init(
+                    &self.get_local(&s._a),
+                    &self.get_local(&s._b),
+                    s._radius,
+                ))
+            }
+            _ => {
+                return Err(CollideError::UnsupportedPair(
+                    self.get_type(),
+                    other.get_type(),
+                ))
+            }
+        })
+    }
+}
+
+impl<T> AnyBase for CompoundChild<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for CompoundChild<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Complex
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point
+            | ShapeType::Ray
+            | ShapeType::Sphere
+            | ShapeType::Plane
+            | ShapeType::Line
+            | ShapeType::Capsule => {
+                //test in the child's frame, then move the contact back
+                let local = self.get_local_shape(other)?;
+                Ok(
+                    dispatch::try_intersect(&*self._shape, &*local)?.map(|c| Contact {
+                        _point_a: self.get_world(&c._point_a),
+                        _point_b: self.get_world(&c._point_b),
+                        _normal: self.get_world(&c._normal),
+                        _depth: c._depth,
+                        _feature: c._feature,
+                    }),
+                )
+            }
+            ShapeType::Complex => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+            _ => dispatch::try_intersect_convex(self, other),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        let l = self.get_local_dir(v);
+        let local_v = Matrix::from([[l[0], l[1], l[2], T::zero()]]).t();
+        self._shape
+            .get_support(&local_v)
+            .map(|p| self.get_world(&p))
+    }
+}
+
+impl<T> Vicinity<T> for CompoundChild<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._shape.set_vicinity(epsilon);
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        self._shape.within_vicinity(a, b)
+    }
+}
+
+///rigid group of shapes, each placed with an offset and rotation, with a bounding volume hierarchy over them
+pub struct Compound<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    pub _children: Vec<CompoundChild<T>>,
+    pub _bvh: Bvh<T>,
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+impl<T> Compound<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    pub fn init(children: Vec<CompoundChild<T>>) -> Compound<T> {
+        let bounds: Vec<AxisAlignedBBox<T>> = children.iter().map(|c| c._bound).collect();
+        let bvh = Bvh::init(&bounds);
+        Compound {
+            _children: children,
+            _bound: bvh.get_bound().cloned().unwrap_or_default(),
+            _bvh: bvh,
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+}
+
+impl<T> AnyBase for Compound<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for Compound<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Complex
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    //the contact's feature is the index of the child hit
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._children.is_empty() {
+            return Err(CollideError::DegenerateShape(ShapeType::Complex));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Ray => {
+                //nearest child along the ray
+                let other_ray: &Ray<T> = downcast(other)?;
+                let (ori, dir) = (other_ray._ori, other_ray._dir);
+                let mut err = None;
+                let hit = self._bvh.query_ray(
+                    &[ori[[0, 0]], ori[[1, 0]], ori[[2, 0]]],
+                    &[dir[[0, 0]], dir[[1, 0]], dir[[2, 0]]],
+                    |i| match dispatch::try_intersect(&self._children[i], other) {
+                        Ok(Some(c)) => Some(((c._point_a - ori).inner(&dir), c)),
+                        Ok(None) => None,
+                        Err(e) => {
+                            err = Some(e);
+                            None
+                        }
+                    },
+                );
+                if let Some(e) = err {
+                    return Err(e);
+                }
+                Ok(hit.map(|(_, i, c)| c.with_feature(i)))
+            }
+            _ => {
+                //deepest contact among children overlapping the other shape's bound
+                let mut best: Option<Contact<T>> = None;
+                for i in self._bvh.query_bound(other.get_bound()) {
+                    if let Some(c) = dispatch::try_intersect(&self._children[i], other)? {
+                        match best {
+                            Some(ref b) if b._depth >= c._depth => {}
+                            _ => best = Some(c.with_feature(i)),
+                        }
+                    }
+                }
+                Ok(best)
+            }
+        }
+    }
+    fn get_support(&self, _v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        //not convex in general
+        None
+    }
+}
+
+impl<T> Vicinity<T> for Compound<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+        for c in self._children.iter_mut() {
+            c.set_vicinity(epsilon);
+        }
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+///a table: box top on a capsule leg lying along x, turned 90 degrees about y and moved to x = 10
+#[cfg(test)]
+fn table() -> Compound<f64> {
+    let rot = QuatR::init_from_axis_angle_degree(Matrix::from([[0., 1., 0.]]).t(), 90.);
+    Compound::init(vec![
+        CompoundChild::init(
+            Box::new(Cuboid::init(&[0., 0., 0.], &[1., 1., 2.])),
+            &[10., 2., 0.],
+            rot.clone(),
+        ),
+        CompoundChild::init(
+            Box::new(Capsule::init(&[0., 0., 0.], &[0., 0., 2.], 0.25)),
+            &[10., 0., 0.],
+            rot,
+        ),
+    ])
+}
+
+#[test]
+fn test_compound_bound() {
+    let a = table();
+    assert!(a._bvh._nodes.len() == 1);
+    //capsule along local z ends up along world x
+    let b = &a._children[1]._bound;
+    assert!((b.bound_lower[0] - 9.75).abs() < 1e-12);
+    assert!((b.bound_upper[0] - 12.25).abs() < 1e-12);
+    assert!((b.bound_upper[2] - 0.25).abs() < 1e-12);
+    assert!((a._bound.bound_lower[1] + 0.25).abs() < 1e-12);
+    assert!((a._bound.bound_upper[1] - 3.).abs() < 1e-12);
+    assert!((a._bound.bound_upper[0] - 12.25).abs() < 1e-12);
+}
+#[test]
+fn test_intersect_compound() {
+    let a = table();
+    //sphere resting on the top
+    let b = Sphere::init(&[10., 3.4, 0.], 0.5);
+    let c = a
+        .get_intersect(&b)
+        .expect("compound sphere unexpected miss");
+    assert_eq!(c._feature, Some(0));
+    assert!((c._depth - 0.1).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+
+    //sphere touching the far end of the capsule
+    let b = Sphere::init(&[12.5, 0., 0.], 0.5);
+    let c = b
+        .get_intersect(&a)
+        .expect("sphere compound unexpected miss");
+    assert_eq!(c._feature, Some(1));
+    assert!((c._depth - 0.25).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+    assert!(a
+        .get_intersect(&Sphere::init(&[13., 2., 0.], 0.5))
+        .is_none());
+
+    //box against the top goes through gjk
+    let b = RectBox::init(&[10., 3.8, 0.], 1.);
+    let c = a.get_intersect(&b).expect("compound box unexpected miss");
+    assert_eq!(c._feature, Some(0));
+    assert!((c._depth - 0.2).abs() < 1e-6);
+}
+#[test]
+fn test_intersect_compound_ray() {
+    let a = table();
+    let b = Ray::init(&[10.5, 10., 0.], &[0., -1., 0.]);
+    let c = a.get_intersect(&b).expect("compound ray unexpected miss");
+    assert_eq!(c._feature, Some(0));
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[10.5, 3., 0., 1.]]).t(), 1e-12);
+    //below the top only the capsule is in the way
+    let b = Ray::init(&[11.5, -5., 0.], &[0., 1., 0.]);
+    let c = a.get_intersect(&b).expect("compound ray unexpected miss");
+    assert_eq!(c._feature, Some(1));
+    assert_matrix_approx_eq_float(
+        &c._point_a,
+        &Matrix::from([[11.5, -0.25, 0., 1.]]).t(),
+        1e-12,
+    );
+    assert!(a
+        .get_intersect(&Ray::init(&[13., -5., 0.], &[0., 1., 0.]))
+        .is_none());
+}
+#[test]
+fn test_try_intersect_compound_degenerate() {
+    let a: Compound<f64> = Compound::init(vec![]);
+    assert_eq!(
+        a.try_intersect(&Point::init(&[0., 0., 0.])).err(),
+        Some(CollideError::DegenerateShape(ShapeType::Complex))
+    );
+}
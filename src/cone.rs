@@ -0,0 +1,328 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::cylinder::{get_axial_radial, get_disc_extent, get_radial_direction};
+use crate::error::CollideError;
+use crate::point::*;
+use crate::ray::*;
+use crate::shape::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+///solid cone with its tip at _apex, opening along _axis to a base disc of _radius at _height
+#[derive(Debug, Clone)]
+pub struct Cone<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _apex: Matrix<T, 4, 1>,
+    pub _axis: Matrix<T, 4, 1>,
+    pub _height: T,
+    pub _radius: T,
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+impl<T> Cone<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub fn init(apex: &[T], axis: &[T], height: T, radius: T) -> Cone<T> {
+        assert!(apex.len() == 3);
+        assert!(axis.len() == 3);
+        let apex = Matrix::from([[apex[0], apex[1], apex[2], T::one()]]).t();
+        let axis = Matrix::from([[axis[0], axis[1], axis[2], T::zero()]])
+            .t()
+            .normalize_l2();
+        let base = apex + axis * height;
+        let disc = get_disc_extent(&axis, radius);
+        let mut vals = [T::zero(); 6];
+        for i in 0..3 {
+            vals[i] = apex[[i, 0]].min(base[[i, 0]] - disc[i]);
+            vals[i + 3] = apex[[i, 0]].max(base[[i, 0]] + disc[i]);
+        }
+        Cone {
+            _apex: apex,
+            _axis: axis,
+            _height: height,
+            _radius: radius,
+            _bound: AxisAlignedBBox::new(ShapeType::Rect, &vals),
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+
+    pub fn get_base(&self) -> Matrix<T, 4, 1> {
+        self._apex + self._axis * self._height
+    }
+
+    pub fn contains(&self, p: &Matrix<T, 4, 1>) -> bool {
+        let (s, radial) = get_axial_radial(&self._apex, &self._axis, p);
+        s >= T::zero() && s <= self._height && radial.norm_l2() * self._height <= s * self._radius
+    }
+
+    ///distance to the base and to the slanted side, and the outward normal of the nearer one
+    fn get_nearest_surface(&self, p: &Matrix<T, 4, 1>) -> (T, Matrix<T, 4, 1>) {
+        let (s, radial) = get_axial_radial(&self._apex, &self._axis, p);
+        let slant = (self._height * self._height + self._radius * self._radius).sqrt();
+        let d_base = self._height - s;
+        let d_side = (s * self._radius - radial.norm_l2() * self._height) / slant;
+        if d_base <= d_side {
+            return (d_base, self._axis);
+        }
+        let w = if radial.norm_l2() != T::zero() {
+            radial.normalize_l2()
+        } else if s <= T::zero() {
+            //at the tip
+            return (d_side, self._axis * -T::one());
+        } else {
+            let x = Matrix::from([[T::one(), T::zero(), T::zero(), T::zero()]]).t();
+            let y = Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t();
+            get_radial_direction(&self._axis, &x)
+                .or_else(|| get_radial_direction(&self._axis, &y))
+                .unwrap()
+        };
+        (
+            d_side,
+            (w * self._height - self._axis * self._radius) / slant,
+        )
+    }
+
+    ///outward surface normal at a point on the surface
+    pub fn get_normal(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        self.get_nearest_surface(p).1
+    }
+
+    ///parameter interval of ray ori + dir * t inside of the cone
+    pub fn intersect_ray(&self, ori: &Matrix<T, 4, 1>, dir: &Matrix<T, 4, 1>) -> Option<(T, T)> {
+        let k = self._radius / self._height;
+        let m = *ori - self._apex;
+        let (mu, du) = (m.inner(&self._axis), dir.inner(&self._axis));
+        let m_perp = m - self._axis * mu;
+        let d_perp = *dir - self._axis * du;
+
+        //a line crosses the boundary of a convex shape at most twice
+        let mut interval: Option<(T, T)> = None;
+        let mut add = |t: T| {
+            interval = match interval {
+                Some((lo, hi)) => Some((lo.min(t), hi.max(t))),
+                _ => Some((t, t)),
+            };
+        };
+
+        //slanted side, restricted to the nappe between tip and base
+        let a = d_perp.inner(&d_perp) - k * k * du * du;
+        let b = m_perp.inner(&d_perp) - k * k * mu * du;
+        let c = m_perp.inner(&m_perp) - k * k * mu * mu;
+        let mut roots = [None, None];
+        if a.abs() > T::epsilon() {
+            let disc = b * b - a * c;
+            if disc >= T::zero() {
+                roots = [Some((-b - disc.sqrt()) / a), Some((-b + disc.sqrt()) / a)];
+            }
+        } else if b != T::zero() {
+            roots[0] = Some(-c / (T::from(2.).unwrap() * b));
+        }
+        for t in roots.iter().flatten() {
+            let s = mu + *t * du;
+            if s >= T::zero() && s <= self._height {
+                add(*t);
+            }
+        }
+
+        //base
+        if du != T::zero() {
+            let t = (self._height - mu) / du;
+            if (m_perp + d_perp * t).norm_l2() <= self._radius {
+                add(t);
+            }
+        }
+        interval
+    }
+}
+
+impl<T> AnyBase for Cone<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for Cone<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Cone
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if !self._axis[[0, 0]].is_finite()
+            || self._radius.is_nan()
+            || self._radius < T::zero()
+            || self._height.is_nan()
+            || self._height <= T::zero()
+        {
+            return Err(CollideError::DegenerateShape(ShapeType::Cone));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let p = other_point._ori;
+                if !self.contains(&p) {
+                    return Ok(None);
+                }
+                //push the point out through the nearest of the base or the side
+                let (depth, n) = self.get_nearest_surface(&p);
+                Ok(Some(Contact {
+                    _point_a: p + n * depth,
+                    _point_b: p,
+                    _normal: n,
+                    _depth: depth,
+                    _feature: None,
+                }))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                let (t0, t1) = match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                if t1 < T::zero() {
+                    return Ok(None);
+                }
+                //ray starting inside of the cone hits on its way out
+                let t = if t0 >= T::zero() { t0 } else { t1 };
+                let hit = other_ray._ori + other_ray._dir * t;
+                Ok(Some(Contact::init_point(hit, self.get_normal(&hit))))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() {
+            let rim = match get_radial_direction(&self._axis, v) {
+                Some(w) => self.get_base() + w * self._radius,
+                _ => self.get_base(),
+            };
+            if self._apex.inner(v) > rim.inner(v) {
+                Some(self._apex)
+            } else {
+                Some(rim)
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for Cone<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+#[test]
+fn test_cone_bound_support() {
+    let a = Cone::init(&[0., 0., 2.], &[0., 0., -1.], 2., 1.);
+    assert_eq!(a._bound.bound_lower, [-1., -1., 0.]);
+    assert_eq!(a._bound.bound_upper, [1., 1., 2.]);
+    let s = a
+        .get_support(&Matrix::from([[0., 0., 1., 0.]]).t())
+        .expect("cone support unexpected");
+    assert_eq!(s, Matrix::from([[0., 0., 2., 1.]]).t());
+    let s = a
+        .get_support(&Matrix::from([[1., 0., 0., 0.]]).t())
+        .expect("cone support unexpected");
+    assert_eq!(s, Matrix::from([[1., 0., 0., 1.]]).t());
+
+    let b = crate::sphere::Sphere::init(&[0.6, 0., 1.], 0.2);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(true));
+    let b = crate::sphere::Sphere::init(&[0.9, 0., 1.5], 0.2);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(false));
+}
+#[test]
+fn test_intersect_cone_point() {
+    let a = Cone::init(&[0., 0., 2.], &[0., 0., -1.], 2., 1.);
+    let c = a
+        .get_intersect(&Point::init(&[0., 0., 0.1]))
+        .expect("cone point unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-12);
+    let c = a
+        .get_intersect(&Point::init(&[0.4, 0., 1.]))
+        .expect("cone point unexpected miss");
+    let s = 5f64.sqrt();
+    assert!((c._depth - 0.2 / s).abs() < 1e-12);
+    assert_matrix_approx_eq_float(
+        &c._normal,
+        &Matrix::from([[2. / s, 0., 1. / s, 0.]]).t(),
+        1e-12,
+    );
+    //inside of the bound but outside of the slanted side
+    assert!(a.get_intersect(&Point::init(&[0.6, 0., 1.])).is_none());
+}
+#[test]
+fn test_intersect_cone_ray() {
+    let a = Cone::init(&[0., 0., 2.], &[0., 0., -1.], 2., 1.);
+    //side
+    let b = Ray::init(&[-5., 0., 1.], &[1., 0., 0.]);
+    let c = a.get_intersect(&b).expect("cone ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-0.5, 0., 1., 1.]]).t(), 1e-12);
+    let s = 5f64.sqrt();
+    assert_matrix_approx_eq_float(
+        &c._normal,
+        &Matrix::from([[-2. / s, 0., 1. / s, 0.]]).t(),
+        1e-12,
+    );
+    //base
+    let b = Ray::init(&[0.5, 0., -5.], &[0., 0., 1.]);
+    let c = b.get_intersect(&a).expect("ray cone unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.5, 0., 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-12);
+    //down the axis onto the tip
+    let b = Ray::init(&[0., 0., 5.], &[0., 0., -1.]);
+    let c = a.get_intersect(&b).expect("cone ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 0., 2., 1.]]).t(), 1e-12);
+    //starting inside
+    let b = Ray::init(&[0., 0., 1.], &[0., 1., 0.]);
+    let c = a.get_intersect(&b).expect("cone ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 0.5, 1., 1.]]).t(), 1e-12);
+
+    //would hit the other nappe of the infinite cone
+    assert!(a
+        .get_intersect(&Ray::init(&[-5., 0., 3.], &[1., 0., -0.1]))
+        .is_none());
+    assert!(a
+        .get_intersect(&Ray::init(&[0.6, 0., 1.], &[0., 1., 0.]))
+        .is_none());
+}
+#[test]
+fn test_try_intersect_cone_degenerate() {
+    let a = Cone::init(&[0., 0., 0.], &[0., 0., 1.], 0., 1.);
+    assert_eq!(
+        a.try_intersect(&Point::init(&[0., 0., 0.])).err(),
+        Some(CollideError::DegenerateShape(ShapeType::Cone))
+    );
+}
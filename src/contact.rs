@@ -0,0 +1,66 @@
+use lightmatrix::matrix::*;
+use num_traits::{Float, NumAssign};
+
+///result of an intersection query between a first shape a and a second shape b
+#[derive(Debug, Clone, Copy)]
+pub struct Contact<T: NumAssign + Copy + Default + Float> {
+    ///contact point on shape a
+    pub _point_a: Matrix<T, 4, 1>,
+    ///contact point on shape b
+    pub _point_b: Matrix<T, 4, 1>,
+    ///unit normal pointing from shape a towards shape b, zero if undefined for the pair
+    pub _normal: Matrix<T, 4, 1>,
+    ///signed penetration depth along the normal, negative values are separation distances
+    pub _depth: T,
+    ///optional sub feature of the shapes involved, eg: facet index
+    pub _feature: Option<usize>,
+}
+
+impl<T> Contact<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///contact at a single point shared by both shapes
+    pub fn init_point(point: Matrix<T, 4, 1>, normal: Matrix<T, 4, 1>) -> Contact<T> {
+        Contact {
+            _point_a: point,
+            _point_b: point,
+            _normal: normal,
+            _depth: T::zero(),
+            _feature: None,
+        }
+    }
+
+    pub fn with_feature(mut self, feature: usize) -> Contact<T> {
+        self._feature = Some(feature);
+        self
+    }
+
+    ///contact as seen from shape b
+    pub fn flip(&self) -> Contact<T> {
+        Contact {
+            _point_a: self._point_b,
+            _point_b: self._point_a,
+            _normal: self._normal * -T::one(),
+            _depth: self._depth,
+            _feature: self._feature,
+        }
+    }
+}
+
+#[test]
+fn test_contact_flip() {
+    let c = Contact {
+        _point_a: Matrix::from([[1f64, 0f64, 0f64, 1f64]]).t(),
+        _point_b: Matrix::from([[2f64, 0f64, 0f64, 1f64]]).t(),
+        _normal: Matrix::from([[1f64, 0f64, 0f64, 0f64]]).t(),
+        _depth: -1f64,
+        _feature: Some(3),
+    };
+    let f = c.flip();
+    assert_eq!(f._point_a, c._point_b);
+    assert_eq!(f._point_b, c._point_a);
+    assert_eq!(f._normal, Matrix::from([[-1f64, 0f64, 0f64, 0f64]]).t());
+    assert_eq!(f._depth, c._depth);
+    assert_eq!(f._feature, Some(3));
+}
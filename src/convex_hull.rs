@@ -0,0 +1,604 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::dispatch;
+use crate::error::CollideError;
+use crate::plane::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::shape::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+use std::collections::{HashMap, HashSet};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+///convex polyhedron with triangulated faces, ccw when viewed from outside
+#[derive(Debug, Clone)]
+pub struct ConvexHull<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _vertices: Vec<Matrix<T, 4, 1>>,
+    pub _faces: Vec<[usize; 3]>,
+    pub _normals: Vec<Matrix<T, 4, 1>>,
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+///face of the hull under construction along with the points lying outside of it
+struct HullFace<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    _v: [usize; 3],
+    _normal: Matrix<T, 4, 1>,
+    _offset: T,
+    _outside: Vec<usize>,
+    _alive: bool,
+}
+
+impl<T> HullFace<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn init(pts: &[Matrix<T, 4, 1>], v: [usize; 3]) -> HullFace<T> {
+        let normal = (pts[v[1]] - pts[v[0]])
+            .cross(&(pts[v[2]] - pts[v[0]]))
+            .normalize_l2();
+        HullFace {
+            _v: v,
+            _normal: normal,
+            _offset: normal.inner(&pts[v[0]]),
+            _outside: vec![],
+            _alive: true,
+        }
+    }
+    fn get_distance(&self, p: &Matrix<T, 4, 1>) -> T {
+        self._normal.inner(p) - self._offset
+    }
+}
+
+///quickhull, returns faces indexing into pts or None if the points do not span a volume
+pub fn quickhull<T>(pts: &[Matrix<T, 4, 1>]) -> Option<Vec<[usize; 3]>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    if pts.len() < 4 {
+        return None;
+    }
+
+    //tolerance scaled by the extent of the cloud
+    let mut max_abs = [T::zero(); 3];
+    for p in pts.iter() {
+        for (i, x) in max_abs.iter_mut().enumerate() {
+            *x = x.max(p[[i, 0]].abs());
+        }
+    }
+    let eps = T::from(3.).unwrap() * T::epsilon() * (max_abs[0] + max_abs[1] + max_abs[2]);
+
+    //initial simplex from the extreme points
+    let mut extremes = [0; 6];
+    for (i, p) in pts.iter().enumerate() {
+        for axis in 0..3 {
+            if p[[axis, 0]] < pts[extremes[axis * 2]][[axis, 0]] {
+                extremes[axis * 2] = i;
+            }
+            if p[[axis, 0]] > pts[extremes[axis * 2 + 1]][[axis, 0]] {
+                extremes[axis * 2 + 1] = i;
+            }
+        }
+    }
+    let mut simplex = [0; 4];
+    let mut best = T::zero();
+    for a in extremes.iter() {
+        for b in extremes.iter() {
+            let d = (pts[*a] - pts[*b]).norm_l2();
+            if d > best {
+                best = d;
+                simplex[0] = *a;
+                simplex[1] = *b;
+            }
+        }
+    }
+    if best <= eps {
+        return None;
+    }
+    let dir = (pts[simplex[1]] - pts[simplex[0]]).normalize_l2();
+    best = T::zero();
+    for (i, p) in pts.iter().enumerate() {
+        let m = *p - pts[simplex[0]];
+        let d = (m - dir * m.inner(&dir)).norm_l2();
+        if d > best {
+            best = d;
+            simplex[2] = i;
+        }
+    }
+    if best <= eps {
+        return None;
+    }
+    let n = (pts[simplex[1]] - pts[simplex[0]])
+        .cross(&(pts[simplex[2]] - pts[simplex[0]]))
+        .normalize_l2();
+    best = T::zero();
+    for (i, p) in pts.iter().enumerate() {
+        let d = (*p - pts[simplex[0]]).inner(&n).abs();
+        if d > best {
+            best = d;
+            simplex[3] = i;
+        }
+    }
+    if best <= eps {
+        return None;
+    }
+
+    //orient faces of the simplex away from its centroid, which stays inside of the hull
+    let centroid = (pts[simplex[0]] + pts[simplex[1]] + pts[simplex[2]] + pts[simplex[3]])
+        * T::from(0.25).unwrap();
+    let mut faces: Vec<HullFace<T>> = vec![];
+    let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+    for [a, b, c] in [[0, 1, 2], [0, 3, 1], [1, 3, 2], [2, 3, 0]].iter() {
+        let mut v = [simplex[*a], simplex[*b], simplex[*c]];
+        if HullFace::init(pts, v).get_distance(&centroid) > T::zero() {
+            v.swap(1, 2);
+        }
+        for k in 0..3 {
+            edges.insert((v[k], v[(k + 1) % 3]), faces.len());
+        }
+        faces.push(HullFace::init(pts, v));
+    }
+    for (i, p) in pts.iter().enumerate() {
+        if simplex.contains(&i) {
+            continue;
+        }
+        if let Some(f) = faces.iter_mut().find(|f| f.get_distance(p) > eps) {
+            f._outside.push(i);
+        }
+    }
+
+    while let Some(fi) = faces
+        .iter()
+        .position(|f| f._alive && !f._outside.is_empty())
+    {
+        //furthest point of the face's outside set
+        let eye = *faces[fi]
+            ._outside
+            .iter()
+            .max_by(|a, b| {
+                let da = faces[fi].get_distance(&pts[**a]);
+                let db = faces[fi].get_distance(&pts[**b]);
+                da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap();
+
+        //connected set of faces visible from the eye point and the boundary of it
+        let mut visible = vec![fi];
+        let mut visited: HashSet<usize> = [fi].iter().cloned().collect();
+        let mut stack = vec![fi];
+        let mut horizon = vec![];
+        while let Some(f) = stack.pop() {
+            let v = faces[f]._v;
+            for k in 0..3 {
+                let (a, b) = (v[k], v[(k + 1) % 3]);
+                let g = edges[&(b, a)];
+                if visited.contains(&g) {
+                    continue;
+                }
+                if faces[g].get_distance(&pts[eye]) > eps {
+                    visited.insert(g);
+                    visible.push(g);
+                    stack.push(g);
+                } else {
+                    horizon.push((a, b));
+                }
+            }
+        }
+
+        let mut orphans = vec![];
+        for f in visible.iter() {
+            faces[*f]._alive = false;
+            orphans.append(&mut faces[*f]._outside);
+            let v = faces[*f]._v;
+            for k in 0..3 {
+                edges.remove(&(v[k], v[(k + 1) % 3]));
+            }
+        }
+
+        //cone of new faces from the horizon to the eye point
+        let first_new = faces.len();
+        for (a, b) in horizon.iter() {
+            let v = [*a, *b, eye];
+            for k in 0..3 {
+                edges.insert((v[k], v[(k + 1) % 3]), faces.len());
+            }
+            faces.push(HullFace::init(pts, v));
+        }
+        for i in orphans.into_iter().filter(|i| *i != eye) {
+            if let Some(f) = faces[first_new..]
+                .iter_mut()
+                .find(|f| f.get_distance(&pts[i]) > eps)
+            {
+                f._outside.push(i);
+            }
+        }
+    }
+
+    Some(
+        faces
+            .into_iter()
+            .filter(|f| f._alive)
+            .map(|f| f._v)
+            .collect(),
+    )
+}
+
+impl<T> ConvexHull<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///builds the hull of a flat list of xyz coordinates
+    pub fn init(points: &[T]) -> ConvexHull<T> {
+        assert_eq!(points.len() % 3, 0);
+        let pts: Vec<Matrix<T, 4, 1>> = points
+            .chunks(3)
+            .map(|p| Matrix::from([[p[0], p[1], p[2], T::one()]]).t())
+            .collect();
+
+        //keep only the vertices referenced by hull faces
+        let mut vertices = vec![];
+        let mut faces = vec![];
+        let mut normals = vec![];
+        if let Some(hull) = quickhull(&pts) {
+            let mut remap = HashMap::new();
+            for f in hull.iter() {
+                let mut v = [0; 3];
+                for (k, i) in f.iter().enumerate() {
+                    v[k] = *remap.entry(*i).or_insert_with(|| {
+                        vertices.push(pts[*i]);
+                        vertices.len() - 1
+                    });
+                }
+                faces.push(v);
+                normals.push(HullFace::init(&pts, *f)._normal);
+            }
+        }
+
+        let mut vals = [
+            T::infinity(),
+            T::infinity(),
+            T::infinity(),
+            T::neg_infinity(),
+            T::neg_infinity(),
+            T::neg_infinity(),
+        ];
+        for v in vertices.iter() {
+            for i in 0..3 {
+                vals[i] = vals[i].min(v[[i, 0]]);
+                vals[i + 3] = vals[i + 3].max(v[[i, 0]]);
+            }
+        }
+        ConvexHull {
+            _vertices: vertices,
+            _faces: faces,
+            _normals: normals,
+            _bound: AxisAlignedBBox::new(ShapeType::Rect, &vals),
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+
+    ///signed distance of p to the plane of face i, positive outside
+    pub fn get_distance(&self, i: usize, p: &Matrix<T, 4, 1>) -> T {
+        (*p - self._vertices[self._faces[i][0]]).inner(&self._normals[i])
+    }
+
+    pub fn contains(&self, p: &Matrix<T, 4, 1>) -> bool {
+        (0..self._faces.len()).all(|i| self.get_distance(i, p) <= self._vicinity)
+    }
+
+    ///face with the greatest signed distance to p and the distance
+    fn get_nearest_face(&self, p: &Matrix<T, 4, 1>) -> (usize, T) {
+        let mut best = (0, T::neg_infinity());
+        for i in 0..self._faces.len() {
+            let d = self.get_distance(i, p);
+            if d > best.1 {
+                best = (i, d);
+            }
+        }
+        best
+    }
+
+    ///entering and leaving parameters and faces of ray ori + dir * t clipped by the face planes
+    pub fn intersect_ray(
+        &self,
+        ori: &Matrix<T, 4, 1>,
+        dir: &Matrix<T, 4, 1>,
+    ) -> Option<((T, usize), (T, usize))> {
+        let mut t_near = (T::neg_infinity(), 0);
+        let mut t_far = (T::infinity(), 0);
+        for (i, n) in self._normals.iter().enumerate() {
+            let dist = self.get_distance(i, ori);
+            let denom = n.inner(dir);
+            if denom == T::zero() {
+                if dist > self._vicinity {
+                    return None;
+                }
+                continue;
+            }
+            let t = -dist / denom;
+            if denom < T::zero() {
+                if t > t_near.0 {
+                    t_near = (t, i);
+                }
+            } else if t < t_far.0 {
+                t_far = (t, i);
+            }
+            if t_near.0 > t_far.0 {
+                return None;
+            }
+        }
+        Some((t_near, t_far))
+    }
+}
+
+impl<T> AnyBase for ConvexHull<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for ConvexHull<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::ConvexHull
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._faces.is_empty() {
+            return Err(CollideError::DegenerateShape(ShapeType::ConvexHull));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let p = other_point._ori;
+                if !self.contains(&p) {
+                    return Ok(None);
+                }
+                //push the point out through the nearest face
+                let (i, dist) = self.get_nearest_face(&p);
+                Ok(Some(Contact {
+                    _point_a: p - self._normals[i] * dist,
+                    _point_b: p,
+                    _normal: self._normals[i],
+                    _depth: -dist,
+                    _feature: Some(i),
+                }))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                let (near, far) = match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                if far.0 < T::zero() {
+                    return Ok(None);
+                }
+                //ray starting inside of the hull hits on its way out
+                let (t, i) = if near.0 >= T::zero() { near } else { far };
+                Ok(Some(
+                    Contact::init_point(other_ray._ori + other_ray._dir * t, self._normals[i])
+                        .with_feature(i),
+                ))
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                let n = other_plane._normal;
+                let dists: Vec<T> = self
+                    ._vertices
+                    .iter()
+                    .map(|v| (*v - other_plane._offset).inner(&n))
+                    .collect();
+                let (mut i_min, mut i_max) = (0, 0);
+                for (i, d) in dists.iter().enumerate() {
+                    if *d < dists[i_min] {
+                        i_min = i;
+                    }
+                    if *d > dists[i_max] {
+                        i_max = i;
+                    }
+                }
+                let (d_min, d_max) = (dists[i_min], dists[i_max]);
+                if d_min > T::zero() || d_max < T::zero() {
+                    return Ok(None);
+                }
+                //the plane is pushed towards the side holding the lesser part of the hull
+                let (i, d, normal) = if -d_min <= d_max {
+                    (i_min, d_min, n * -T::one())
+                } else {
+                    (i_max, d_max, n)
+                };
+                let p = self._vertices[i];
+                Ok(Some(Contact {
+                    _point_a: p,
+                    _point_b: p - n * d,
+                    _normal: normal,
+                    _depth: d.abs(),
+                    _feature: None,
+                }))
+            }
+            ShapeType::Sphere
+            | ShapeType::Box
+            | ShapeType::Rect
+            | ShapeType::OrientedBox
+            | ShapeType::TriPrism
+            | ShapeType::Trig
+            | ShapeType::ConvexPolygon
+            | ShapeType::Frustum
+            | ShapeType::Capsule
+            | ShapeType::Cylinder
+            | ShapeType::Cone
+            | ShapeType::Ellipsoid
+            | ShapeType::ConvexHull => dispatch::try_intersect_convex(self, other),
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() && !self._vertices.is_empty() {
+            let mut best = self._vertices[0];
+            for p in self._vertices.iter() {
+                if p.inner(v) > best.inner(v) {
+                    best = *p;
+                }
+            }
+            Some(best)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for ConvexHull<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+#[cfg(test)]
+fn cube_cloud() -> Vec<f64> {
+    let mut pts = vec![];
+    for i in 0..8 {
+        for axis in 0..3 {
+            pts.push(if i & (1 << axis) == 0 { -1. } else { 1. });
+        }
+    }
+    //interior, face and edge points, and a duplicate corner
+    pts.extend_from_slice(&[
+        0., 0., 0., 0.5, -0.2, 0.1, 1., 0., 0., 0., -1., 0.3, 1., 1., 0., 1., 1., 1.,
+    ]);
+    pts
+}
+
+#[test]
+fn test_convex_hull_build() {
+    let a = ConvexHull::init(&cube_cloud());
+    assert_eq!(a._vertices.len(), 8);
+    assert_eq!(a._faces.len(), 12);
+    assert_eq!(a._bound.bound_lower, [-1., -1., -1.]);
+    assert_eq!(a._bound.bound_upper, [1., 1., 1.]);
+    //every face is outward facing
+    for (f, n) in a._faces.iter().zip(a._normals.iter()) {
+        let center = (a._vertices[f[0]] + a._vertices[f[1]] + a._vertices[f[2]]) * (1. / 3.);
+        assert!(center.inner(n) > 0.);
+    }
+
+    //points scattered on a sphere all lie on the hull
+    let mut seed = 12345u64;
+    let mut rand = || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 11) as f64 / (1u64 << 53) as f64 * 2. - 1.
+    };
+    let mut pts = vec![];
+    for _ in 0..200 {
+        let p = Matrix::from([[rand(), rand(), rand(), 0.]])
+            .t()
+            .normalize_l2();
+        pts.extend_from_slice(&[p[[0, 0]], p[[1, 0]], p[[2, 0]]]);
+    }
+    let a = ConvexHull::init(&pts);
+    assert_eq!(a._vertices.len(), 200);
+    //euler characteristic of a closed triangulated surface
+    assert_eq!(a._faces.len(), 2 * 200 - 4);
+    for p in pts.chunks(3) {
+        assert!(a.contains(&Matrix::from([[p[0], p[1], p[2], 1.]]).t()));
+    }
+}
+#[test]
+fn test_convex_hull_support() {
+    let a = ConvexHull::init(&cube_cloud());
+    let s = a
+        .get_support(&Matrix::from([[1., -2., 3., 0.]]).t())
+        .expect("convex hull support unexpected");
+    assert_eq!(s, Matrix::from([[1., -1., 1., 1.]]).t());
+
+    let b = crate::sphere::Sphere::init(&[1.2, 1.2, 0.], 0.5);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(true));
+    let b = crate::sphere::Sphere::init(&[1.4, 1.4, 0.], 0.5);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(false));
+    let c = a.get_intersect(&crate::sphere::Sphere::init(&[1.3, 0., 0.], 0.5));
+    let c = c.expect("convex hull sphere unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-3);
+}
+#[test]
+fn test_intersect_convex_hull_point_ray() {
+    let a = ConvexHull::init(&cube_cloud());
+    let c = a
+        .get_intersect(&Point::init(&[0.2, 0.9, 0.]))
+        .expect("convex hull point unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+    assert!(a.get_intersect(&Point::init(&[0.2, 1.1, 0.])).is_none());
+
+    let b = Ray::init(&[-5., 0.2, 0.1], &[1., 0., 0.]);
+    let c = a
+        .get_intersect(&b)
+        .expect("convex hull ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-1., 0.2, 0.1, 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+    let b = Ray::init(&[0., 0., 0.], &[0., 0., 1.]);
+    let c = b
+        .get_intersect(&a)
+        .expect("ray convex hull unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 0., 1., 1.]]).t(), 1e-12);
+    assert!(a
+        .get_intersect(&Ray::init(&[-5., 1.2, 0.], &[1., 0., 0.]))
+        .is_none());
+    assert!(a
+        .get_intersect(&Ray::init(&[-5., 0., 0.], &[-1., 0., 0.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_convex_hull_plane() {
+    let a = ConvexHull::init(&cube_cloud());
+    let b = Plane::init(&[0., -0.8, 0.], &[0., 1., 0.]);
+    let c = a
+        .get_intersect(&b)
+        .expect("convex hull plane unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+    let b = Plane::init(&[0., -1.2, 0.], &[0., 1., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_try_intersect_convex_hull_degenerate() {
+    //coplanar points do not enclose a volume
+    let a = ConvexHull::init(&[0., 0., 0., 1., 0., 0., 0., 1., 0., 1., 1., 0., 0.5, 0.5, 0.]);
+    assert!(a._faces.is_empty());
+    assert_eq!(
+        a.try_intersect(&Point::init(&[0., 0., 0.])).err(),
+        Some(CollideError::DegenerateShape(ShapeType::ConvexHull))
+    );
+}
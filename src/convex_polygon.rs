@@ -0,0 +1,411 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::cuboid::*;
+use crate::error::CollideError;
+use crate::intersect_sat;
+use crate::line::*;
+use crate::oriented_box::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::rect_box::*;
+use crate::shape::*;
+use crate::sphere::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+///flat convex polygon bounded by its coplanar vertices
+#[derive(Debug, Clone)]
+pub struct ConvexPolygon<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///vertices in ccw order
+    pub _v: Vec<Matrix<T, 4, 1>>,
+
+    ///unit normal following the ccw winding of the vertices
+    pub _normal: Matrix<T, 4, 1>,
+
+    pub _bound: AxisAlignedBBox<T>,
+
+    pub _vicinity: T,
+}
+
+impl<T> ConvexPolygon<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    /// initialize with a flat list of xyz coordinates of the vertices in ccw order
+    pub fn init(verts: &[T]) -> ConvexPolygon<T> {
+        assert_eq!(verts.len() % 3, 0);
+        let v: Vec<Matrix<T, 4, 1>> = verts
+            .chunks(3)
+            .map(|p| Matrix::from([[p[0], p[1], p[2], T::one()]]).t())
+            .collect();
+
+        //sum of the fan triangles' normals, weighted by their areas
+        let mut normal = Matrix::zero();
+        for i in 1..v.len().saturating_sub(1) {
+            normal += (v[i] - v[0]).cross(&(v[i + 1] - v[0]));
+        }
+        let normal = normal.normalize_l2();
+
+        let mut lower = [T::infinity(); 3];
+        let mut upper = [T::neg_infinity(); 3];
+        for p in v.iter() {
+            for i in 0..3 {
+                lower[i] = lower[i].min(p[[i, 0]]);
+                upper[i] = upper[i].max(p[[i, 0]]);
+            }
+        }
+
+        ConvexPolygon {
+            _v: v,
+            _normal: normal,
+            _bound: AxisAlignedBBox::new(ShapeType::Rect, &[lower, upper].concat()),
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+
+    ///edge i runs from vertex i to vertex i + 1
+    pub fn get_edges(&self) -> Vec<Matrix<T, 4, 1>> {
+        let n = self._v.len();
+        (0..n).map(|i| self._v[(i + 1) % n] - self._v[i]).collect()
+    }
+
+    ///test if p projected onto the plane of the polygon lies within its edges
+    fn contains_projected(&self, p: &Matrix<T, 4, 1>) -> bool {
+        self.get_edges().iter().enumerate().all(|(i, e)| {
+            let inward = self._normal.cross(e);
+            (*p - self._v[i]).inner(&inward) >= -self._vicinity * inward.norm_l2()
+        })
+    }
+
+    ///test if p lies on the polygon within vicinity
+    pub fn contains(&self, p: &Matrix<T, 4, 1>) -> bool {
+        (*p - self._v[0]).inner(&self._normal).abs() <= self._vicinity && self.contains_projected(p)
+    }
+
+    ///point on the polygon closest to p
+    pub fn get_closest_point(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        let q = *p - self._normal * (*p - self._v[0]).inner(&self._normal);
+        if self.contains_projected(&q) {
+            return q;
+        }
+        let n = self._v.len();
+        let mut best = self._v[0];
+        for i in 0..n {
+            let (c, _) = closest_points_segments(&self._v[i], &self._v[(i + 1) % n], p, p);
+            if (c - *p).norm_l2() < (best - *p).norm_l2() {
+                best = c;
+            }
+        }
+        best
+    }
+
+    ///intersection of ray ori + dir * t with the polygon, returns t, None if missed or parallel
+    pub fn intersect_ray(&self, ori: &Matrix<T, 4, 1>, dir: &Matrix<T, 4, 1>) -> Option<T> {
+        let denom = self._normal.inner(dir);
+        if denom.abs() <= T::epsilon() * dir.norm_l2() {
+            return None;
+        }
+        let t = (self._v[0] - *ori).inner(&self._normal) / denom;
+        if t < T::zero() || !self.contains_projected(&(*ori + *dir * t)) {
+            return None;
+        }
+        Some(t)
+    }
+
+    ///normal of the polygon facing towards p
+    fn get_normal_towards(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        if (*p - self._v[0]).inner(&self._normal) < T::zero() {
+            self._normal * -T::one()
+        } else {
+            self._normal
+        }
+    }
+}
+
+impl<T> AnyBase for ConvexPolygon<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for ConvexPolygon<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::ConvexPolygon
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._v.len() < 3 || !self._normal.norm_l2().is_finite() {
+            return Err(CollideError::DegenerateShape(ShapeType::ConvexPolygon));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let p = other_point._ori;
+                let closest = self.get_closest_point(&p);
+                let d = (p - closest).norm_l2();
+                if d > self._vicinity {
+                    return Ok(None);
+                }
+                Ok(Some(Contact {
+                    _point_a: closest,
+                    _point_b: p,
+                    _normal: self.get_normal_towards(&p),
+                    _depth: -d,
+                    _feature: None,
+                }))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some(t) => Ok(Some(Contact::init_point(
+                        other_ray._ori + other_ray._dir * t,
+                        self.get_normal_towards(&other_ray._ori),
+                    ))),
+                    _ => Ok(None),
+                }
+            }
+            ShapeType::Line => {
+                let other_line: &Line<T> = downcast(other)?;
+                let (a, b) = (other_line._a, other_line._b);
+                let dir = b - a;
+                if let Some(t) = self.intersect_ray(&a, &dir) {
+                    if t <= T::one() {
+                        return Ok(Some(Contact::init_point(
+                            a + dir * t,
+                            self.get_normal_towards(&a),
+                        )));
+                    }
+                    return Ok(None);
+                }
+                let coplanar = (a - self._v[0]).inner(&self._normal).abs() <= self._vicinity
+                    && (b - self._v[0]).inner(&self._normal).abs() <= self._vicinity;
+                if !coplanar {
+                    return Ok(None);
+                }
+                //segment lies in the plane of the polygon
+                for p in [a, b].iter() {
+                    if self.contains(p) {
+                        return Ok(Some(Contact::init_point(*p, self._normal)));
+                    }
+                }
+                let n = self._v.len();
+                for i in 0..n {
+                    let (p, q) =
+                        closest_points_segments(&self._v[i], &self._v[(i + 1) % n], &a, &b);
+                    if (p - q).norm_l2() <= self._vicinity {
+                        return Ok(Some(Contact::init_point(p, self._normal).with_feature(i)));
+                    }
+                }
+                Ok(None)
+            }
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
+                let c = other_sphere._ori;
+                let r = other_sphere._radius;
+                let closest = self.get_closest_point(&c);
+                let d = (c - closest).norm_l2();
+                if d > r {
+                    return Ok(None);
+                }
+                let n = if d == T::zero() {
+                    self._normal
+                } else {
+                    (c - closest) / d
+                };
+                Ok(Some(Contact {
+                    _point_a: closest,
+                    _point_b: c - n * r,
+                    _normal: n,
+                    _depth: r - d,
+                    _feature: None,
+                }))
+            }
+            ShapeType::Box | ShapeType::Rect | ShapeType::OrientedBox => {
+                let world_axes = [
+                    Matrix::from([[T::one(), T::zero(), T::zero(), T::zero()]]).t(),
+                    Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t(),
+                    Matrix::from([[T::zero(), T::zero(), T::one(), T::zero()]]).t(),
+                ];
+                let (corners, box_axes) = match other.get_type() {
+                    ShapeType::Box => (downcast::<RectBox<T>, T>(other)?.get_corners(), world_axes),
+                    ShapeType::Rect => (downcast::<Cuboid<T>, T>(other)?.get_corners(), world_axes),
+                    _ => {
+                        let other_box: &OrientedBox<T> = downcast(other)?;
+                        (other_box.get_corners(), other_box._axes)
+                    }
+                };
+                let mut axes = vec![self._normal];
+                axes.extend(box_axes.iter().cloned());
+                for e in self.get_edges().iter() {
+                    for b in box_axes.iter() {
+                        axes.push(e.cross(b));
+                    }
+                }
+                Ok(intersect_sat::query_contact(&axes, &self._v, &corners))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() && !self._v.is_empty() {
+            //get a furthest point in the given direction v
+            let mut best = self._v[0];
+            for p in self._v.iter() {
+                if p.inner(v) > best.inner(v) {
+                    best = *p;
+                }
+            }
+            Some(best)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for ConvexPolygon<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+///regular hexagon of circumradius 1 in the z = 0 plane
+#[cfg(test)]
+fn hexagon() -> ConvexPolygon<f64> {
+    let mut v = vec![];
+    for i in 0..6 {
+        let a = std::f64::consts::PI / 3. * i as f64;
+        v.extend_from_slice(&[a.cos(), a.sin(), 0.]);
+    }
+    ConvexPolygon::init(&v)
+}
+
+#[test]
+fn test_convex_polygon_contains() {
+    let a = hexagon();
+    assert_matrix_approx_eq_float(&a._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-12);
+    assert!(a.contains(&Matrix::from([[0.9, 0., 0., 1.]]).t()));
+    assert!(a.contains(&Matrix::from([[0., 0.85, 0., 1.]]).t()));
+    assert!(!a.contains(&Matrix::from([[0., 0.9, 0., 1.]]).t()));
+    assert!(!a.contains(&Matrix::from([[0.5, 0., 0.1, 1.]]).t()));
+    let s = a
+        .get_support(&Matrix::from([[1., 0.1, 0., 0.]]).t())
+        .expect("polygon support unexpected");
+    assert_matrix_approx_eq_float(&s, &Matrix::from([[1., 0., 0., 1.]]).t(), 1e-12);
+
+    let b = Point::init(&[0.3, -0.4, 0.]);
+    let c = a.get_intersect(&b).expect("polygon point unexpected miss");
+    assert_eq!(c._point_b, b._ori);
+    assert!(b.get_intersect(&a).is_some());
+    assert!(a.get_intersect(&Point::init(&[0.3, -0.4, 0.01])).is_none());
+}
+#[test]
+fn test_intersect_convex_polygon_ray() {
+    let a = hexagon();
+    let b = Ray::init(&[0.5, 0.5, 3.], &[0., 0., -1.]);
+    let c = a.get_intersect(&b).expect("polygon ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.5, 0.5, 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("ray polygon unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-12);
+
+    //inside of the bound but past a slanted edge
+    assert!(a
+        .get_intersect(&Ray::init(&[0.9, 0.8, 3.], &[0., 0., -1.]))
+        .is_none());
+    assert!(a
+        .get_intersect(&Ray::init(&[0.5, 0.5, 3.], &[0., 0., 1.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_convex_polygon_line() {
+    let a = hexagon();
+    let b = Line::init(&[0.2, 0.3, -1.], &[0.2, 0.3, 1.]);
+    let c = a.get_intersect(&b).expect("polygon line unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.2, 0.3, 0., 1.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("line polygon unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.2, 0.3, 0., 1.]]).t(), 1e-12);
+    assert!(a
+        .get_intersect(&Line::init(&[0.2, 0.3, 0.5], &[0.2, 0.3, 1.]))
+        .is_none());
+
+    //coplanar segments
+    let b = Line::init(&[0.75, 0.2, 0.], &[2., 0.5, 0.]);
+    assert!(a.get_intersect(&b).is_some());
+    let b = Line::init(&[0.9, 0.8, 0.], &[2., 0.5, 0.]);
+    assert!(a.get_intersect(&b).is_none());
+    //crossing the whole polygon, first through the edge from vertex 0 to vertex 1
+    let b = Line::init(&[2., 0.5, 0.], &[-2., 0.5, 0.]);
+    let c = a.get_intersect(&b).expect("polygon line unexpected miss");
+    assert_eq!(c._feature, Some(0));
+}
+#[test]
+fn test_intersect_convex_polygon_sphere() {
+    let a = hexagon();
+    let b = Sphere::init(&[0., 0., 0.5], 1.);
+    let c = a.get_intersect(&b).expect("polygon sphere unexpected miss");
+    assert!((c._depth - 0.5).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("sphere polygon unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-12);
+
+    //closest feature is a vertex
+    let b = Sphere::init(&[1.5, 0., 0.], 0.6);
+    let c = a.get_intersect(&b).expect("polygon sphere unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[1., 0., 0., 1.]]).t(), 1e-12);
+    assert!(a
+        .get_intersect(&Sphere::init(&[1.5, 0., 0.], 0.4))
+        .is_none());
+}
+#[test]
+fn test_intersect_convex_polygon_box() {
+    let a = hexagon();
+    let b = RectBox::init(&[0., 0., 0.4], 0.5);
+    let c = a.get_intersect(&b).expect("polygon box unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-9);
+    let c = b.get_intersect(&a).expect("box polygon unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-9);
+
+    //box next to a slanted edge overlaps the polygon's bound only
+    let b = RectBox::init(&[1.05, 0.95, 0.], 0.1);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_try_intersect_convex_polygon_degenerate() {
+    let a = ConvexPolygon::init(&[0., 0., 0., 1., 0., 0., 2., 0., 0., 3., 0., 0.]);
+    assert_eq!(
+        a.try_intersect(&Point::init(&[0.5, 0., 0.])).err(),
+        Some(CollideError::DegenerateShape(ShapeType::ConvexPolygon))
+    );
+}
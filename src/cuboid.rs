@@ -0,0 +1,571 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::error::CollideError;
+use crate::plane::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::rect_box::*;
+use crate::shape::*;
+use crate::sphere::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+///axis aligned box with separate half extents per axis
+#[derive(Debug, Clone)]
+pub struct Cuboid<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _ori: Matrix<T, 4, 1>,
+    ///half of the length of box edge along x, y, z
+    pub _size: [T; 3],
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+impl<T> Cuboid<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub fn init(origin: &[T], size: &[T]) -> Cuboid<T> {
+        assert!(origin.len() == 3);
+        assert!(size.len() == 3);
+        Cuboid {
+            _ori: Matrix::from([[origin[0], origin[1], origin[2], T::one()]]).t(),
+            _size: [size[0], size[1], size[2]],
+            _bound: AxisAlignedBBox::new(
+                ShapeType::Rect,
+                &[
+                    origin[0] - size[0],
+                    origin[1] - size[1],
+                    origin[2] - size[2],
+                    origin[0] + size[0],
+                    origin[1] + size[1],
+                    origin[2] + size[2],
+                ],
+            ),
+            _vicinity: T::epsilon(),
+        }
+    }
+
+    ///the 8 corners of the box
+    pub fn get_corners(&self) -> [Matrix<T, 4, 1>; 8] {
+        box_corners(&self._ori, &self._size)
+    }
+
+    ///point on or inside of the box closest to p
+    pub fn get_closest_point(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        box_closest_point(&self._ori, &self._size, p)
+    }
+}
+
+///unit vector along an axis
+pub(crate) fn axis_unit<T>(axis: usize, sign: T) -> Matrix<T, 4, 1>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let mut n = Matrix::zero();
+    n[[axis, 0]] = sign;
+    n
+}
+
+///t, axis and outward sign of a box face crossed by a ray
+pub(crate) type SlabFace<T> = (T, usize, T);
+
+///slab clipping of a ray against a box centered at the origin with half extents size,
+///returns t, axis and outward sign of the faces where the ray enters and leaves the box
+pub(crate) fn clip_slab<T>(
+    ori: &[T; 3],
+    dir: &[T; 3],
+    size: &[T; 3],
+) -> Option<(SlabFace<T>, SlabFace<T>)>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let mut t_near = (T::neg_infinity(), 0);
+    let mut t_far = (T::infinity(), 0);
+    for i in 0..3 {
+        if dir[i] == T::zero() {
+            if ori[i] < -size[i] || ori[i] > size[i] {
+                return None;
+            }
+            continue;
+        }
+        let t0 = (-size[i] - ori[i]) / dir[i];
+        let t1 = (size[i] - ori[i]) / dir[i];
+        let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+        if t0 > t_near.0 {
+            t_near = (t0, i);
+        }
+        if t1 < t_far.0 {
+            t_far = (t1, i);
+        }
+    }
+    if t_near.0 > t_far.0 {
+        return None;
+    }
+    Some((
+        (t_near.0, t_near.1, -dir[t_near.1].signum()),
+        (t_far.0, t_far.1, dir[t_far.1].signum()),
+    ))
+}
+
+///slab test of a ray against a box centered at the origin with half extents size,
+///returns t, axis and sign of the face hit. a ray starting inside of the box hits the exit face
+pub(crate) fn intersect_slab<T>(ori: &[T; 3], dir: &[T; 3], size: &[T; 3]) -> Option<SlabFace<T>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let (near, far) = clip_slab(ori, dir, size)?;
+    if far.0 < T::zero() {
+        None
+    } else if near.0 >= T::zero() {
+        Some(near)
+    } else {
+        Some(far)
+    }
+}
+
+///contact between 2 axis aligned boxes given by centers and half extents, along the axis of least overlap
+pub(crate) fn intersect_aabb_aabb<T>(
+    ori_a: &Matrix<T, 4, 1>,
+    size_a: &[T; 3],
+    ori_b: &Matrix<T, 4, 1>,
+    size_b: &[T; 3],
+) -> Option<Contact<T>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let d = *ori_b - *ori_a;
+    let mut axis = 0;
+    let mut depth = T::infinity();
+    for i in 0..3 {
+        let overlap = size_a[i] + size_b[i] - d[[i, 0]].abs();
+        if overlap < T::zero() {
+            return None;
+        }
+        if overlap < depth {
+            depth = overlap;
+            axis = i;
+        }
+    }
+    let sign = if d[[axis, 0]] < T::zero() {
+        -T::one()
+    } else {
+        T::one()
+    };
+    //center of the overlapping region on the remaining axes
+    let two = T::from(2.).unwrap();
+    let mut point_a = *ori_a;
+    for i in 0..3 {
+        let lo = (ori_a[[i, 0]] - size_a[i]).max(ori_b[[i, 0]] - size_b[i]);
+        let hi = (ori_a[[i, 0]] + size_a[i]).min(ori_b[[i, 0]] + size_b[i]);
+        point_a[[i, 0]] = (lo + hi) / two;
+    }
+    let mut point_b = point_a;
+    point_a[[axis, 0]] = ori_a[[axis, 0]] + sign * size_a[axis];
+    point_b[[axis, 0]] = ori_b[[axis, 0]] - sign * size_b[axis];
+    Some(Contact {
+        _point_a: point_a,
+        _point_b: point_b,
+        _normal: axis_unit(axis, sign),
+        _depth: depth,
+        _feature: None,
+    })
+}
+
+///the 8 corners of an axis aligned box given by center and half extents
+pub(crate) fn box_corners<T>(ori: &Matrix<T, 4, 1>, size: &[T; 3]) -> [Matrix<T, 4, 1>; 8]
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let mut corners = [*ori; 8];
+    for (i, c) in corners.iter_mut().enumerate() {
+        for axis in 0..3 {
+            if i & (1 << axis) == 0 {
+                c[[axis, 0]] -= size[axis];
+            } else {
+                c[[axis, 0]] += size[axis];
+            }
+        }
+    }
+    corners
+}
+
+///point on or inside of an axis aligned box closest to p
+pub(crate) fn box_closest_point<T>(
+    ori: &Matrix<T, 4, 1>,
+    size: &[T; 3],
+    p: &Matrix<T, 4, 1>,
+) -> Matrix<T, 4, 1>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let mut q = *p;
+    for i in 0..3 {
+        q[[i, 0]] = p[[i, 0]]
+            .max(ori[[i, 0]] - size[i])
+            .min(ori[[i, 0]] + size[i]);
+    }
+    q
+}
+
+///face with the largest signed distance to p, as (axis, sign of the face, distance to the face).
+///this is the nearest face for p inside of the box, where the distance is negative
+fn box_nearest_face<T>(ori: &Matrix<T, 4, 1>, size: &[T; 3], p: &Matrix<T, 4, 1>) -> (usize, T, T)
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let d = *p - *ori;
+    let mut best = (0, T::one(), T::neg_infinity());
+    for i in 0..3 {
+        let dist = d[[i, 0]].abs() - size[i];
+        if i == 0 || dist > best.2 {
+            let sign = if d[[i, 0]] < T::zero() {
+                -T::one()
+            } else {
+                T::one()
+            };
+            best = (i, sign, dist);
+        }
+    }
+    best
+}
+
+///contact of a point inside of an axis aligned box, pushed out through the nearest face
+pub(crate) fn contact_box_point<T>(
+    ori: &Matrix<T, 4, 1>,
+    size: &[T; 3],
+    p: &Matrix<T, 4, 1>,
+) -> Option<Contact<T>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let (axis, sign, dist) = box_nearest_face(ori, size, p);
+    if dist > T::zero() {
+        return None;
+    }
+    let mut point_a = *p;
+    point_a[[axis, 0]] = ori[[axis, 0]] + sign * size[axis];
+    Some(Contact {
+        _point_a: point_a,
+        _point_b: *p,
+        _normal: axis_unit(axis, sign),
+        _depth: -dist,
+        _feature: Some(axis * 2 + (sign > T::zero()) as usize),
+    })
+}
+
+///contact of a ray with the face of an axis aligned box it enters, or leaves when starting inside
+pub(crate) fn contact_box_ray<T>(
+    ori: &Matrix<T, 4, 1>,
+    size: &[T; 3],
+    ray: &Ray<T>,
+) -> Option<Contact<T>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let (rel, dir) = (ray._ori - *ori, ray._dir);
+    let (t, axis, sign) = intersect_slab(
+        &[rel[[0, 0]], rel[[1, 0]], rel[[2, 0]]],
+        &[dir[[0, 0]], dir[[1, 0]], dir[[2, 0]]],
+        size,
+    )?;
+    Some(
+        Contact::init_point(ray._ori + dir * t, axis_unit(axis, sign))
+            .with_feature(axis * 2 + (sign > T::zero()) as usize),
+    )
+}
+
+///contact between an axis aligned box and a sphere
+pub(crate) fn contact_box_sphere<T>(
+    ori: &Matrix<T, 4, 1>,
+    size: &[T; 3],
+    sphere: &Sphere<T>,
+) -> Option<Contact<T>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let c = sphere._ori;
+    let r = sphere._radius;
+    let (axis, sign, dist) = box_nearest_face(ori, size, &c);
+    if dist > T::zero() {
+        //center outside of the box, clamp it onto the box
+        let q = box_closest_point(ori, size, &c);
+        let d = (c - q).norm_l2();
+        if d > r {
+            return None;
+        }
+        let n = (c - q) / d;
+        Some(Contact {
+            _point_a: q,
+            _point_b: c - n * r,
+            _normal: n,
+            _depth: r - d,
+            _feature: None,
+        })
+    } else {
+        //center inside of the box, push out through the nearest face
+        let n = axis_unit(axis, sign);
+        let mut q = c;
+        q[[axis, 0]] = ori[[axis, 0]] + sign * size[axis];
+        Some(Contact {
+            _point_a: q,
+            _point_b: c - n * r,
+            _normal: n,
+            _depth: r - dist,
+            _feature: Some(axis * 2 + (sign > T::zero()) as usize),
+        })
+    }
+}
+
+///contact between an axis aligned box and a plane,
+///the plane is pushed towards the side holding the lesser part of the box
+pub(crate) fn contact_box_plane<T>(
+    ori: &Matrix<T, 4, 1>,
+    size: &[T; 3],
+    plane: &Plane<T>,
+) -> Option<Contact<T>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let n = plane._normal;
+    //projected radius of the box onto the plane normal
+    let r = (0..3).fold(T::zero(), |acc, i| acc + n[[i, 0]].abs() * size[i]);
+    let s = plane.get_distance(ori);
+    if s.abs() > r {
+        return None;
+    }
+    let normal = if s >= T::zero() { n * -T::one() } else { n };
+    let depth = r - s.abs();
+    let mut deepest = *ori;
+    for i in 0..3 {
+        if normal[[i, 0]] > T::zero() {
+            deepest[[i, 0]] += size[i];
+        } else if normal[[i, 0]] < T::zero() {
+            deepest[[i, 0]] -= size[i];
+        }
+    }
+    Some(Contact {
+        _point_a: deepest,
+        _point_b: deepest - normal * depth,
+        _normal: normal,
+        _depth: depth,
+        _feature: None,
+    })
+}
+
+impl<T> AnyBase for Cuboid<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for Cuboid<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Rect
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._size.iter().any(|s| s.is_nan() || *s < T::zero()) {
+            return Err(CollideError::DegenerateShape(ShapeType::Rect));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                Ok(contact_box_point(
+                    &self._ori,
+                    &self._size,
+                    &other_point._ori,
+                ))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                Ok(contact_box_ray(&self._ori, &self._size, other_ray))
+            }
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
+                Ok(contact_box_sphere(&self._ori, &self._size, other_sphere))
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                Ok(contact_box_plane(&self._ori, &self._size, other_plane))
+            }
+            ShapeType::Rect => {
+                let other_cuboid: &Cuboid<T> = downcast(other)?;
+                Ok(intersect_aabb_aabb(
+                    &self._ori,
+                    &self._size,
+                    &other_cuboid._ori,
+                    &other_cuboid._size,
+                ))
+            }
+            ShapeType::Box => {
+                let other_box: &RectBox<T> = downcast(other)?;
+                Ok(intersect_aabb_aabb(
+                    &self._ori,
+                    &self._size,
+                    &other_box._ori,
+                    &[other_box._size; 3],
+                ))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() {
+            //get a furthest point in the given direction v
+            let mut o = self._ori;
+            for i in 0..3 {
+                if v[[i, 0]] >= T::zero() {
+                    o[[i, 0]] += self._size[i];
+                } else {
+                    o[[i, 0]] -= self._size[i];
+                }
+            }
+            Some(o)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for Cuboid<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+#[test]
+fn test_cuboid_support() {
+    let a = Cuboid::init(&[1., 2., 3.], &[1., 2., 0.5]);
+    let s = a
+        .get_support(&Matrix::from([[1., -1., 1., 0.]]).t())
+        .expect("cuboid support unexpected");
+    assert_eq!(s, Matrix::from([[2., 0., 3.5, 1.]]).t());
+    assert_eq!(a._bound.bound_lower, [0., 0., 2.5]);
+    assert_eq!(a._bound.bound_upper, [2., 4., 3.5]);
+}
+#[test]
+fn test_intersect_cuboid_point() {
+    let a = Cuboid::init(&[0., 0., 0.], &[4., 1., 2.]);
+    let b = Point::init(&[3., 0.2, 0.]);
+    let c = a.get_intersect(&b).expect("cuboid point unexpected miss");
+    assert!((c._depth - 0.8).abs() < 1e-12);
+    assert_eq!(c._normal, Matrix::from([[0., 1., 0., 0.]]).t());
+    assert_eq!(c._point_a, Matrix::from([[3., 1., 0., 1.]]).t());
+
+    let b = Point::init(&[3., 1.2, 0.]);
+    assert!(a.get_intersect(&b).is_none());
+    assert!(b.get_intersect(&a).is_none());
+}
+#[test]
+fn test_intersect_cuboid_ray() {
+    let a = Cuboid::init(&[0., 0., 0.], &[4., 1., 2.]);
+    let b = Ray::init(&[-10., 0.5, 0.], &[1., 0., 0.]);
+    let c = a.get_intersect(&b).expect("cuboid ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-4., 0.5, 0., 1.]]).t(), 1e-12);
+    assert_eq!(c._normal, Matrix::from([[-1., 0., 0., 0.]]).t());
+    assert_eq!(c._feature, Some(0));
+
+    let c = b.get_intersect(&a).expect("ray cuboid unexpected miss");
+    assert_eq!(c._normal, Matrix::from([[1., 0., 0., 0.]]).t());
+
+    //starting inside hits the exit face
+    let b = Ray::init(&[0., 0., 0.], &[0., 0., 1.]);
+    let c = a.get_intersect(&b).expect("cuboid ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 0., 2., 1.]]).t(), 1e-12);
+
+    let b = Ray::init(&[-10., 1.5, 0.], &[1., 0., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+    let b = Ray::init(&[-10., 0.5, 0.], &[-1., 0., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_cuboid_sphere() {
+    let a = Cuboid::init(&[0., 0., 0.], &[4., 1., 2.]);
+    let b = Sphere::init(&[5., 2., 0.], 2.);
+    let c = a.get_intersect(&b).expect("cuboid sphere unexpected miss");
+    assert!((c._depth - (2. - 2f64.sqrt())).abs() < 1e-12);
+    assert_eq!(c._point_a, Matrix::from([[4., 1., 0., 1.]]).t());
+
+    let c = b.get_intersect(&a).expect("sphere cuboid unexpected miss");
+    assert!((c._depth - (2. - 2f64.sqrt())).abs() < 1e-12);
+
+    //center inside
+    let b = Sphere::init(&[3.5, 0., 0.], 0.25);
+    let c = a.get_intersect(&b).expect("cuboid sphere unexpected miss");
+    assert!((c._depth - 0.75).abs() < 1e-12);
+    assert_eq!(c._normal, Matrix::from([[1., 0., 0., 0.]]).t());
+
+    let b = Sphere::init(&[5.5, 2.5, 0.], 2.);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_cuboid_plane() {
+    let a = Cuboid::init(&[0., 0., 0.], &[4., 1., 2.]);
+    let b = Plane::init(&[0., -0.5, 0.], &[0., 1., 0.]);
+    let c = a.get_intersect(&b).expect("cuboid plane unexpected miss");
+    assert!((c._depth - 0.5).abs() < 1e-12);
+    assert_eq!(c._normal, Matrix::from([[0., -1., 0., 0.]]).t());
+    assert!((c._point_a[[1, 0]] + 1.).abs() < 1e-12);
+    assert!((c._point_b[[1, 0]] + 0.5).abs() < 1e-12);
+
+    let b = Plane::init(&[0., -1.5, 0.], &[0., 1., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+    assert!(b.get_intersect(&a).is_none());
+}
+#[test]
+fn test_intersect_cuboid_box() {
+    let a = Cuboid::init(&[0., 0., 0.], &[4., 1., 2.]);
+    let b = Cuboid::init(&[5., 0.5, 0.], &[1.5, 1., 1.]);
+    let c = a.get_intersect(&b).expect("cuboid cuboid unexpected miss");
+    assert!((c._depth - 0.5).abs() < 1e-12);
+    assert_eq!(c._normal, Matrix::from([[1., 0., 0., 0.]]).t());
+    assert_eq!(c._point_a, Matrix::from([[4., 0.25, 0., 1.]]).t());
+    assert_eq!(c._point_b, Matrix::from([[3.5, 0.25, 0., 1.]]).t());
+
+    let b = RectBox::init(&[0., -1.5, 0.], 1.);
+    let c = b.get_intersect(&a).expect("box cuboid unexpected miss");
+    assert!((c._depth - 0.5).abs() < 1e-12);
+    assert_eq!(c._normal, Matrix::from([[0., 1., 0., 0.]]).t());
+
+    let b = Cuboid::init(&[6., 0., 0.], &[1.5, 1., 1.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_try_intersect_cuboid_degenerate() {
+    let a = Cuboid::init(&[0., 0., 0.], &[4., -1., 2.]);
+    let b = Point::init(&[0., 0., 0.]);
+    assert_eq!(
+        a.try_intersect(&b).err(),
+        Some(CollideError::DegenerateShape(ShapeType::Rect))
+    );
+}
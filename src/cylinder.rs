@@ -0,0 +1,336 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::error::CollideError;
+use crate::point::*;
+use crate::ray::*;
+use crate::shape::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+///solid cylinder centered at _ori, extending _half_height along _axis in both directions
+#[derive(Debug, Clone)]
+pub struct Cylinder<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _ori: Matrix<T, 4, 1>,
+    pub _axis: Matrix<T, 4, 1>,
+    pub _half_height: T,
+    pub _radius: T,
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+impl<T> Cylinder<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub fn init(origin: &[T], axis: &[T], half_height: T, radius: T) -> Cylinder<T> {
+        assert!(origin.len() == 3);
+        assert!(axis.len() == 3);
+        let ori = Matrix::from([[origin[0], origin[1], origin[2], T::one()]]).t();
+        let axis = Matrix::from([[axis[0], axis[1], axis[2], T::zero()]])
+            .t()
+            .normalize_l2();
+        let disc = get_disc_extent(&axis, radius);
+        let mut vals = [T::zero(); 6];
+        for i in 0..3 {
+            let h = (axis[[i, 0]] * half_height).abs();
+            vals[i] = ori[[i, 0]] - h - disc[i];
+            vals[i + 3] = ori[[i, 0]] + h + disc[i];
+        }
+        Cylinder {
+            _ori: ori,
+            _axis: axis,
+            _half_height: half_height,
+            _radius: radius,
+            _bound: AxisAlignedBBox::new(ShapeType::Rect, &vals),
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+
+    pub fn contains(&self, p: &Matrix<T, 4, 1>) -> bool {
+        let (s, radial) = get_axial_radial(&self._ori, &self._axis, p);
+        s.abs() <= self._half_height && radial.norm_l2() <= self._radius
+    }
+
+    ///outward surface normal at a point on the surface
+    pub fn get_normal(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        let (s, radial) = get_axial_radial(&self._ori, &self._axis, p);
+        let d_cap = self._half_height - s.abs();
+        let d_side = self._radius - radial.norm_l2();
+        if d_cap <= d_side || radial.norm_l2() == T::zero() {
+            self._axis * s.signum()
+        } else {
+            radial.normalize_l2()
+        }
+    }
+
+    ///parameter interval of ray ori + dir * t inside of the cylinder
+    pub fn intersect_ray(&self, ori: &Matrix<T, 4, 1>, dir: &Matrix<T, 4, 1>) -> Option<(T, T)> {
+        let m = *ori - self._ori;
+        let (mu, du) = (m.inner(&self._axis), dir.inner(&self._axis));
+        let m_perp = m - self._axis * mu;
+        let d_perp = *dir - self._axis * du;
+        let a = d_perp.inner(&d_perp);
+        let b = m_perp.inner(&d_perp);
+        let c = m_perp.inner(&m_perp) - self._radius * self._radius;
+        let radial = if a == T::zero() {
+            if c > T::zero() {
+                return None;
+            }
+            (T::neg_infinity(), T::infinity())
+        } else {
+            let disc = b * b - a * c;
+            if disc < T::zero() {
+                return None;
+            }
+            ((-b - disc.sqrt()) / a, (-b + disc.sqrt()) / a)
+        };
+        let axial = if du == T::zero() {
+            if mu.abs() > self._half_height {
+                return None;
+            }
+            (T::neg_infinity(), T::infinity())
+        } else {
+            let (t0, t1) = (
+                (-self._half_height - mu) / du,
+                (self._half_height - mu) / du,
+            );
+            (t0.min(t1), t0.max(t1))
+        };
+        let (t0, t1) = (radial.0.max(axial.0), radial.1.min(axial.1));
+        if t0 <= t1 {
+            Some((t0, t1))
+        } else {
+            None
+        }
+    }
+}
+
+///distance along axis and the perpendicular offset of p relative to ori
+pub(crate) fn get_axial_radial<T>(
+    ori: &Matrix<T, 4, 1>,
+    axis: &Matrix<T, 4, 1>,
+    p: &Matrix<T, 4, 1>,
+) -> (T, Matrix<T, 4, 1>)
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let m = *p - *ori;
+    let s = m.inner(axis);
+    (s, m - *axis * s)
+}
+
+///per axis half extent of a disc with the given unit normal and radius
+pub(crate) fn get_disc_extent<T>(normal: &Matrix<T, 4, 1>, radius: T) -> [T; 3]
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let mut e = [T::zero(); 3];
+    for (i, x) in e.iter_mut().enumerate() {
+        let n = normal[[i, 0]];
+        *x = radius * (T::one() - n * n).max(T::zero()).sqrt();
+    }
+    e
+}
+
+///unit component of v perpendicular to axis, if any
+pub(crate) fn get_radial_direction<T>(
+    axis: &Matrix<T, 4, 1>,
+    v: &Matrix<T, 4, 1>,
+) -> Option<Matrix<T, 4, 1>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let w = *v - *axis * v.inner(axis);
+    if w.norm_l2() > T::epsilon() * v.norm_l2() {
+        Some(w.normalize_l2())
+    } else {
+        None
+    }
+}
+
+impl<T> AnyBase for Cylinder<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for Cylinder<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Cylinder
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if !self._axis[[0, 0]].is_finite()
+            || self._radius.is_nan()
+            || self._radius < T::zero()
+            || self._half_height.is_nan()
+            || self._half_height < T::zero()
+        {
+            return Err(CollideError::DegenerateShape(ShapeType::Cylinder));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let p = other_point._ori;
+                if !self.contains(&p) {
+                    return Ok(None);
+                }
+                //push the point out through the nearest of the caps or the side
+                let (s, radial) = get_axial_radial(&self._ori, &self._axis, &p);
+                let d_cap = self._half_height - s.abs();
+                let d_side = self._radius - radial.norm_l2();
+                let n = self.get_normal(&p);
+                let depth = d_cap.min(d_side);
+                Ok(Some(Contact {
+                    _point_a: p + n * depth,
+                    _point_b: p,
+                    _normal: n,
+                    _depth: depth,
+                    _feature: None,
+                }))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                let (t0, t1) = match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                if t1 < T::zero() {
+                    return Ok(None);
+                }
+                //ray starting inside of the cylinder hits on its way out
+                let t = if t0 >= T::zero() { t0 } else { t1 };
+                let hit = other_ray._ori + other_ray._dir * t;
+                Ok(Some(Contact::init_point(hit, self.get_normal(&hit))))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() {
+            let sign = if v.inner(&self._axis) >= T::zero() {
+                T::one()
+            } else {
+                -T::one()
+            };
+            let cap = self._ori + self._axis * (sign * self._half_height);
+            match get_radial_direction(&self._axis, v) {
+                Some(w) => Some(cap + w * self._radius),
+                _ => Some(cap),
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for Cylinder<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+#[test]
+fn test_cylinder_bound_support() {
+    let a = Cylinder::init(&[0., 0., 0.], &[0., 0., 1.], 2., 1.);
+    assert_eq!(a._bound.bound_lower, [-1., -1., -2.]);
+    assert_eq!(a._bound.bound_upper, [1., 1., 2.]);
+    let s = a
+        .get_support(&Matrix::from([[1., 0., 1., 0.]]).t())
+        .expect("cylinder support unexpected");
+    assert_eq!(s, Matrix::from([[1., 0., 2., 1.]]).t());
+    let s = a
+        .get_support(&Matrix::from([[0., 0., -1., 0.]]).t())
+        .expect("cylinder support unexpected");
+    assert_eq!(s, Matrix::from([[0., 0., -2., 1.]]).t());
+
+    let b = crate::sphere::Sphere::init(&[0., 1.4, 1.], 0.5);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(true));
+    let b = crate::sphere::Sphere::init(&[0., 0., 2.6], 0.5);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(false));
+}
+#[test]
+fn test_intersect_cylinder_point() {
+    let a = Cylinder::init(&[0., 0., 0.], &[0., 0., 1.], 2., 1.);
+    let c = a
+        .get_intersect(&Point::init(&[0.7, 0., 0.5]))
+        .expect("cylinder point unexpected miss");
+    assert!((c._depth - 0.3).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+    let c = a
+        .get_intersect(&Point::init(&[0., 0.2, -1.9]))
+        .expect("cylinder point unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-12);
+    //inside of the bound but outside of the rim
+    assert!(a.get_intersect(&Point::init(&[0.8, 0.8, 0.])).is_none());
+}
+#[test]
+fn test_intersect_cylinder_ray() {
+    let a = Cylinder::init(&[0., 0., 0.], &[0., 0., 1.], 2., 1.);
+    //side
+    let b = Ray::init(&[-5., 0., 1.], &[1., 0., 0.]);
+    let c = a.get_intersect(&b).expect("cylinder ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-1., 0., 1., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+    //cap
+    let b = Ray::init(&[0.5, 0., 5.], &[0., 0., -1.]);
+    let c = b.get_intersect(&a).expect("ray cylinder unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.5, 0., 2., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-12);
+    //oblique through the cap
+    let b = Ray::init(&[-3., 0., 5.], &[1., 0., -1.]);
+    let c = a.get_intersect(&b).expect("cylinder ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 0., 2., 1.]]).t(), 1e-12);
+    //starting inside
+    let b = Ray::init(&[0., 0., 0.], &[0., 1., 0.]);
+    let c = a.get_intersect(&b).expect("cylinder ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 1., 0., 1.]]).t(), 1e-12);
+
+    assert!(a
+        .get_intersect(&Ray::init(&[-5., 0., 2.1], &[1., 0., 0.]))
+        .is_none());
+    assert!(a
+        .get_intersect(&Ray::init(&[1.1, 0., 5.], &[0., 0., -1.]))
+        .is_none());
+    assert!(a
+        .get_intersect(&Ray::init(&[-5., 0., 1.], &[-1., 0., 0.]))
+        .is_none());
+}
+#[test]
+fn test_try_intersect_cylinder_degenerate() {
+    let a = Cylinder::init(&[0., 0., 0.], &[0., 0., 0.], 2., 1.);
+    assert_eq!(
+        a.try_intersect(&Point::init(&[0., 0., 0.])).err(),
+        Some(CollideError::DegenerateShape(ShapeType::Cylinder))
+    );
+}
@@ -0,0 +1,409 @@
+//! central intersection dispatch for pairs of shapes, symmetric in the order of the pair
+
+use std::collections::HashMap;
+
+use crate::contact::Contact;
+use crate::error::CollideError;
+use crate::intersect_epa::query_penetration;
+use crate::intersect_gjk::query_simplex;
+use crate::shape::*;
+use lightmatrix::matrix::*;
+use num_traits::{Float, NumAssign};
+
+///intersection algorithm for a pair of shapes, returning the contact with the first shape as shape a
+pub type PairAlgorithm<T> =
+    fn(&dyn Shape<T>, &dyn Shape<T>) -> Result<Option<Contact<T>>, CollideError>;
+
+///resolves intersection of shape pairs in the following order:
+///registered algorithms, the shapes' own try_intersect_native in either order, then gjk and epa
+pub struct Dispatcher<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    pub _algorithms: HashMap<(ShapeType, ShapeType), PairAlgorithm<T>>,
+}
+
+impl<T> Dispatcher<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    pub fn init() -> Dispatcher<T> {
+        Dispatcher {
+            _algorithms: HashMap::new(),
+        }
+    }
+
+    ///registers an algorithm for the pair, it is also used for the swapped pair with the contact flipped.
+    ///replaces any algorithm previously registered for the same pair
+    pub fn register(&mut self, a: ShapeType, b: ShapeType, f: PairAlgorithm<T>) {
+        self._algorithms.insert((a, b), f);
+    }
+
+    pub fn try_intersect(
+        &self,
+        a: &dyn Shape<T>,
+        b: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        let (ta, tb) = (a.get_type(), b.get_type());
+        if let Some(f) = self._algorithms.get(&(ta, tb)) {
+            return f(a, b);
+        }
+        if let Some(f) = self._algorithms.get(&(tb, ta)) {
+            return Ok(f(b, a)?.map(|c| c.flip()));
+        }
+        try_intersect(a, b)
+    }
+
+    ///panics if the pair is not supported, see try_intersect for a fallible version
+    pub fn get_intersect(&self, a: &dyn Shape<T>, b: &dyn Shape<T>) -> Option<Contact<T>> {
+        match self.try_intersect(a, b) {
+            Ok(o) => o,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+impl<T> Default for Dispatcher<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn default() -> Dispatcher<T> {
+        Dispatcher::init()
+    }
+}
+
+///intersection of a pair without registered algorithms, symmetric in the order of the pair
+pub fn try_intersect<T>(
+    a: &dyn Shape<T>,
+    b: &dyn Shape<T>,
+) -> Result<Option<Contact<T>>, CollideError>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    //pairs are evaluated in the order of their shape types, so swapping the pair only flips the contact
+    if (b.get_type() as usize) < (a.get_type() as usize) {
+        return match try_intersect_ordered(b, a) {
+            Err(CollideError::UnsupportedPair(..)) => {
+                Err(CollideError::UnsupportedPair(a.get_type(), b.get_type()))
+            }
+            r => Ok(r?.map(|c| c.flip())),
+        };
+    }
+    try_intersect_ordered(a, b)
+}
+
+fn try_intersect_ordered<T>(
+    a: &dyn Shape<T>,
+    b: &dyn Shape<T>,
+) -> Result<Option<Contact<T>>, CollideError>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    if !a.get_bound().intersect(b.get_bound()) {
+        //nothing to compute, though either shape may still report itself degenerate
+        for r in [a.try_intersect_native(b), b.try_intersect_native(a)] {
+            match r {
+                Err(CollideError::UnsupportedPair(..)) | Ok(_) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        return Ok(None);
+    }
+    match a.try_intersect_native(b) {
+        Err(CollideError::UnsupportedPair(..)) => {}
+        r => return r,
+    }
+    match b.try_intersect_native(a) {
+        Err(CollideError::UnsupportedPair(..)) => {}
+        r => return Ok(r?.map(|c| c.flip())),
+    }
+    try_intersect_convex(a, b).map_err(|e| match e {
+        CollideError::UnsupportedPair(..) => {
+            CollideError::UnsupportedPair(a.get_type(), b.get_type())
+        }
+        e => e,
+    })
+}
+
+///panics if the pair is not supported, see try_intersect for a fallible version
+pub fn get_intersect<T>(a: &dyn Shape<T>, b: &dyn Shape<T>) -> Option<Contact<T>>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    match try_intersect(a, b) {
+        Ok(o) => o,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+///intersection of a pair of convex shapes using gjk, with penetration from epa
+pub fn try_intersect_convex<T>(
+    a: &dyn Shape<T>,
+    b: &dyn Shape<T>,
+) -> Result<Option<Contact<T>>, CollideError>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    if !a.get_bound().intersect(b.get_bound()) {
+        return Ok(None);
+    }
+    let (hit, simplex) = query_simplex(a, b)?;
+    if !hit {
+        return Ok(None);
+    }
+    match query_penetration(a, b, &simplex) {
+        Some(p) => Ok(Some(Contact {
+            _point_a: p._point_a,
+            _point_b: p._point_b,
+            _normal: p._normal,
+            _depth: p._depth,
+            _feature: None,
+        })),
+        //touching or flat minkowski difference, no penetration direction available
+        _ => Ok(Some(Contact {
+            _point_a: simplex[0]._a,
+            _point_b: simplex[0]._b,
+            _normal: Matrix::zero(),
+            _depth: T::zero(),
+            _feature: None,
+        })),
+    }
+}
+
+#[cfg(test)]
+use crate::{
+    bound::Bound, capsule::Capsule, compound::*, cone::Cone, convex_hull::ConvexHull,
+    convex_polygon::ConvexPolygon, cuboid::Cuboid, cylinder::Cylinder, ellipsoid::Ellipsoid,
+    frustum::Frustum, height_field::HeightField, line::Line, oriented_box::OrientedBox,
+    plane::Plane, point::Point, ray::Ray, rect_box::RectBox, sphere::Sphere, tri_mesh::TriMesh,
+    tri_prism::TriPrism, triangle::Triangle, vicinity::Vicinity,
+};
+#[cfg(test)]
+use lightmatrix::quatr::QuatR;
+
+#[cfg(test)]
+struct Blob {
+    _inner: Sphere<f64>,
+}
+
+#[cfg(test)]
+impl AnyBase for Blob {
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+impl Vicinity<f64> for Blob {
+    fn set_vicinity(&mut self, epsilon: f64) {
+        self._inner.set_vicinity(epsilon);
+    }
+    fn within_vicinity(&self, a: f64, b: f64) -> bool {
+        self._inner.within_vicinity(a, b)
+    }
+}
+
+#[cfg(test)]
+impl Shape<f64> for Blob {
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Complex
+    }
+    fn get_bound(&self) -> &dyn Bound<f64> {
+        self._inner.get_bound()
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<f64>,
+    ) -> Result<Option<Contact<f64>>, CollideError> {
+        Err(CollideError::UnsupportedPair(
+            self.get_type(),
+            other.get_type(),
+        ))
+    }
+    fn get_support(&self, _v: &Matrix<f64, 4, 1>) -> Option<Matrix<f64, 4, 1>> {
+        None
+    }
+}
+
+#[test]
+fn test_dispatch_swapped() {
+    let a = Sphere::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = Ray::init(&[-5f64, 0f64, 0f64], &[1f64, 0f64, 0f64]);
+    let d = Dispatcher::init();
+    let ab = d.get_intersect(&a, &b).expect("sphere ray unexpected");
+    let ba = d.get_intersect(&b, &a).expect("ray sphere unexpected");
+    assert_matrix_approx_eq_float(&ab._normal, &(ba._normal * -1f64), 1e-9);
+    assert_matrix_approx_eq_float(&ab._point_a, &ba._point_b, 1e-9);
+}
+#[test]
+fn test_dispatch_registered_complex() {
+    fn blob_sphere(
+        a: &dyn Shape<f64>,
+        b: &dyn Shape<f64>,
+    ) -> Result<Option<Contact<f64>>, CollideError> {
+        let blob: &Blob = downcast(a)?;
+        blob._inner.try_intersect(b)
+    }
+    let a = Blob {
+        _inner: Sphere::init(&[0f64, 0f64, 0f64], 1f64),
+    };
+    let b = Sphere::init(&[1.5f64, 0f64, 0f64], 1f64);
+
+    let mut d = Dispatcher::init();
+    assert_eq!(
+        d.try_intersect(&a, &b).err(),
+        Some(CollideError::UnsupportedPair(
+            ShapeType::Complex,
+            ShapeType::Sphere
+        ))
+    );
+
+    d.register(ShapeType::Complex, ShapeType::Sphere, blob_sphere);
+    let c = d.get_intersect(&a, &b).expect("blob sphere unexpected");
+    assert!((c._depth - 0.5f64).abs() < 1e-9);
+    assert_matrix_approx_eq_float(
+        &c._normal,
+        &Matrix::from([[1f64, 0f64, 0f64, 0f64]]).t(),
+        1e-9,
+    );
+
+    let c = d.get_intersect(&b, &a).expect("sphere blob unexpected");
+    assert!((c._depth - 0.5f64).abs() < 1e-9);
+    assert_matrix_approx_eq_float(
+        &c._normal,
+        &Matrix::from([[-1f64, 0f64, 0f64, 0f64]]).t(),
+        1e-9,
+    );
+}
+#[test]
+fn test_dispatch_gjk_fallback() {
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = RectBox::init(&[0.75f64, 0.25f64, 1.4f64], 0.5f64);
+    let c = try_intersect(&a, &b)
+        .expect("fallback unexpected error")
+        .expect("fallback unexpected miss");
+    assert!((c._depth - 0.1f64).abs() < 1e-6);
+    assert_matrix_approx_eq_float(
+        &c._normal,
+        &Matrix::from([[0f64, 0f64, 1f64, 0f64]]).t(),
+        1e-6,
+    );
+
+    let b = RectBox::init(&[0.75f64, 0.25f64, 1.6f64], 0.5f64);
+    assert!(try_intersect(&a, &b)
+        .expect("fallback unexpected error")
+        .is_none());
+}
+#[test]
+fn test_dispatch_unsupported() {
+    //neither shape has a support mapping to fall back on
+    let a = Plane::init(&[0f64, 0f64, 0f64], &[0f64, 1f64, 0f64]);
+    let b = Blob {
+        _inner: Sphere::init(&[0f64, 0f64, 0f64], 1f64),
+    };
+    assert_eq!(
+        try_intersect(&a, &b).err(),
+        Some(CollideError::UnsupportedPair(
+            ShapeType::Plane,
+            ShapeType::Complex
+        ))
+    );
+    assert_eq!(
+        try_intersect(&b, &a).err(),
+        Some(CollideError::UnsupportedPair(
+            ShapeType::Complex,
+            ShapeType::Plane
+        ))
+    );
+}
+
+///one shape of every type, all overlapping around the origin
+#[cfg(test)]
+fn shapes_at_origin() -> Vec<Box<dyn Shape<f64>>> {
+    let rot = QuatR::init_from_axis_angle_degree(Matrix::from([[0., 0., 1.]]).t(), 30.);
+    let mut hexagon = vec![];
+    for i in 0..6 {
+        let a = std::f64::consts::PI / 3. * i as f64;
+        hexagon.extend_from_slice(&[a.cos(), a.sin(), 0.05]);
+    }
+    let mut cube = vec![];
+    for i in 0..8 {
+        for axis in 0..3 {
+            cube.push(if i & (1 << axis) == 0 { -0.8 } else { 0.8 });
+        }
+    }
+    vec![
+        Box::new(Point::init(&[0., 0., 0.])),
+        Box::new(Ray::init(&[-3., 0.1, 0.2], &[1., 0., 0.])),
+        Box::new(Sphere::init(&[0., 0., 0.], 1.)),
+        Box::new(Plane::init(&[0., 0., 0.], &[0., 1., 0.])),
+        Box::new(Triangle::init(&[
+            -1., -0.5, -1., 1., -0.5, -1., 0., 0.5, 1.,
+        ])),
+        Box::new(RectBox::init(&[0., 0., 0.], 1.)),
+        Box::new(Cuboid::init(&[0.2, 0., 0.], &[1., 0.5, 1.])),
+        Box::new(TriPrism::init(
+            &[-1., -1., -0.5, 1., -1., -0.5, 0., 1., -0.5],
+            1.,
+        )),
+        Box::new(Line::init(&[-2., -0.1, 0.05], &[2., 0.1, 0.05])),
+        Box::new(OrientedBox::init(&[0., 0., 0.], &[1., 1., 1.], rot)),
+        Box::new(Frustum::init([
+            Plane::init(&[-0.5, -0.5, 0.], &[-1., -1., 0.]),
+            Plane::init(&[0.5, 0.5, 0.], &[1., 1., 0.]),
+            Plane::init(&[0.5, -0.5, 0.], &[1., -1., 0.]),
+            Plane::init(&[-0.5, 0.5, 0.], &[-1., 1., 0.]),
+            Plane::init(&[0., 0., 1.], &[0., 0., 1.]),
+            Plane::init(&[0., 0., -1.], &[0., 0., -1.]),
+        ])),
+        Box::new(Capsule::init(&[-1., 0., 0.], &[1., 0., 0.], 0.5)),
+        Box::new(Cylinder::init(&[0., 0., 0.], &[0., 1., 0.], 1., 1.)),
+        Box::new(Cone::init(&[0., 1., 0.], &[0., -1., 0.], 2., 1.)),
+        Box::new(Ellipsoid::init(&[0., 0., 0.], &[1., 0.5, 0.8], None)),
+        Box::new(ConvexPolygon::init(&hexagon)),
+        Box::new(ConvexHull::init(&cube)),
+        Box::new(TriMesh::init(
+            &[-2., 0.05, -2., 2., 0.05, -2., 2., 0.05, 2., -2., 0.05, 2.],
+            &[0, 1, 2, 0, 2, 3],
+        )),
+        Box::new(HeightField::init(&[-2., 0., -2.], &[0.1; 9], 3, &[2., 2.])),
+        Box::new(Compound::init(vec![CompoundChild::init(
+            Box::new(Sphere::init(&[0., 0., 0.], 0.5)),
+            &[0., 0.2, 0.],
+            QuatR::init(0., 0., 0., 1.),
+        )])),
+    ]
+}
+
+#[test]
+fn test_dispatch_all_pairs() {
+    let shapes = shapes_at_origin();
+    let mut failures = vec![];
+    for (i, a) in shapes.iter().enumerate() {
+        for b in shapes.iter().skip(i + 1) {
+            let (ta, tb) = (a.get_type(), b.get_type());
+            //the shapes' own queries go through the dispatcher as well
+            match (a.try_intersect(&**b), b.try_intersect(&**a)) {
+                (Ok(Some(x)), Ok(Some(y))) => {
+                    if (x._depth - y._depth).abs() > 1e-6
+                        || (x._normal + y._normal).norm_l2() > 1e-6
+                    {
+                        failures.push(format!(
+                            "{:?} {:?}: contact differs by order {:?} {:?}",
+                            ta, tb, x, y
+                        ));
+                    }
+                }
+                (Ok(None), Ok(None)) => {}
+                (ab, ba) => failures.push(format!(
+                    "{:?} {:?}: {:?} {:?}",
+                    ta,
+                    tb,
+                    ab.map(|c| c.is_some()),
+                    ba.map(|c| c.is_some())
+                )),
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{:#?}", failures);
+}
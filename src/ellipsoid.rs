@@ -0,0 +1,419 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::dispatch;
+use crate::error::CollideError;
+use crate::plane::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::shape::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use lightmatrix::quatr::QuatR;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+#[cfg(test)]
+use crate::sphere::*;
+
+///solid ellipsoid centered at _ori with a radius along each of its rotated axes
+#[derive(Debug, Clone)]
+pub struct Ellipsoid<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _ori: Matrix<T, 4, 1>,
+    pub _radii: [T; 3],
+    pub _rot: QuatR<T>,
+    ///local axes rotated into world space
+    pub _axes: [Matrix<T, 4, 1>; 3],
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+impl<T> Ellipsoid<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///ellipsoid aligned with the world axes unless a rotation is given
+    pub fn init(origin: &[T], radii: &[T], rot: Option<QuatR<T>>) -> Ellipsoid<T> {
+        assert!(origin.len() == 3);
+        assert!(radii.len() == 3);
+        let mut e = Ellipsoid {
+            _ori: Matrix::from([[origin[0], origin[1], origin[2], T::one()]]).t(),
+            _radii: [radii[0], radii[1], radii[2]],
+            _rot: QuatR::default(),
+            _axes: [Matrix::zero(); 3],
+            _bound: AxisAlignedBBox::default(),
+            _vicinity: T::from(1e-7).unwrap(),
+        };
+        e.set_rotation(
+            rot.unwrap_or_else(|| QuatR::init(T::zero(), T::zero(), T::zero(), T::one())),
+        );
+        e
+    }
+
+    ///updates the axes and the enclosing bound
+    pub fn set_rotation(&mut self, rot: QuatR<T>) {
+        let m = rot.to_matrix();
+        for (j, axis) in self._axes.iter_mut().enumerate() {
+            *axis = Matrix::from([[m[[0, j]], m[[1, j]], m[[2, j]], T::zero()]]).t();
+        }
+        self._rot = rot;
+        self.update_bound();
+    }
+
+    ///updates the enclosing bound
+    pub fn set_origin(&mut self, origin: &[T]) {
+        assert!(origin.len() == 3);
+        self._ori = Matrix::from([[origin[0], origin[1], origin[2], T::one()]]).t();
+        self.update_bound();
+    }
+
+    fn update_bound(&mut self) {
+        let mut vals = [T::zero(); 6];
+        for i in 0..3 {
+            let extent = (0..3)
+                .fold(T::zero(), |acc, j| {
+                    let x = self._axes[j][[i, 0]] * self._radii[j];
+                    acc + x * x
+                })
+                .sqrt();
+            vals[i] = self._ori[[i, 0]] - extent;
+            vals[i + 3] = self._ori[[i, 0]] + extent;
+        }
+        self._bound = AxisAlignedBBox::new(ShapeType::Rect, &vals);
+    }
+
+    ///coordinates of a world point along the ellipsoid's axes relative to its center
+    pub fn get_local(&self, p: &Matrix<T, 4, 1>) -> [T; 3] {
+        let d = *p - self._ori;
+        [
+            d.inner(&self._axes[0]),
+            d.inner(&self._axes[1]),
+            d.inner(&self._axes[2]),
+        ]
+    }
+
+    ///world point from coordinates along the ellipsoid's axes
+    fn get_world(&self, l: &[T; 3]) -> Matrix<T, 4, 1> {
+        self._ori + self._axes[0] * l[0] + self._axes[1] * l[1] + self._axes[2] * l[2]
+    }
+
+    pub fn contains(&self, p: &Matrix<T, 4, 1>) -> bool {
+        let l = self.get_local(p);
+        (0..3).fold(T::zero(), |acc, j| {
+            let x = l[j] / self._radii[j];
+            acc + x * x
+        }) <= T::one()
+    }
+
+    ///outward surface normal at a point on the surface
+    pub fn get_normal(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        let l = self.get_local(p);
+        (0..3)
+            .fold(Matrix::zero(), |acc, j| {
+                acc + self._axes[j] * (l[j] / (self._radii[j] * self._radii[j]))
+            })
+            .normalize_l2()
+    }
+
+    ///nearest point on the surface to a point inside of the ellipsoid,
+    ///found by bisecting for the lagrange multiplier of the constrained distance
+    fn get_nearest_surface(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        let y = self.get_local(p);
+        let r = self._radii;
+        let r_min = r[0].min(r[1]).min(r[2]);
+        let f = |lambda: T| {
+            (0..3).fold(-T::one(), |acc, j| {
+                let x = r[j] * y[j] / (r[j] * r[j] + lambda);
+                acc + x * x
+            })
+        };
+        let two = T::from(2.).unwrap();
+        let (mut lo, mut hi) = (-r_min * r_min, T::zero());
+        for _ in 0..128 {
+            let mid = (lo + hi) / two;
+            if f(mid) > T::zero() {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut x = [T::zero(); 3];
+        for (j, xj) in x.iter_mut().enumerate() {
+            if r[j] != r_min || y[j] != T::zero() {
+                *xj = r[j] * r[j] * y[j] / (r[j] * r[j] + hi);
+            }
+        }
+        //a point on the plane across the shortest axis leaves its position along that axis to the constraint
+        if let Some(j) = (0..3).find(|j| r[*j] == r_min && y[*j] == T::zero()) {
+            let s = (0..3).fold(T::one(), |acc, k| {
+                let u = x[k] / r[k];
+                acc - u * u
+            });
+            x[j] = r[j] * s.max(T::zero()).sqrt();
+        }
+        self.get_world(&x)
+    }
+
+    ///parameter interval of ray ori + dir * t inside of the ellipsoid,
+    ///solved against the unit sphere after scaling each axis by its radius
+    pub fn intersect_ray(&self, ori: &Matrix<T, 4, 1>, dir: &Matrix<T, 4, 1>) -> Option<(T, T)> {
+        let l = self.get_local(ori);
+        let (mut a, mut b, mut c) = (T::zero(), T::zero(), -T::one());
+        for (j, x) in l.iter().enumerate() {
+            let o = *x / self._radii[j];
+            let d = dir.inner(&self._axes[j]) / self._radii[j];
+            a += d * d;
+            b += o * d;
+            c += o * o;
+        }
+        if a == T::zero() {
+            return None;
+        }
+        let disc = b * b - a * c;
+        if disc < T::zero() {
+            return None;
+        }
+        Some(((-b - disc.sqrt()) / a, (-b + disc.sqrt()) / a))
+    }
+}
+
+impl<T> AnyBase for Ellipsoid<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for Ellipsoid<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Ellipsoid
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._radii.iter().any(|r| r.is_nan() || *r <= T::zero()) {
+            return Err(CollideError::DegenerateShape(ShapeType::Ellipsoid));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let p = other_point._ori;
+                if !self.contains(&p) {
+                    return Ok(None);
+                }
+                //push the point out through the nearest point of the surface
+                let q = self.get_nearest_surface(&p);
+                Ok(Some(Contact {
+                    _point_a: q,
+                    _point_b: p,
+                    _normal: self.get_normal(&q),
+                    _depth: (q - p).norm_l2(),
+                    _feature: None,
+                }))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                let (t0, t1) = match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                if t1 < T::zero() {
+                    return Ok(None);
+                }
+                //ray starting inside of the ellipsoid hits on its way out
+                let t = if t0 >= T::zero() { t0 } else { t1 };
+                let hit = other_ray._ori + other_ray._dir * t;
+                Ok(Some(Contact::init_point(hit, self.get_normal(&hit))))
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                let n = other_plane._normal;
+                let s = (self._ori - other_plane._offset).inner(&n);
+                let extent = (0..3)
+                    .fold(T::zero(), |acc, j| {
+                        let x = self._axes[j].inner(&n) * self._radii[j];
+                        acc + x * x
+                    })
+                    .sqrt();
+                if s.abs() > extent {
+                    return Ok(None);
+                }
+                //the plane is pushed towards the side holding the lesser part of the ellipsoid
+                let normal = if s >= T::zero() { n * -T::one() } else { n };
+                let p = match self.get_support(&normal) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                Ok(Some(Contact {
+                    _point_a: p,
+                    _point_b: p - n * (p - other_plane._offset).inner(&n),
+                    _normal: normal,
+                    _depth: extent - s.abs(),
+                    _feature: None,
+                }))
+            }
+            ShapeType::Sphere
+            | ShapeType::Box
+            | ShapeType::Rect
+            | ShapeType::OrientedBox
+            | ShapeType::TriPrism
+            | ShapeType::Trig
+            | ShapeType::ConvexPolygon
+            | ShapeType::Frustum
+            | ShapeType::Capsule
+            | ShapeType::Cylinder
+            | ShapeType::Cone
+            | ShapeType::Ellipsoid
+            | ShapeType::ConvexHull => dispatch::try_intersect_convex(self, other),
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() {
+            //support of the unit sphere along the direction scaled into its space, then scaled back
+            let mut w = [T::zero(); 3];
+            for (j, x) in w.iter_mut().enumerate() {
+                *x = v.inner(&self._axes[j]) * self._radii[j];
+            }
+            let len = (w[0] * w[0] + w[1] * w[1] + w[2] * w[2]).sqrt();
+            let mut l = [T::zero(); 3];
+            for (j, x) in l.iter_mut().enumerate() {
+                *x = self._radii[j] * w[j] / len;
+            }
+            Some(self.get_world(&l))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for Ellipsoid<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+#[test]
+fn test_ellipsoid_bound_support() {
+    let a = Ellipsoid::init(&[0., 0., 0.], &[3., 2., 1.], None);
+    assert_eq!(a._bound.bound_lower, [-3., -2., -1.]);
+    assert_eq!(a._bound.bound_upper, [3., 2., 1.]);
+    let s = a
+        .get_support(&Matrix::from([[0., 0., 1., 0.]]).t())
+        .expect("ellipsoid support unexpected");
+    assert_eq!(s, Matrix::from([[0., 0., 1., 1.]]).t());
+    //support lies on the surface with its normal along the direction
+    let v = Matrix::from([[1., 1., 1., 0.]]).t();
+    let s = a.get_support(&v).expect("ellipsoid support unexpected");
+    let l = a.get_local(&s);
+    assert!(((l[0] / 3.).powi(2) + (l[1] / 2.).powi(2) + l[2].powi(2) - 1.).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&a.get_normal(&s), &v.normalize_l2(), 1e-12);
+
+    //turned about z, the longest axis ends up along y
+    let rot = QuatR::init_from_axis_angle_degree(Matrix::from([[0., 0., 1.]]).t(), 90.);
+    let a = Ellipsoid::init(&[1., 0., 0.], &[3., 2., 1.], Some(rot));
+    assert!((a._bound.bound_lower[0] + 1.).abs() < 1e-12);
+    assert!((a._bound.bound_upper[1] - 3.).abs() < 1e-12);
+    let s = a
+        .get_support(&Matrix::from([[0., 1., 0., 0.]]).t())
+        .expect("ellipsoid support unexpected");
+    assert_matrix_approx_eq_float(&s, &Matrix::from([[1., 3., 0., 1.]]).t(), 1e-12);
+
+    let b = Sphere::init(&[1., 3.4, 0.], 0.5);
+    let c = a
+        .get_intersect(&b)
+        .expect("ellipsoid sphere unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-6);
+    assert!(a.get_intersect(&Sphere::init(&[3., 3., 0.], 0.5)).is_none());
+}
+#[test]
+fn test_intersect_ellipsoid_point() {
+    let a = Ellipsoid::init(&[0., 0., 0.], &[3., 2., 1.], None);
+    let c = a
+        .get_intersect(&Point::init(&[0., 0., 0.5]))
+        .expect("ellipsoid point unexpected miss");
+    assert!((c._depth - 0.5).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-12);
+    //nearest point off the axes is closer than the end of the long axis
+    let c = a
+        .get_intersect(&Point::init(&[2., 0., 0.]))
+        .expect("ellipsoid point unexpected miss");
+    assert!((c._depth - 0.5f64.sqrt()).abs() < 1e-9);
+    assert!((c._point_a[[0, 0]] - 2.25).abs() < 1e-9);
+    //inside of the bound but outside of the surface
+    assert!(a.get_intersect(&Point::init(&[2.5, 1.5, 0.])).is_none());
+}
+#[test]
+fn test_intersect_ellipsoid_ray() {
+    let rot = QuatR::init_from_axis_angle_degree(Matrix::from([[0., 0., 1.]]).t(), 90.);
+    let a = Ellipsoid::init(&[0., 0., 0.], &[3., 2., 1.], Some(rot));
+    let b = Ray::init(&[0., 5., 0.], &[0., -1., 0.]);
+    let c = a.get_intersect(&b).expect("ellipsoid ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 3., 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+    let b = Ray::init(&[-5., 0., 0.], &[1., 0., 0.]);
+    let c = b.get_intersect(&a).expect("ray ellipsoid unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-2., 0., 0., 1.]]).t(), 1e-12);
+    //starting inside
+    let b = Ray::init(&[0., 0., 0.], &[0., 0., 1.]);
+    let c = a.get_intersect(&b).expect("ellipsoid ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 0., 1., 1.]]).t(), 1e-12);
+
+    assert!(a
+        .get_intersect(&Ray::init(&[-5., 0., 1.1], &[1., 0., 0.]))
+        .is_none());
+    assert!(a
+        .get_intersect(&Ray::init(&[-5., 0., 0.], &[-1., 0., 0.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_ellipsoid_plane() {
+    let a = Ellipsoid::init(&[0., 0.5, 0.], &[3., 2., 1.], None);
+    let b = Plane::init(&[0., 0., 0.], &[0., 1., 0.]);
+    let c = a
+        .get_intersect(&b)
+        .expect("ellipsoid plane unexpected miss");
+    assert!((c._depth - 1.5).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., -1.5, 0., 1.]]).t(), 1e-12);
+    let c = b
+        .get_intersect(&a)
+        .expect("plane ellipsoid unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+    assert!(a
+        .get_intersect(&Plane::init(&[0., 2.6, 0.], &[0., 1., 0.]))
+        .is_none());
+}
+#[test]
+fn test_try_intersect_ellipsoid_degenerate() {
+    let a = Ellipsoid::init(&[0., 0., 0.], &[3., 0., 1.], None);
+    assert_eq!(
+        a.try_intersect(&Point::init(&[0., 0., 0.])).err(),
+        Some(CollideError::DegenerateShape(ShapeType::Ellipsoid))
+    );
+}
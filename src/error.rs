@@ -0,0 +1,29 @@
+use crate::shape::ShapeType;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollideError {
+    ///no algorithm is available for the pair of shapes
+    UnsupportedPair(ShapeType, ShapeType),
+    ///shape has invalid parameters, eg: zero length direction or collinear vertices
+    DegenerateShape(ShapeType),
+    ///iterative algorithm did not terminate within its iteration limit
+    NonConvergence,
+    ///shape reported a type that does not match its concrete implementation
+    DowncastFailed(ShapeType),
+}
+
+impl fmt::Display for CollideError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CollideError::UnsupportedPair(a, b) => {
+                write!(f, "unsupported shape pair: {:?}, {:?}", a, b)
+            }
+            CollideError::DegenerateShape(a) => write!(f, "degenerate shape: {:?}", a),
+            CollideError::NonConvergence => write!(f, "algorithm did not converge"),
+            CollideError::DowncastFailed(a) => write!(f, "cast to {:?} failed", a),
+        }
+    }
+}
+
+impl std::error::Error for CollideError {}
@@ -0,0 +1,567 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::cuboid::*;
+use crate::dispatch;
+use crate::error::CollideError;
+use crate::intersect_gjk::try_query_intersect;
+use crate::intersect_sat;
+use crate::oriented_box::*;
+use crate::plane::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::rect_box::*;
+use crate::shape::*;
+use crate::sphere::*;
+use crate::tri_prism::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+///result of a culling test against a frustum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Intersecting,
+    Outside,
+}
+
+#[derive(Debug, Clone)]
+pub struct Frustum<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///left, right, bottom, top, near, far planes with normals pointing out of the frustum
+    pub _planes: [Plane<T>; 6],
+
+    ///near corners: left bottom, right bottom, right top, left top, followed by far corners in the same order
+    pub _corners: [Matrix<T, 4, 1>; 8],
+
+    pub _bound: AxisAlignedBBox<T>,
+
+    pub _vicinity: T,
+}
+
+///intersection point of 3 planes
+fn intersect_planes<T>(a: &Plane<T>, b: &Plane<T>, c: &Plane<T>) -> Matrix<T, 4, 1>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let bc = b._normal.cross(&c._normal);
+    let ca = c._normal.cross(&a._normal);
+    let ab = a._normal.cross(&b._normal);
+    let denom = a._normal.inner(&bc);
+    let mut p = (bc * a._normal.inner(&a._offset)
+        + ca * b._normal.inner(&b._offset)
+        + ab * c._normal.inner(&c._offset))
+        / denom;
+    p[[3, 0]] = T::one();
+    p
+}
+
+impl<T> Frustum<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    /// initialize with left, right, bottom, top, near, far planes, normals pointing out of the frustum
+    pub fn init(planes: [Plane<T>; 6]) -> Frustum<T> {
+        let triples = [
+            [0, 2, 4],
+            [1, 2, 4],
+            [1, 3, 4],
+            [0, 3, 4],
+            [0, 2, 5],
+            [1, 2, 5],
+            [1, 3, 5],
+            [0, 3, 5],
+        ];
+        let mut corners = [Matrix::zero(); 8];
+        for (c, t) in corners.iter_mut().zip(triples.iter()) {
+            *c = intersect_planes(&planes[t[0]], &planes[t[1]], &planes[t[2]]);
+        }
+        let mut vals = vec![];
+        for c in corners.iter() {
+            vals.extend_from_slice(&[c[[0, 0]], c[[1, 0]], c[[2, 0]]]);
+        }
+        Frustum {
+            _planes: planes,
+            _corners: corners,
+            _bound: AxisAlignedBBox::new(ShapeType::Frustum, &vals),
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+
+    /// extract the frustum from a view projection matrix m, mapping column vectors to clip space
+    /// with -w <= x, y, z <= w inside of the frustum
+    pub fn init_from_matrix(m: &Matrix<T, 4, 4>) -> Frustum<T> {
+        let row = |i: usize| [m[[i, 0]], m[[i, 1]], m[[i, 2]], m[[i, 3]]];
+        let w = row(3);
+        let plane = |r: [T; 4], sign: T| {
+            //inside: dot((a, b, c), x) + d >= 0
+            let (a, b, c, d) = (
+                w[0] + r[0] * sign,
+                w[1] + r[1] * sign,
+                w[2] + r[2] * sign,
+                w[3] + r[3] * sign,
+            );
+            let l2 = a * a + b * b + c * c;
+            Plane::init(&[-d * a / l2, -d * b / l2, -d * c / l2], &[-a, -b, -c])
+        };
+        Frustum::init([
+            plane(row(0), T::one()),
+            plane(row(0), -T::one()),
+            plane(row(1), T::one()),
+            plane(row(1), -T::one()),
+            plane(row(2), T::one()),
+            plane(row(2), -T::one()),
+        ])
+    }
+
+    ///signed distance of p to each of the planes, positive outside
+    fn get_distances(&self, p: &Matrix<T, 4, 1>) -> [T; 6] {
+        let mut d = [T::zero(); 6];
+        for (x, plane) in d.iter_mut().zip(self._planes.iter()) {
+            *x = (*p - plane._offset).inner(&plane._normal);
+        }
+        d
+    }
+
+    ///entering and leaving parameters and planes of ray ori + dir * t clipped by the planes
+    pub fn intersect_ray(
+        &self,
+        ori: &Matrix<T, 4, 1>,
+        dir: &Matrix<T, 4, 1>,
+    ) -> Option<((T, usize), (T, usize))> {
+        let mut t_near = (T::neg_infinity(), 0);
+        let mut t_far = (T::infinity(), 0);
+        let dists = self.get_distances(ori);
+        for (i, plane) in self._planes.iter().enumerate() {
+            let denom = plane._normal.inner(dir);
+            if denom == T::zero() {
+                if dists[i] > self._vicinity {
+                    return None;
+                }
+                continue;
+            }
+            let t = -dists[i] / denom;
+            if denom < T::zero() {
+                if t > t_near.0 {
+                    t_near = (t, i);
+                }
+            } else if t < t_far.0 {
+                t_far = (t, i);
+            }
+            if t_near.0 > t_far.0 {
+                return None;
+            }
+        }
+        Some((t_near, t_far))
+    }
+
+    pub fn classify_point(&self, p: &Matrix<T, 4, 1>) -> Containment {
+        if self.get_distances(p).iter().any(|d| *d > self._vicinity) {
+            Containment::Outside
+        } else {
+            Containment::Inside
+        }
+    }
+
+    ///conservative, spheres near edges and corners outside of the frustum may be reported as intersecting
+    pub fn classify_sphere(&self, center: &Matrix<T, 4, 1>, radius: T) -> Containment {
+        let mut ret = Containment::Inside;
+        for d in self.get_distances(center).iter() {
+            if *d > radius + self._vicinity {
+                return Containment::Outside;
+            } else if *d > -radius {
+                ret = Containment::Intersecting;
+            }
+        }
+        ret
+    }
+
+    ///conservative test of the convex hull of the vertices
+    pub fn classify_vertices(&self, verts: &[Matrix<T, 4, 1>]) -> Containment {
+        let mut ret = Containment::Inside;
+        for plane in self._planes.iter() {
+            let (lo, hi) = verts
+                .iter()
+                .fold((T::infinity(), T::neg_infinity()), |(lo, hi), v| {
+                    let d = (*v - plane._offset).inner(&plane._normal);
+                    (lo.min(d), hi.max(d))
+                });
+            if lo > self._vicinity {
+                return Containment::Outside;
+            } else if hi > self._vicinity {
+                ret = Containment::Intersecting;
+            }
+        }
+        ret
+    }
+
+    ///conservative, see classify_aabb_exact
+    pub fn classify_aabb(&self, b: &AxisAlignedBBox<T>) -> Containment {
+        if !is_finite(b) {
+            return Containment::Intersecting;
+        }
+        self.classify_vertices(&aabb_corners(b))
+    }
+
+    pub fn classify_aabb_exact(&self, b: &AxisAlignedBBox<T>) -> Containment {
+        let c = self.classify_aabb(b);
+        if c != Containment::Intersecting || !is_finite(b) {
+            return c;
+        }
+        let box_axes = [
+            Matrix::from([[T::one(), T::zero(), T::zero(), T::zero()]]).t(),
+            Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t(),
+            Matrix::from([[T::zero(), T::zero(), T::one(), T::zero()]]).t(),
+        ];
+        let mut axes: Vec<Matrix<T, 4, 1>> = self._planes.iter().map(|p| p._normal).collect();
+        axes.extend(box_axes.iter().cloned());
+        for e in self.get_edge_directions().iter() {
+            for b in box_axes.iter() {
+                axes.push(e.cross(b));
+            }
+        }
+        match intersect_sat::query_overlap(&axes, &self._corners, &aabb_corners(b)) {
+            Some(_) => Containment::Intersecting,
+            _ => Containment::Outside,
+        }
+    }
+
+    ///directions of the 12 edges, parallel edges are listed once
+    pub fn get_edge_directions(&self) -> [Matrix<T, 4, 1>; 6] {
+        let c = &self._corners;
+        [
+            c[1] - c[0],
+            c[3] - c[0],
+            c[4] - c[0],
+            c[5] - c[1],
+            c[6] - c[2],
+            c[7] - c[3],
+        ]
+    }
+}
+
+impl<T> Frustum<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    ///conservative test of Point, Sphere, RectBox, Cuboid, OrientedBox and TriPrism against the frustum
+    pub fn classify(&self, other: &dyn Shape<T>) -> Result<Containment, CollideError> {
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                Ok(self.classify_point(&other_point._ori))
+            }
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
+                Ok(self.classify_sphere(&other_sphere._ori, other_sphere._radius))
+            }
+            ShapeType::Box => {
+                let other_box: &RectBox<T> = downcast(other)?;
+                Ok(self.classify_vertices(&other_box.get_corners()))
+            }
+            ShapeType::Rect => {
+                let other_cuboid: &Cuboid<T> = downcast(other)?;
+                Ok(self.classify_vertices(&other_cuboid.get_corners()))
+            }
+            ShapeType::OrientedBox => {
+                let other_box: &OrientedBox<T> = downcast(other)?;
+                Ok(self.classify_vertices(&other_box.get_corners()))
+            }
+            ShapeType::TriPrism => {
+                let other_prism: &TriPrism<T> = downcast(other)?;
+                Ok(self.classify_vertices(&other_prism.get_vertices()))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                ShapeType::Frustum,
+                other.get_type(),
+            )),
+        }
+    }
+
+    ///exact version of classify, shapes reported as intersecting are verified with gjk
+    pub fn classify_exact(&self, other: &dyn Shape<T>) -> Result<Containment, CollideError> {
+        match self.classify(other)? {
+            Containment::Intersecting => {
+                if try_query_intersect(self, other)? {
+                    Ok(Containment::Intersecting)
+                } else {
+                    Ok(Containment::Outside)
+                }
+            }
+            c => Ok(c),
+        }
+    }
+}
+
+fn is_finite<T>(b: &AxisAlignedBBox<T>) -> bool
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    b.bound_lower
+        .iter()
+        .chain(b.bound_upper.iter())
+        .all(|x| x.is_finite())
+}
+
+fn aabb_corners<T>(b: &AxisAlignedBBox<T>) -> [Matrix<T, 4, 1>; 8]
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let mut corners = [Matrix::zero(); 8];
+    for (i, c) in corners.iter_mut().enumerate() {
+        for axis in 0..3 {
+            c[[axis, 0]] = if i & (1 << axis) == 0 {
+                b.bound_lower[axis]
+            } else {
+                b.bound_upper[axis]
+            };
+        }
+        c[[3, 0]] = T::one();
+    }
+    corners
+}
+
+impl<T> AnyBase for Frustum<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for Frustum<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Frustum
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._corners.iter().any(|c| !c.norm_l2().is_finite()) {
+            return Err(CollideError::DegenerateShape(ShapeType::Frustum));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point
+            | ShapeType::Sphere
+            | ShapeType::Box
+            | ShapeType::Rect
+            | ShapeType::OrientedBox
+            | ShapeType::TriPrism
+            | ShapeType::Trig
+            | ShapeType::ConvexPolygon
+            | ShapeType::Frustum
+            | ShapeType::Capsule
+            | ShapeType::Cylinder
+            | ShapeType::Cone
+            | ShapeType::Ellipsoid
+            | ShapeType::ConvexHull => dispatch::try_intersect_convex(self, other),
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                let (near, far) = match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                if far.0 < T::zero() {
+                    return Ok(None);
+                }
+                //ray starting inside of the frustum hits on its way out
+                let (t, i) = if near.0 >= T::zero() { near } else { far };
+                Ok(Some(
+                    Contact::init_point(
+                        other_ray._ori + other_ray._dir * t,
+                        self._planes[i]._normal,
+                    )
+                    .with_feature(i),
+                ))
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                Ok(other_plane.intersect_convex(self))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() {
+            //get a furthest point in the given direction v
+            let furthest = self
+                ._corners
+                .iter()
+                .map(|x| x.inner(v))
+                .enumerate()
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap();
+            Some(self._corners[furthest.0])
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for Frustum<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+#[cfg(test)]
+fn perspective(near: f64, far: f64) -> Matrix<f64, 4, 4> {
+    //90 degree field of view, aspect ratio of 1, looking down -z
+    Matrix::from([
+        [1., 0., 0., 0.],
+        [0., 1., 0., 0.],
+        [
+            0.,
+            0.,
+            (far + near) / (near - far),
+            2. * far * near / (near - far),
+        ],
+        [0., 0., -1., 0.],
+    ])
+}
+
+#[cfg(test)]
+fn diamond() -> Frustum<f64> {
+    //|x| + |y| <= 1, |z| <= 1
+    Frustum::init([
+        Plane::init(&[-0.5, -0.5, 0.], &[-1., -1., 0.]),
+        Plane::init(&[0.5, 0.5, 0.], &[1., 1., 0.]),
+        Plane::init(&[0.5, -0.5, 0.], &[1., -1., 0.]),
+        Plane::init(&[-0.5, 0.5, 0.], &[-1., 1., 0.]),
+        Plane::init(&[0., 0., 1.], &[0., 0., 1.]),
+        Plane::init(&[0., 0., -1.], &[0., 0., -1.]),
+    ])
+}
+
+#[test]
+fn test_frustum_from_matrix() {
+    let m = Matrix::from([
+        [1., 0., 0., 0.],
+        [0., 1., 0., 0.],
+        [0., 0., 1., 0.],
+        [0., 0., 0., 1.],
+    ]);
+    let a = Frustum::init_from_matrix(&m);
+    assert_matrix_approx_eq_float(
+        &a._corners[0],
+        &Matrix::from([[-1., -1., -1., 1.]]).t(),
+        1e-9,
+    );
+    assert_matrix_approx_eq_float(&a._corners[6], &Matrix::from([[1., 1., 1., 1.]]).t(), 1e-9);
+    assert_eq!(a._bound.bound_lower, [-1., -1., -1.]);
+    assert_eq!(a._bound.bound_upper, [1., 1., 1.]);
+
+    let a = Frustum::init_from_matrix(&perspective(1., 10.));
+    assert_matrix_approx_eq_float(&a._corners[2], &Matrix::from([[1., 1., -1., 1.]]).t(), 1e-9);
+    assert_matrix_approx_eq_float(
+        &a._corners[4],
+        &Matrix::from([[-10., -10., -10., 1.]]).t(),
+        1e-9,
+    );
+}
+#[test]
+fn test_frustum_classify_point_sphere() {
+    let a = Frustum::init_from_matrix(&perspective(1., 10.));
+    let p = Point::init(&[0., 0., -5.]);
+    assert_eq!(a.classify(&p), Ok(Containment::Inside));
+    let p = Point::init(&[0., 0., -0.5]);
+    assert_eq!(a.classify(&p), Ok(Containment::Outside));
+    let p = Point::init(&[6., 0., -5.]);
+    assert_eq!(a.classify(&p), Ok(Containment::Outside));
+
+    let s = Sphere::init(&[0., 0., -5.], 1.);
+    assert_eq!(a.classify(&s), Ok(Containment::Inside));
+    let s = Sphere::init(&[0., 0., -10.5], 1.);
+    assert_eq!(a.classify(&s), Ok(Containment::Intersecting));
+    let s = Sphere::init(&[0., 0., -12.], 1.);
+    assert_eq!(a.classify(&s), Ok(Containment::Outside));
+
+    //near a corner of the frustum, outside of both side planes only in the exact test
+    let a = Frustum::init_from_matrix(&Matrix::from([
+        [1., 0., 0., 0.],
+        [0., 1., 0., 0.],
+        [0., 0., 1., 0.],
+        [0., 0., 0., 1.],
+    ]));
+    let s = Sphere::init(&[1.5, 1.5, 0.], 0.6);
+    assert_eq!(a.classify(&s), Ok(Containment::Intersecting));
+    assert_eq!(a.classify_exact(&s), Ok(Containment::Outside));
+}
+#[test]
+fn test_frustum_classify_box_prism() {
+    let a = Frustum::init_from_matrix(&perspective(1., 10.));
+    let b = RectBox::init(&[0., 0., -5.], 1.);
+    assert_eq!(a.classify(&b), Ok(Containment::Inside));
+    assert_eq!(a.classify_aabb(&b._bound), Containment::Inside);
+    let b = RectBox::init(&[0., 0., -1.], 0.5);
+    assert_eq!(a.classify_exact(&b), Ok(Containment::Intersecting));
+    let b = RectBox::init(&[20., 0., -5.], 1.);
+    assert_eq!(a.classify_exact(&b), Ok(Containment::Outside));
+
+    let b = TriPrism::init(&[0., 0., -5., 1., 0., -5., 1., 1., -5.], 1.);
+    assert_eq!(a.classify(&b), Ok(Containment::Inside));
+    let b = TriPrism::init(&[0., 0., -10.5, 1., 0., -10.5, 1., 1., -10.5], 1.);
+    assert_eq!(a.classify_exact(&b), Ok(Containment::Intersecting));
+
+    assert_eq!(
+        a.classify(&Plane::init(&[0., 0., 0.], &[0., 1., 0.])),
+        Err(CollideError::UnsupportedPair(
+            ShapeType::Frustum,
+            ShapeType::Plane
+        ))
+    );
+}
+#[test]
+fn test_frustum_classify_aabb_exact() {
+    let a = diamond();
+    let b = AxisAlignedBBox::new(ShapeType::Rect, &[1.05, -0.5, -0.5, 2., 0.5, 0.5]);
+    assert_eq!(a.classify_aabb(&b), Containment::Intersecting);
+    assert_eq!(a.classify_aabb_exact(&b), Containment::Outside);
+
+    let b = AxisAlignedBBox::new(ShapeType::Rect, &[0.9, -0.5, -0.5, 2., 0.5, 0.5]);
+    assert_eq!(a.classify_aabb_exact(&b), Containment::Intersecting);
+
+    let b = AxisAlignedBBox::new(ShapeType::Rect, &[-0.2, -0.2, -0.2, 0.2, 0.2, 0.2]);
+    assert_eq!(a.classify_aabb_exact(&b), Containment::Inside);
+}
+#[test]
+fn test_intersect_frustum_sphere() {
+    let a = diamond();
+    let b = Sphere::init(&[0., 0., 1.5], 0.6);
+    let c = a.get_intersect(&b).expect("frustum sphere unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-3);
+    let b = Sphere::init(&[0., 0., 1.5], 0.4);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_frustum_swapped() {
+    let a = diamond();
+    let b = RectBox::init(&[0., 0., 1.4], 0.5);
+    let c = b.get_intersect(&a).expect("box frustum unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-6);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-6);
+}
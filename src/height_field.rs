@@ -0,0 +1,557 @@
+use crate::bound::{Bound, BoundType};
+use crate::contact::Contact;
+use crate::dispatch;
+use crate::error::CollideError;
+use crate::point::*;
+use crate::ray::*;
+use crate::shape::*;
+use crate::triangle::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+#[cfg(test)]
+use crate::capsule::*;
+#[cfg(test)]
+use crate::cone::*;
+#[cfg(test)]
+use crate::cylinder::*;
+#[cfg(test)]
+use crate::rect_box::*;
+#[cfg(test)]
+use crate::sphere::*;
+
+///terrain on a regular grid in the xz plane with heights along y, solid below the surface
+///down to the lowest sample, each cell is split into 2 triangles along its diagonal
+#[derive(Debug, Clone)]
+pub struct HeightField<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _ori: Matrix<T, 4, 1>,
+    pub _heights: Vec<T>,
+    pub _cols: usize,
+    pub _rows: usize,
+    pub _spacing: [T; 2],
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+impl<T> HeightField<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///heights are given row by row, _cols samples along x per row, rows advance along z,
+    ///spacing is the distance between samples along x and z
+    pub fn init(origin: &[T], heights: &[T], cols: usize, spacing: &[T; 2]) -> HeightField<T> {
+        assert!(origin.len() == 3);
+        assert!(cols > 0);
+        assert_eq!(heights.len() % cols, 0);
+        let rows = heights.len() / cols;
+        let (h_min, h_max) = heights
+            .iter()
+            .fold((T::infinity(), T::neg_infinity()), |acc, h| {
+                (acc.0.min(*h), acc.1.max(*h))
+            });
+        let span_x = spacing[0] * T::from(cols.max(1) - 1).unwrap();
+        let span_z = spacing[1] * T::from(rows.max(1) - 1).unwrap();
+        let vals = [
+            origin[0],
+            origin[1] + h_min,
+            origin[2],
+            origin[0] + span_x,
+            origin[1] + h_max,
+            origin[2] + span_z,
+        ];
+        HeightField {
+            _ori: Matrix::from([[origin[0], origin[1], origin[2], T::one()]]).t(),
+            _heights: heights.to_vec(),
+            _cols: cols,
+            _rows: rows,
+            _spacing: *spacing,
+            _bound: AxisAlignedBBox::new(ShapeType::Rect, &vals),
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+
+    ///world position of sample (i, j)
+    pub fn get_vertex(&self, i: usize, j: usize) -> Matrix<T, 4, 1> {
+        let offset = Matrix::from([[
+            self._spacing[0] * T::from(i).unwrap(),
+            self._heights[j * self._cols + i],
+            self._spacing[1] * T::from(j).unwrap(),
+            T::zero(),
+        ]])
+        .t();
+        self._ori + offset
+    }
+
+    ///the 2 triangles of cell (i, j), both facing +y
+    pub fn get_cell_triangles(&self, i: usize, j: usize) -> [Triangle<T>; 2] {
+        let v00 = self.get_vertex(i, j);
+        let v10 = self.get_vertex(i + 1, j);
+        let v01 = self.get_vertex(i, j + 1);
+        let v11 = self.get_vertex(i + 1, j + 1);
+        let tri = |a: Matrix<T, 4, 1>, b: Matrix<T, 4, 1>, c: Matrix<T, 4, 1>| {
+            let mut v = [T::zero(); 9];
+            for (k, p) in [a, b, c].iter().enumerate() {
+                for m in 0..3 {
+                    v[k * 3 + m] = p[[m, 0]];
+                }
+            }
+            Triangle::init(&v)
+        };
+        [tri(v00, v01, v10), tri(v10, v01, v11)]
+    }
+
+    ///feature index of triangle k of cell (i, j)
+    pub fn get_feature(&self, i: usize, j: usize, k: usize) -> usize {
+        (j * (self._cols - 1) + i) * 2 + k
+    }
+
+    ///cell containing the xz coordinates of p, clamped to the grid
+    fn get_cell(&self, x: T, z: T) -> (usize, usize) {
+        let clamp = |v: T, n: usize| -> usize {
+            //clamped before the conversion, unbounded shapes give infinite extents
+            let c_max = n.saturating_sub(2);
+            v.floor()
+                .max(T::zero())
+                .min(T::from(c_max).unwrap())
+                .to_usize()
+                .unwrap_or(0)
+        };
+        (
+            clamp((x - self._ori[[0, 0]]) / self._spacing[0], self._cols),
+            clamp((z - self._ori[[2, 0]]) / self._spacing[1], self._rows),
+        )
+    }
+
+    ///range of cells overlapped by the xz extent of a bound, inclusive
+    pub fn get_cells(&self, bound: &dyn Bound<T>) -> Option<((usize, usize), (usize, usize))> {
+        let b = bound.get_bound_data();
+        let (lower, upper) = match bound.get_type() {
+            BoundType::AxisAlignBox => ([b[0], b[1], b[2]], [b[3], b[4], b[5]]),
+            BoundType::Sphere => (
+                [b[0] - b[3], b[1] - b[3], b[2] - b[3]],
+                [b[0] + b[3], b[1] + b[3], b[2] + b[3]],
+            ),
+        };
+        if !self._bound.intersect(bound) {
+            return None;
+        }
+        let lo = self.get_cell(lower[0], lower[2]);
+        let hi = self.get_cell(upper[0], upper[2]);
+        Some(((lo.0, hi.0), (lo.1, hi.1)))
+    }
+
+    ///surface height and up facing normal at the xz coordinates of p, None outside of the grid
+    pub fn get_height(&self, p: &Matrix<T, 4, 1>) -> Option<(T, Matrix<T, 4, 1>, usize)> {
+        let x = (p[[0, 0]] - self._ori[[0, 0]]) / self._spacing[0];
+        let z = (p[[2, 0]] - self._ori[[2, 0]]) / self._spacing[1];
+        let (x_max, z_max) = (
+            T::from(self._cols - 1).unwrap(),
+            T::from(self._rows - 1).unwrap(),
+        );
+        if x < T::zero() || z < T::zero() || x > x_max || z > z_max {
+            return None;
+        }
+        let (i, j) = self.get_cell(p[[0, 0]], p[[2, 0]]);
+        let (u, w) = (x - T::from(i).unwrap(), z - T::from(j).unwrap());
+        //first triangle covers u + w <= 1
+        let k = if u + w <= T::one() { 0 } else { 1 };
+        let tri = &self.get_cell_triangles(i, j)[k];
+        let n = tri._normal;
+        let h = tri._v[0][[1, 0]]
+            - ((p[[0, 0]] - tri._v[0][[0, 0]]) * n[[0, 0]]
+                + (p[[2, 0]] - tri._v[0][[2, 0]]) * n[[2, 0]])
+                / n[[1, 0]];
+        Some((h, n, self.get_feature(i, j, k)))
+    }
+
+    ///nearest hit of ray ori + dir * t by walking the cells under the ray,
+    ///returns t and feature index of the triangle hit
+    pub fn intersect_ray(
+        &self,
+        ori: &Matrix<T, 4, 1>,
+        dir: &Matrix<T, 4, 1>,
+    ) -> Option<(T, usize)> {
+        let o = [ori[[0, 0]], ori[[1, 0]], ori[[2, 0]]];
+        let d = [dir[[0, 0]], dir[[1, 0]], dir[[2, 0]]];
+        let (t_enter, t_exit) = self._bound.intersect_ray(&o, &d)?;
+
+        let p = *ori + *dir * t_enter;
+        let (mut i, mut j) = self.get_cell(p[[0, 0]], p[[2, 0]]);
+
+        //parameter of the next cell boundary crossing and the parameter span of a cell along x and z
+        let setup = |axis: usize, cell: usize, spacing: T| -> (T, T, isize) {
+            if d[axis] == T::zero() {
+                return (T::infinity(), T::infinity(), 0);
+            }
+            let delta = spacing / d[axis].abs();
+            let base = self._ori[[axis, 0]] + spacing * T::from(cell).unwrap();
+            let (next, step) = if d[axis] > T::zero() {
+                (base + spacing, 1)
+            } else {
+                (base, -1)
+            };
+            ((next - o[axis]) / d[axis], delta, step)
+        };
+        let (mut t_max_x, delta_x, step_x) = setup(0, i, self._spacing[0]);
+        let (mut t_max_z, delta_z, step_z) = setup(2, j, self._spacing[1]);
+
+        loop {
+            let mut best: Option<(T, usize)> = None;
+            for (k, tri) in self.get_cell_triangles(i, j).iter().enumerate() {
+                if let Some((t, _)) = tri.intersect_ray(ori, dir) {
+                    match best {
+                        Some((t_best, _)) if t_best <= t => {}
+                        _ => best = Some((t, self.get_feature(i, j, k))),
+                    }
+                }
+            }
+            if best.is_some() {
+                return best;
+            }
+            if t_max_x.min(t_max_z) > t_exit {
+                return None;
+            }
+            if t_max_x < t_max_z {
+                let next = i as isize + step_x;
+                if next < 0 || next as usize > self._cols - 2 {
+                    return None;
+                }
+                i = next as usize;
+                t_max_x += delta_x;
+            } else {
+                let next = j as isize + step_z;
+                if next < 0 || next as usize > self._rows - 2 {
+                    return None;
+                }
+                j = next as usize;
+                t_max_z += delta_z;
+            }
+        }
+    }
+}
+
+impl<T> AnyBase for HeightField<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for HeightField<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::HeightField
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    //the contact's feature is the index of the triangle hit, see get_feature
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._cols < 2
+            || self._rows < 2
+            || !(self._spacing[0] > T::zero() && self._spacing[1] > T::zero())
+        {
+            return Err(CollideError::DegenerateShape(ShapeType::HeightField));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let p = other_point._ori;
+                let (h, n, feature) = match self.get_height(&p) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                if p[[1, 0]] > h {
+                    return Ok(None);
+                }
+                //push the point out perpendicular to the surface
+                let depth = (h - p[[1, 0]]) * n[[1, 0]];
+                Ok(Some(Contact {
+                    _point_a: p + n * depth,
+                    _point_b: p,
+                    _normal: n,
+                    _depth: depth,
+                    _feature: Some(feature),
+                }))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some((_, feature)) => {
+                        let cell = feature / 2;
+                        let (i, j) = (cell % (self._cols - 1), cell / (self._cols - 1));
+                        let tri = &self.get_cell_triangles(i, j)[feature % 2];
+                        Ok(dispatch::try_intersect(tri, other)?.map(|c| c.with_feature(feature)))
+                    }
+                    _ => Ok(None),
+                }
+            }
+            _ => {
+                //deepest contact among triangles of the cells under the other shape's bound
+                let ((i0, i1), (j0, j1)) = match self.get_cells(other.get_bound()) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                //convex shapes are pushed out along the face normals, as the field is solid below its triangles
+                let up = Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t();
+                let convex = other.get_support(&up).is_some();
+                let mut best: Option<Contact<T>> = None;
+                for j in j0..=j1 {
+                    for i in i0..=i1 {
+                        for (k, tri) in self.get_cell_triangles(i, j).iter().enumerate() {
+                            let c = if convex {
+                                contact_column(tri, other)
+                            } else {
+                                dispatch::try_intersect(tri, other)?
+                            };
+                            if let Some(c) = c {
+                                match best {
+                                    Some(ref b) if b._depth >= c._depth => {}
+                                    _ => best = Some(c.with_feature(self.get_feature(i, j, k))),
+                                }
+                            }
+                        }
+                    }
+                }
+                Ok(best)
+            }
+        }
+    }
+    fn get_support(&self, _v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        //not convex
+        None
+    }
+}
+
+///contact of the solid below a triangle facing up, as shape a, with a convex shape.
+///the shape has to reach below the plane of the triangle and within its edges
+fn contact_column<T>(tri: &Triangle<T>, other: &dyn Shape<T>) -> Option<Contact<T>>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    let n = tri._normal;
+    for (v, e) in tri._v.iter().zip(tri.get_edges().iter()) {
+        //outward normal of the side through the edge
+        let m = e.cross(&n);
+        if other.get_support(&(m * -T::one()))?.inner(&m) > v.inner(&m) {
+            return None;
+        }
+    }
+    let deepest = other.get_support(&(n * -T::one()))?;
+    let depth = (tri._v[0] - deepest).inner(&n);
+    if depth < T::zero() {
+        return None;
+    }
+    Some(Contact {
+        _point_a: deepest + n * depth,
+        _point_b: deepest,
+        _normal: n,
+        _depth: depth,
+        _feature: None,
+    })
+}
+
+impl<T> Vicinity<T> for HeightField<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+///8 by 6 samples, flat at height 1 with a bump in the middle
+#[cfg(test)]
+fn bumpy_field() -> HeightField<f64> {
+    let mut heights = vec![1.; 48];
+    heights[3 * 8 + 4] = 2.;
+    HeightField::init(&[-4., 0., -2.], &heights, 8, &[1., 1.])
+}
+
+#[test]
+fn test_height_field_bound_cells() {
+    let a = bumpy_field();
+    assert_eq!(a._rows, 6);
+    assert_eq!(a._bound.bound_lower, [-4., 1., -2.]);
+    assert_eq!(a._bound.bound_upper, [3., 2., 3.]);
+    let b = Sphere::init(&[0.5, 1., 0.5], 0.2);
+    assert_eq!(a.get_cells(b.get_bound()), Some(((4, 4), (2, 2))));
+    let b = Sphere::init(&[-10., 1., 0.5], 1.);
+    assert_eq!(a.get_cells(b.get_bound()), None);
+    //past the far edge clamps to the last cell
+    let b = Sphere::init(&[3., 1., 3.], 0.5);
+    assert_eq!(a.get_cells(b.get_bound()), Some(((6, 6), (4, 4))));
+}
+#[test]
+fn test_intersect_height_field_point() {
+    let a = bumpy_field();
+    let (h, _, _) = a
+        .get_height(&Matrix::from([[-2.5, 0., 0.5, 1.]]).t())
+        .expect("height field height unexpected");
+    assert!((h - 1.).abs() < 1e-12);
+    //halfway up the slope towards the bump at (0, 2, 1)
+    let (h, _, _) = a
+        .get_height(&Matrix::from([[-0.5, 0., 1., 1.]]).t())
+        .expect("height field height unexpected");
+    assert!((h - 1.5).abs() < 1e-12);
+
+    let c = a
+        .get_intersect(&Point::init(&[-0.5, 1.3, 1.]))
+        .expect("height field point unexpected miss");
+    let s = 2f64.sqrt();
+    assert!((c._depth - 0.2 / s).abs() < 1e-12);
+    assert_matrix_approx_eq_float(
+        &c._normal,
+        &Matrix::from([[-1. / s, 1. / s, 0., 0.]]).t(),
+        1e-12,
+    );
+    assert!(a.get_intersect(&Point::init(&[-0.5, 1.6, 1.])).is_none());
+    assert!(a.get_intersect(&Point::init(&[-2.5, 1.1, 0.5])).is_none());
+}
+#[test]
+fn test_intersect_height_field_ray() {
+    let a = bumpy_field();
+    let ori = Matrix::from([[-3.5, 5., -1.5, 1.]]).t();
+    let dir = Matrix::from([[0., -1., 0., 0.]]).t();
+    let (t, _) = a
+        .intersect_ray(&ori, &dir)
+        .expect("height field ray unexpected miss");
+    assert!((t - 4.).abs() < 1e-12);
+
+    //grazing ray crossing many cells agrees with testing every triangle
+    let ori = Matrix::from([[-4., 1.5, -1.7, 1.]]).t();
+    let dir = Matrix::from([[1., -0.1, 0.45, 0.]]).t().normalize_l2();
+    let (t, feature) = a
+        .intersect_ray(&ori, &dir)
+        .expect("height field ray unexpected miss");
+    let mut brute = (f64::INFINITY, 0);
+    for j in 0..a._rows - 1 {
+        for i in 0..a._cols - 1 {
+            for (k, tri) in a.get_cell_triangles(i, j).iter().enumerate() {
+                if let Some((t, _)) = tri.intersect_ray(&ori, &dir) {
+                    if t < brute.0 {
+                        brute = (t, a.get_feature(i, j, k));
+                    }
+                }
+            }
+        }
+    }
+    assert!((t - brute.0).abs() < 1e-12);
+    assert_eq!(feature, brute.1);
+
+    let b = Ray::init(&[-3.5, 5., -1.5], &[0., -1., 0.]);
+    let c = b
+        .get_intersect(&a)
+        .expect("ray height field unexpected miss");
+    assert_matrix_approx_eq_float(
+        &c._point_a,
+        &Matrix::from([[-3.5, 1., -1.5, 1.]]).t(),
+        1e-12,
+    );
+    assert!(a
+        .get_intersect(&Ray::init(&[-3.5, 5., -1.5], &[1., 0., 0.]))
+        .is_none());
+    assert!(a
+        .get_intersect(&Ray::init(&[-3.5, 1.5, -1.5], &[-1., 0., 0.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_height_field_shapes() {
+    let a = bumpy_field();
+    let b = Sphere::init(&[-2.5, 1.3, 0.5], 0.5);
+    let c = a
+        .get_intersect(&b)
+        .expect("height field sphere unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+    assert!(a
+        .get_intersect(&Sphere::init(&[-2.5, 1.6, 0.5], 0.5))
+        .is_none());
+
+    let b = Capsule::init(&[-3., 1.3, 0.5], &[-1.5, 1.3, 0.5], 0.5);
+    let c = a
+        .get_intersect(&b)
+        .expect("height field capsule unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert!(a
+        .get_intersect(&Capsule::init(&[-3., 1.6, 0.5], &[-1.5, 1.6, 0.5], 0.5))
+        .is_none());
+
+    let b = RectBox::init(&[-2.5, 1.3, 0.5], 0.5);
+    let c = a
+        .get_intersect(&b)
+        .expect("height field box unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-9);
+    //sitting on the bump
+    let b = RectBox::init(&[0., 2.4, 1.], 0.5);
+    assert!(a.get_intersect(&b).is_some());
+    let b = RectBox::init(&[-2.5, 1.6, 0.5], 0.5);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_try_intersect_height_field_degenerate() {
+    let a = HeightField::init(&[0., 0., 0.], &[1., 1., 1.], 3, &[1., 1.]);
+    assert_eq!(
+        a.try_intersect(&Point::init(&[0., 1., 0.])).err(),
+        Some(CollideError::DegenerateShape(ShapeType::HeightField))
+    );
+}
+#[test]
+fn test_intersect_height_field_flat() {
+    //shapes resting across internal triangle edges are pushed straight up
+    let a = HeightField::init(&[-4., 0., -4.], &[0.; 81], 9, &[1., 1.]);
+    let up = Matrix::from([[0., 1., 0., 0.]]).t();
+    let b = Capsule::init(&[-1.3, 0.3, 0.2], &[1.1, 0.3, 0.7], 0.5);
+    let c = a
+        .get_intersect(&b)
+        .expect("height field capsule unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &up, 1e-12);
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert!((c._point_b[[1, 0]] + 0.2).abs() < 1e-12);
+
+    //also when the center is below the surface
+    let b = Capsule::init(&[-1.3, -0.1, 0.2], &[1.1, -0.1, 0.7], 0.5);
+    let c = b
+        .get_intersect(&a)
+        .expect("capsule height field unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &(up * -1.), 1e-12);
+    assert!((c._depth - 0.6).abs() < 1e-12);
+
+    let b = Cylinder::init(&[0.2, 0.4, 0.1], &[1., 0., 0.], 1.5, 0.7);
+    let c = a
+        .get_intersect(&b)
+        .expect("height field cylinder unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &up, 1e-12);
+    assert!((c._depth - 0.3).abs() < 1e-12);
+
+    let b = Cone::init(&[0.2, 0.8, 0.3], &[0., -1., 0.], 1., 0.6);
+    let c = a
+        .get_intersect(&b)
+        .expect("height field cone unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &up, 1e-12);
+    assert!((c._depth - 0.2).abs() < 1e-12);
+
+    //beside the field
+    let b = Capsule::init(&[-5.6, -0.1, 0.2], &[-4.6, -0.1, 0.7], 0.5);
+    assert!(a.get_intersect(&b).is_none());
+}
@@ -0,0 +1,328 @@
+//! expanding polytope algorithm for penetration depth of intersecting convex shapes,
+//! seeded with the terminating simplex of gjk
+
+use crate::intersect_gjk::{support, SupportPoint};
+use crate::shape::*;
+use lightmatrix::matrix::*;
+use num_traits::{Float, NumAssign};
+
+///upper bound on polytope expansions before the best estimate so far is returned
+const MAX_ITERATIONS: usize = 128;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Penetration<T: NumAssign + Copy + Default + Float> {
+    ///distance shape b has to move along the normal to separate from shape a
+    pub _depth: T,
+    ///unit contact normal pointing from shape a towards shape b
+    pub _normal: Matrix<T, 4, 1>,
+    ///deepest point of shape a inside shape b
+    pub _point_a: Matrix<T, 4, 1>,
+    ///deepest point of shape b inside shape a
+    pub _point_b: Matrix<T, 4, 1>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Face<T: NumAssign + Copy + Default + Float> {
+    _idx: [usize; 3],
+    _normal: Matrix<T, 4, 1>,
+    _dist: T,
+}
+
+///creates a face with its normal oriented away from an interior point of the polytope
+fn make_face<T>(verts: &[SupportPoint<T>], idx: [usize; 3], interior: &Matrix<T, 4, 1>) -> Face<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let v0 = verts[idx[0]]._v;
+    let n = (verts[idx[1]]._v - v0).cross(&(verts[idx[2]]._v - v0));
+    let l = n.norm_l2();
+    if l == T::zero() {
+        //degenerate face is never chosen as the closest one
+        return Face {
+            _idx: idx,
+            _normal: n,
+            _dist: T::infinity(),
+        };
+    }
+    let mut n = n / l;
+    if n.inner(&(v0 - *interior)) < T::zero() {
+        n *= -T::one();
+    }
+    Face {
+        _idx: idx,
+        _normal: n,
+        _dist: n.inner(&v0),
+    }
+}
+
+///grows a lower dimensional simplex containing the minkowski origin into a tetrahedron
+fn complete_simplex<T>(
+    a: &dyn Shape<T>,
+    b: &dyn Shape<T>,
+    simplex: &mut Vec<SupportPoint<T>>,
+    tol: T,
+) -> Option<()>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    let axes = [
+        Matrix::from([[T::one(), T::zero(), T::zero(), T::zero()]]).t(),
+        Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t(),
+        Matrix::from([[T::zero(), T::zero(), T::one(), T::zero()]]).t(),
+    ];
+    while simplex.len() < 4 {
+        let s0 = simplex[0]._v;
+        let mut candidates = vec![];
+        match simplex.len() {
+            1 => {
+                candidates.extend(axes.iter().cloned());
+            }
+            2 => {
+                let ab = simplex[1]._v - s0;
+                for axis in axes.iter() {
+                    candidates.push(ab.cross(axis));
+                }
+            }
+            _ => {
+                candidates.push((simplex[1]._v - s0).cross(&(simplex[2]._v - s0)));
+            }
+        }
+        let mut found = None;
+        'search: for dir in candidates.iter() {
+            if dir.norm_l2() == T::zero() {
+                continue;
+            }
+            for sign in [T::one(), -T::one()].iter() {
+                let sup = support(a, b, &(*dir * *sign))?;
+                let off = sup._v - s0;
+                let extent = match simplex.len() {
+                    1 => off.norm_l2(),
+                    2 => off.cross(&(simplex[1]._v - s0).normalize_l2()).norm_l2(),
+                    _ => off.inner(&dir.normalize_l2()).abs(),
+                };
+                if extent > tol {
+                    found = Some(sup);
+                    break 'search;
+                }
+            }
+        }
+        //minkowski difference is flat, no volume to expand
+        simplex.push(found?);
+    }
+    Some(())
+}
+
+///barycentric coordinates of p projected onto triangle v0, v1, v2
+pub(crate) fn barycentric<T>(
+    p: &Matrix<T, 4, 1>,
+    v0: &Matrix<T, 4, 1>,
+    v1: &Matrix<T, 4, 1>,
+    v2: &Matrix<T, 4, 1>,
+) -> [T; 3]
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let e0 = *v1 - *v0;
+    let e1 = *v2 - *v0;
+    let e2 = *p - *v0;
+    let d00 = e0.inner(&e0);
+    let d01 = e0.inner(&e1);
+    let d11 = e1.inner(&e1);
+    let d20 = e2.inner(&e0);
+    let d21 = e2.inner(&e1);
+    let denom = d00 * d11 - d01 * d01;
+    if denom == T::zero() {
+        return [T::one(), T::zero(), T::zero()];
+    }
+    let v = (d11 * d20 - d01 * d21) / denom;
+    let w = (d00 * d21 - d01 * d20) / denom;
+    [T::one() - v - w, v, w]
+}
+
+///computes penetration depth, contact normal and witness points of 2 intersecting shapes
+///
+///simplex is the terminating simplex of a positive gjk query, see intersect_gjk::query_simplex.
+///returns None if either shape lacks a support function or the minkowski difference has no volume
+pub fn query_penetration<T>(
+    a: &dyn Shape<T>,
+    b: &dyn Shape<T>,
+    simplex: &[SupportPoint<T>],
+) -> Option<Penetration<T>>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    if simplex.is_empty() {
+        return None;
+    }
+    let tol = T::from(1e-7).unwrap();
+
+    let mut verts = simplex.to_vec();
+    complete_simplex(a, b, &mut verts, tol)?;
+
+    let interior = (verts[0]._v + verts[1]._v + verts[2]._v + verts[3]._v) / T::from(4.).unwrap();
+
+    let mut faces = vec![
+        make_face(&verts, [0, 1, 2], &interior),
+        make_face(&verts, [0, 3, 1], &interior),
+        make_face(&verts, [0, 2, 3], &interior),
+        make_face(&verts, [1, 3, 2], &interior),
+    ];
+
+    let closest = |faces: &Vec<Face<T>>| -> Face<T> {
+        *faces
+            .iter()
+            .min_by(|x, y| x._dist.partial_cmp(&y._dist).unwrap())
+            .unwrap()
+    };
+
+    let mut face = closest(&faces);
+    for _ in 0..MAX_ITERATIONS {
+        if face._dist == T::infinity() {
+            return None;
+        }
+        let sup = support(a, b, &face._normal)?;
+        if sup._v.inner(&face._normal) - face._dist < tol {
+            break;
+        }
+
+        //remove faces visible from the new vertex and collect the horizon edges
+        let mut edges: Vec<(usize, usize)> = vec![];
+        let mut kept = vec![];
+        for f in faces.iter() {
+            if f._normal.inner(&(sup._v - verts[f._idx[0]]._v)) > T::zero() {
+                for k in 0..3 {
+                    let e = (f._idx[k], f._idx[(k + 1) % 3]);
+                    match edges
+                        .iter()
+                        .position(|x| (x.0 == e.0 && x.1 == e.1) || (x.0 == e.1 && x.1 == e.0))
+                    {
+                        Some(pos) => {
+                            edges.swap_remove(pos);
+                        }
+                        _ => edges.push(e),
+                    }
+                }
+            } else {
+                kept.push(*f);
+            }
+        }
+        if edges.is_empty() {
+            break;
+        }
+
+        verts.push(sup);
+        let idx_new = verts.len() - 1;
+        faces = kept;
+        for e in edges.iter() {
+            faces.push(make_face(&verts, [e.0, e.1, idx_new], &interior));
+        }
+        face = closest(&faces);
+    }
+
+    let p = face._normal * face._dist;
+    let [v0, v1, v2] = face._idx;
+    let l = barycentric(&p, &verts[v0]._v, &verts[v1]._v, &verts[v2]._v);
+
+    Some(Penetration {
+        _depth: face._dist,
+        _normal: face._normal,
+        _point_a: verts[v0]._a * l[0] + verts[v1]._a * l[1] + verts[v2]._a * l[2],
+        _point_b: verts[v0]._b * l[0] + verts[v1]._b * l[1] + verts[v2]._b * l[2],
+    })
+}
+
+#[cfg(test)]
+fn penetration<T>(a: &dyn Shape<T>, b: &dyn Shape<T>) -> Option<Penetration<T>>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    use crate::intersect_gjk::query_simplex;
+    match query_simplex(a, b) {
+        Ok((true, simplex)) => query_penetration(a, b, &simplex),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_epa_box_box() {
+    use crate::rect_box::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = RectBox::init(&[1.5f64, 0.2f64, 0.1f64], 1f64);
+    let ret = penetration(&a, &b).expect("epa return unexpected");
+    assert!((ret._depth - 0.5f64).abs() < 1e-6);
+    assert_matrix_approx_eq_float(
+        &ret._normal,
+        &Matrix::from([[1f64, 0f64, 0f64, 0f64]]).t(),
+        1e-6,
+    );
+    assert!((ret._point_a[[0, 0]] - 1f64).abs() < 1e-6);
+    assert!((ret._point_b[[0, 0]] - 0.5f64).abs() < 1e-6);
+}
+#[test]
+fn test_epa_box_point() {
+    use crate::point::*;
+    use crate::rect_box::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = Point::init(&[0.2f64, -0.9f64, 0.3f64]);
+    let ret = penetration(&a, &b).expect("epa return unexpected");
+    assert!((ret._depth - 0.1f64).abs() < 1e-6);
+    assert_matrix_approx_eq_float(
+        &ret._normal,
+        &Matrix::from([[0f64, -1f64, 0f64, 0f64]]).t(),
+        1e-6,
+    );
+    assert_matrix_approx_eq_float(&ret._point_b, &b._ori, 1e-6);
+}
+#[test]
+fn test_epa_sphere_sphere() {
+    use crate::sphere::*;
+    let a = Sphere::init(&[0f64, 0f64, 0f64], 5f64);
+    let b = Sphere::init(&[7f64, 0f64, 0f64], 2.1f64);
+    let ret = penetration(&a, &b).expect("epa return unexpected");
+    assert!((ret._depth - 0.1f64).abs() < 1e-3);
+    assert_matrix_approx_eq_float(
+        &ret._normal,
+        &Matrix::from([[1f64, 0f64, 0f64, 0f64]]).t(),
+        1e-2,
+    );
+    assert_matrix_approx_eq_float(
+        &ret._point_a,
+        &Matrix::from([[5f64, 0f64, 0f64, 1f64]]).t(),
+        1e-1,
+    );
+}
+#[test]
+fn test_epa_box_sphere() {
+    use crate::rect_box::*;
+    use crate::sphere::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = Sphere::init(&[1.5f64, 0f64, 0.2f64], 0.8f64);
+    let ret = penetration(&a, &b).expect("epa return unexpected");
+    assert!((ret._depth - 0.3f64).abs() < 1e-3);
+    assert_matrix_approx_eq_float(
+        &ret._normal,
+        &Matrix::from([[1f64, 0f64, 0f64, 0f64]]).t(),
+        1e-2,
+    );
+}
+#[test]
+fn test_epa_triprism_box() {
+    use crate::rect_box::*;
+    use crate::tri_prism::*;
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = RectBox::init(&[0.75f64, 0.25f64, 1.4f64], 0.5f64);
+    let ret = penetration(&a, &b).expect("epa return unexpected");
+    assert!((ret._depth - 0.1f64).abs() < 1e-6);
+    assert_matrix_approx_eq_float(
+        &ret._normal,
+        &Matrix::from([[0f64, 0f64, 1f64, 0f64]]).t(),
+        1e-6,
+    );
+}
+#[test]
+fn test_epa_separated() {
+    use crate::sphere::*;
+    let a = Sphere::init(&[0f64, 0f64, 0f64], 5f64);
+    let b = Sphere::init(&[7f64, 0f64, 0f64], 1.9f64);
+    assert!(penetration(&a, &b).is_none());
+}
@@ -1,14 +1,31 @@
 //! based on reference tutorial from http://www.dyn4j.org/2010/04/gjk-gilbert-johnson-keerthi/
+//! extended to 3D by growing the simplex up to a tetrahedron
 
+use crate::error::CollideError;
 use crate::shape::*;
 use lightmatrix::matrix::*;
 use num_traits::{Float, NumAssign};
 
-fn support<T>(a: &dyn Shape<T>, b: &dyn Shape<T>, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>>
+///upper bound on simplex refinements before the query gives up
+const MAX_ITERATIONS: usize = 64;
+
+///vertex of the minkowski difference along with the support points of both shapes generating it
+#[derive(Debug, Clone, Copy)]
+pub struct SupportPoint<T: NumAssign + Copy + Default + Float> {
+    pub _v: Matrix<T, 4, 1>,
+    pub _a: Matrix<T, 4, 1>,
+    pub _b: Matrix<T, 4, 1>,
+}
+
+pub(crate) fn support<T>(
+    a: &dyn Shape<T>,
+    b: &dyn Shape<T>,
+    v: &Matrix<T, 4, 1>,
+) -> Option<SupportPoint<T>>
 where
     T: NumAssign + Copy + Default + Float + 'static,
 {
-    let p0 = match a.get_support(&v) {
+    let p0 = match a.get_support(v) {
         Some(o) => o,
         _ => return None,
     };
@@ -18,105 +35,413 @@ where
         _ => return None,
     };
     let p10 = p0 - p1;
-    Some(p10)
+    Some(SupportPoint {
+        _v: p10,
+        _a: p0,
+        _b: p1,
+    })
 }
 
 fn pass_minkowski_origin<T>(last_vert: &Matrix<T, 4, 1>, support: &Matrix<T, 4, 1>) -> bool
 where
     T: NumAssign + Copy + Default + Float,
 {
-    // println!( "last vert dot product: {}", last_vert.dot( &support ).unwrap() );
-    last_vert.inner(&support) > T::zero()
+    last_vert.inner(support) > T::zero()
 }
 
-fn contains_minkowski_origin<T>(
-    simplex: &mut Vec<Matrix<T, 4, 1>>,
+///direction perpendicular to ab pointing towards ao
+fn triple_cross<T>(ab: &Matrix<T, 4, 1>, ao: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ab.cross(ao).cross(ab)
+}
+
+///line segment case: a is the newest vertex, b the older one
+fn update_line<T>(
+    a: SupportPoint<T>,
+    b: SupportPoint<T>,
+    simplex: &mut Vec<SupportPoint<T>>,
     support: &mut Matrix<T, 4, 1>,
 ) -> bool
 where
     T: NumAssign + Copy + Default + Float,
 {
-    let a = simplex.last().unwrap().clone();
-    let ao = a * -T::one();
-    if simplex.len() == 3 {
-        //triangle case
-        let b = simplex[1];
-        let c = simplex[0];
-        let ab = b - a;
-        let ac = c - a;
-        let ab_normal = ac.cross(&ab).cross(&ab);
-        let ac_normal = ab.cross(&ac).cross(&ac);
-        if ab_normal.inner(&ao) >= T::zero() {
-            //remove c and set new direction to ab_normal
-            let simplex_new = vec![simplex[1], simplex[2]];
-            *simplex = simplex_new;
-            *support = ab_normal.clone();
-        } else if ac_normal.inner(&ao) >= T::zero() {
-            //remove b and set new direction to ac_normal
-            let simplex_new = vec![simplex[0], simplex[2]];
-            *simplex = simplex_new.clone();
-            *support = ac_normal.clone();
-        } else {
-            //minkowski origin is enclosed by the triangle
+    let ao = a._v * -T::one();
+    let ab = b._v - a._v;
+    if ab.inner(&ao) > T::zero() {
+        let ab_normal = triple_cross(&ab, &ao);
+        if ab_normal.norm_l2() == T::zero() {
+            //minkowski origin lies on the segment
             return true;
         }
+        *simplex = vec![b, a];
+        *support = ab_normal;
     } else {
-        //line segment case
-        //set direction towards minkowski origin
-        let b = simplex[0];
-        let ab = b - a;
-        let ab_normal = ab.cross(&ao).cross(&ab);
-        if ab_normal.norm_l2() == T::zero() {
-            return true;
-        } else {
-            *support = ab_normal.clone();
+        *simplex = vec![a];
+        *support = ao;
+    }
+    false
+}
+
+///triangle case: a is the newest vertex, b and c the older ones
+fn update_triangle<T>(
+    a: SupportPoint<T>,
+    b: SupportPoint<T>,
+    c: SupportPoint<T>,
+    simplex: &mut Vec<SupportPoint<T>>,
+    support: &mut Matrix<T, 4, 1>,
+) -> bool
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let ao = a._v * -T::one();
+    let ab = b._v - a._v;
+    let ac = c._v - a._v;
+    let abc = ab.cross(&ac);
+    if abc.norm_l2() == T::zero() {
+        //degenerate triangle, fall back to a line segment
+        return update_line(a, b, simplex, support);
+    }
+
+    if abc.cross(&ac).inner(&ao) > T::zero() {
+        if ac.inner(&ao) > T::zero() {
+            //origin is in the region outside of edge ac
+            *simplex = vec![c, a];
+            *support = triple_cross(&ac, &ao);
+            return false;
         }
+        return update_line(a, b, simplex, support);
+    }
+    if ab.cross(&abc).inner(&ao) > T::zero() {
+        //origin is in the region outside of edge ab
+        return update_line(a, b, simplex, support);
     }
-    return false;
+
+    //origin projects into the triangle, continue search above or below it
+    let side = abc.inner(&ao);
+    if side > T::zero() {
+        *simplex = vec![c, b, a];
+        *support = abc;
+    } else if side < T::zero() {
+        *simplex = vec![b, c, a];
+        *support = abc * -T::one();
+    } else {
+        //minkowski origin lies on the triangle
+        return true;
+    }
+    false
 }
 
-pub fn query_intersect<T>(a: &dyn Shape<T>, b: &dyn Shape<T>) -> Option<bool>
+///tetrahedron case: a is the newest vertex, b, c and d the older ones
+fn update_tetrahedron<T>(
+    a: SupportPoint<T>,
+    b: SupportPoint<T>,
+    c: SupportPoint<T>,
+    d: SupportPoint<T>,
+    simplex: &mut Vec<SupportPoint<T>>,
+    support: &mut Matrix<T, 4, 1>,
+) -> bool
 where
-    T: NumAssign + Copy + Default + Float + 'static,
+    T: NumAssign + Copy + Default + Float,
 {
-    match (a.get_type(), b.get_type()) {
-        (ShapeType::Sphere, ShapeType::Sphere) => {}
-        //todo
-        // (ShapeType::POINT,ShapeType::BOX) => {},
-        // (ShapeType::BOX,ShapeType::POINT) => {},
-        _ => {
-            panic!("unsupported shape type");
+    let ao = a._v * -T::one();
+
+    //test the 3 facets adjacent to a, each normal is oriented away from the remaining vertex
+    let facets = [(b, c, d), (c, d, b), (d, b, c)];
+    for (p, q, opposite) in facets.iter() {
+        let mut n = (p._v - a._v).cross(&(q._v - a._v));
+        if n.inner(&(opposite._v - a._v)) > T::zero() {
+            n *= -T::one();
         }
+        if n.inner(&ao) > T::zero() {
+            //origin is outside of this facet, drop the opposite vertex
+            return update_triangle(a, *p, *q, simplex, support);
+        }
+    }
+
+    //minkowski origin is enclosed by the tetrahedron
+    true
+}
+
+fn contains_minkowski_origin<T>(
+    simplex: &mut Vec<SupportPoint<T>>,
+    support: &mut Matrix<T, 4, 1>,
+) -> bool
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    //newest vertex is at the back of the simplex
+    match simplex.len() {
+        2 => update_line(simplex[1], simplex[0], simplex, support),
+        3 => update_triangle(simplex[2], simplex[1], simplex[0], simplex, support),
+        4 => update_tetrahedron(
+            simplex[3], simplex[2], simplex[1], simplex[0], simplex, support,
+        ),
+        _ => panic!("simplex vertices count unexpected"),
     }
+}
+
+///runs gjk to completion, returning the intersection status and the final simplex
+///
+///the simplex is a tetrahedron enclosing the minkowski origin on positive results,
+///except for degenerate cases where the origin lies on a lower dimensional simplex.
+pub fn query_simplex<T>(
+    a: &dyn Shape<T>,
+    b: &dyn Shape<T>,
+) -> Result<(bool, Vec<SupportPoint<T>>), CollideError>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    let unsupported = || CollideError::UnsupportedPair(a.get_type(), b.get_type());
+
     //set initial minkowski vertex from an arbitrary support vector
-    let mut d = Matrix::from([[-T::one(), T::zero(), T::zero(), T::zero()]]).t();
+    let d_init = Matrix::from([[-T::one(), T::zero(), T::zero(), T::zero()]]).t();
+
+    let mut simplex = vec![support(a, b, &d_init).ok_or_else(unsupported)?];
+
+    //search towards the minkowski origin
+    let mut d = simplex[0]._v * -T::one();
+    if d.norm_l2() == T::zero() {
+        return Ok((true, simplex));
+    }
 
-    let mut simplex = vec![];
-    {
-        let sup = support(a, b, &d).unwrap();
+    for _ in 0..MAX_ITERATIONS {
+        let sup = support(a, b, &d).ok_or_else(unsupported)?;
+        if !pass_minkowski_origin(&sup._v, &d) {
+            return Ok((false, simplex));
+        }
         simplex.push(sup);
+        if contains_minkowski_origin(&mut simplex, &mut d) {
+            return Ok((true, simplex));
+        }
+        if d.norm_l2() == T::zero() {
+            return Ok((true, simplex));
+        }
     }
+    Err(CollideError::NonConvergence)
+}
 
-    d = d * -T::one();
-    loop {
-        // println!( "support vector: {:?}", d );
-        {
-            let sup = support(a, b, &d).unwrap();
-            simplex.push(sup);
-        }
-        assert!(simplex.len() <= 3, "simplex vertices count unexpected");
-        // println!( "simplex len: {}", simplex.len() );
-        if !pass_minkowski_origin(simplex.last().unwrap(), &d) {
-            // println!( "new vert not pass origin" );
-            return Some(false);
-        } else {
-            if contains_minkowski_origin(&mut simplex, &mut d) {
-                return Some(true);
+///tests intersection of any 2 convex shapes providing support functions
+///
+///fails if either shape does not provide a support function or the search does not converge
+pub fn try_query_intersect<T>(a: &dyn Shape<T>, b: &dyn Shape<T>) -> Result<bool, CollideError>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    query_simplex(a, b).map(|(hit, _)| hit)
+}
+
+///tests intersection of any 2 convex shapes providing support functions
+///
+///returns None if either shape does not provide a support function or the search does not converge
+pub fn query_intersect<T>(a: &dyn Shape<T>, b: &dyn Shape<T>) -> Option<bool>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    try_query_intersect(a, b).ok()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Separation<T: NumAssign + Copy + Default + Float> {
+    ///shortest distance between the shapes, zero if they intersect
+    pub _distance: T,
+    ///unit direction pointing from shape a towards shape b, zero if the shapes intersect
+    pub _normal: Matrix<T, 4, 1>,
+    ///closest point on shape a, any support point of shape a if the shapes intersect
+    pub _point_a: Matrix<T, 4, 1>,
+    ///closest point on shape b, any support point of shape b if the shapes intersect
+    pub _point_b: Matrix<T, 4, 1>,
+}
+
+///closest point to the minkowski origin on triangle a, b, c as barycentric weights
+pub(crate) fn closest_triangle<T>(
+    a: &Matrix<T, 4, 1>,
+    b: &Matrix<T, 4, 1>,
+    c: &Matrix<T, 4, 1>,
+) -> [T; 3]
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    //voronoi region tests, see Ericson, Real-Time Collision Detection 5.1.5
+    let ab = *b - *a;
+    let ac = *c - *a;
+    let ap = *a * -T::one();
+    let d1 = ab.inner(&ap);
+    let d2 = ac.inner(&ap);
+    if d1 <= T::zero() && d2 <= T::zero() {
+        return [T::one(), T::zero(), T::zero()];
+    }
+    let bp = *b * -T::one();
+    let d3 = ab.inner(&bp);
+    let d4 = ac.inner(&bp);
+    if d3 >= T::zero() && d4 <= d3 {
+        return [T::zero(), T::one(), T::zero()];
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= T::zero() && d1 >= T::zero() && d3 <= T::zero() {
+        let v = d1 / (d1 - d3);
+        return [T::one() - v, v, T::zero()];
+    }
+    let cp = *c * -T::one();
+    let d5 = ab.inner(&cp);
+    let d6 = ac.inner(&cp);
+    if d6 >= T::zero() && d5 <= d6 {
+        return [T::zero(), T::zero(), T::one()];
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= T::zero() && d2 >= T::zero() && d6 <= T::zero() {
+        let w = d2 / (d2 - d6);
+        return [T::one() - w, T::zero(), w];
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= T::zero() && d4 - d3 >= T::zero() && d5 - d6 >= T::zero() {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return [T::zero(), T::one() - w, w];
+    }
+    let denom = T::one() / (va + vb + vc);
+    let v = vb * denom;
+    let w = vc * denom;
+    [T::one() - v - w, v, w]
+}
+
+///closest point to the minkowski origin on the simplex as barycentric weights per vertex
+fn closest_simplex<T>(simplex: &[SupportPoint<T>]) -> Vec<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    match simplex.len() {
+        1 => vec![T::one()],
+        2 => {
+            let a = simplex[0]._v;
+            let ab = simplex[1]._v - a;
+            let l = ab.inner(&ab);
+            if l == T::zero() {
+                return vec![T::one(), T::zero()];
             }
+            let t = (-a.inner(&ab) / l).max(T::zero()).min(T::one());
+            vec![T::one() - t, t]
         }
+        3 => closest_triangle(&simplex[0]._v, &simplex[1]._v, &simplex[2]._v).to_vec(),
+        4 => {
+            //only facets separating the minkowski origin from the remaining vertex are candidates
+            let facets = [[0, 1, 2, 3], [0, 1, 3, 2], [0, 2, 3, 1], [1, 2, 3, 0]];
+            let mut best = None;
+            let mut best_dist = T::infinity();
+            for f in facets.iter() {
+                let a = simplex[f[0]]._v;
+                let n = (simplex[f[1]]._v - a).cross(&(simplex[f[2]]._v - a));
+                let side_origin = n.inner(&(a * -T::one()));
+                let side_opposite = n.inner(&(simplex[f[3]]._v - a));
+                if side_origin * side_opposite >= T::zero() {
+                    continue;
+                }
+                let w = closest_triangle(&a, &simplex[f[1]]._v, &simplex[f[2]]._v);
+                let p = a * w[0] + simplex[f[1]]._v * w[1] + simplex[f[2]]._v * w[2];
+                let dist = p.inner(&p);
+                if dist < best_dist {
+                    best_dist = dist;
+                    let mut weights = vec![T::zero(); 4];
+                    for k in 0..3 {
+                        weights[f[k]] = w[k];
+                    }
+                    best = Some(weights);
+                }
+            }
+            //minkowski origin is enclosed by the tetrahedron
+            best.unwrap_or_else(|| vec![T::zero(); 4])
+        }
+        _ => panic!("simplex vertices count unexpected"),
     }
 }
 
+///computes the shortest distance, closest points and separating direction of 2 convex shapes
+///
+///tolerance is the absolute distance accuracy at which the search terminates.
+///returns None if either shape does not provide a support function
+pub fn query_distance<T>(a: &dyn Shape<T>, b: &dyn Shape<T>, tolerance: T) -> Option<Separation<T>>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    let d_init = Matrix::from([[-T::one(), T::zero(), T::zero(), T::zero()]]).t();
+    let mut simplex = vec![support(a, b, &d_init)?];
+    let mut weights = vec![T::one()];
+
+    for _ in 0..MAX_ITERATIONS {
+        weights = closest_simplex(&simplex);
+        if weights.iter().all(|w| *w == T::zero()) {
+            //tetrahedron encloses the minkowski origin
+            break;
+        }
+
+        //drop vertices not contributing to the closest point
+        let mut i = 0;
+        while i < simplex.len() {
+            if weights[i] <= T::zero() {
+                simplex.remove(i);
+                weights.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        let v = simplex
+            .iter()
+            .zip(weights.iter())
+            .fold(Matrix::zero(), |acc, (s, w)| acc + s._v * *w);
+        let dist = v.norm_l2();
+        if dist <= tolerance {
+            break;
+        }
+
+        //lower bound of the distance from the support in the opposing direction
+        let sup = support(a, b, &(v * -T::one()))?;
+        let lower = v.inner(&sup._v) / dist;
+        if dist - lower <= tolerance {
+            break;
+        }
+        if simplex
+            .iter()
+            .any(|s| (s._v - sup._v).norm_l2() == T::zero())
+        {
+            break;
+        }
+        simplex.push(sup);
+    }
+
+    if simplex.len() != weights.len() {
+        weights = closest_simplex(&simplex);
+    }
+
+    let enclosed = weights.iter().all(|w| *w == T::zero());
+    let (mut point_a, mut point_b) = if enclosed {
+        (simplex[0]._a, simplex[0]._b)
+    } else {
+        (Matrix::zero(), Matrix::zero())
+    };
+    for (s, w) in simplex.iter().zip(weights.iter()) {
+        point_a += s._a * *w;
+        point_b += s._b * *w;
+    }
+
+    let v = point_b - point_a;
+    let dist = v.norm_l2();
+    if enclosed || dist <= tolerance {
+        return Some(Separation {
+            _distance: T::zero(),
+            _normal: Matrix::zero(),
+            _point_a: point_a,
+            _point_b: point_b,
+        });
+    }
+    Some(Separation {
+        _distance: dist,
+        _normal: v / dist,
+        _point_a: point_a,
+        _point_b: point_b,
+    })
+}
+
 #[test]
 fn test_intersect_gjk_shape_support() {
     use crate::sphere::*;
@@ -187,13 +512,41 @@ fn test_intersect_gjk_query_intersect_positive_3() {
     let ret = query_intersect(&a, &b);
     assert!(ret.expect("gjk return unexpected"));
 }
-//todo
-// {
-//     let a = Point3::init( &[ -9.9, 9.9, 9.9 ] );
-//     let b = RecBox::init( &[ 0.,0.,0. ], 10. );
-//     let ret = intersect_gjk::query_intersect( &a, &b );
-//     assert!( ret.expect("gjk return unexpected") );
-// }
+#[test]
+fn test_intersect_gjk_query_intersect_positive_4() {
+    use crate::point::*;
+    use crate::rect_box::*;
+    let a = Point::init(&[-9.9f64, 9.9f64, 9.9f64]);
+    let b = RectBox::init(&[0f64, 0f64, 0f64], 10f64);
+    let ret = query_intersect(&a, &b);
+    assert!(ret.expect("gjk return unexpected"));
+}
+#[test]
+fn test_intersect_gjk_query_intersect_positive_5() {
+    use crate::rect_box::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = RectBox::init(&[1.5f64, -1.2f64, 0.9f64], 1f64);
+    let ret = query_intersect(&a, &b);
+    assert!(ret.expect("gjk return unexpected"));
+}
+#[test]
+fn test_intersect_gjk_query_intersect_positive_6() {
+    use crate::sphere::*;
+    use crate::tri_prism::*;
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = Sphere::init(&[0.75f64, 0.25f64, 1.3f64], 0.5f64);
+    let ret = query_intersect(&a, &b);
+    assert!(ret.expect("gjk return unexpected"));
+}
+#[test]
+fn test_intersect_gjk_query_intersect_positive_7() {
+    use crate::rect_box::*;
+    use crate::sphere::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = Sphere::init(&[1.5f64, 1.5f64, 1.5f64], 0.9f64);
+    let ret = query_intersect(&a, &b);
+    assert!(ret.expect("gjk return unexpected"));
+}
 
 #[test]
 fn test_intersect_gjk_query_intersect_negative_0() {
@@ -219,3 +572,163 @@ fn test_intersect_gjk_query_intersect_negative_2() {
     let ret = query_intersect(&a, &b);
     assert!(!ret.expect("gjk return unexpected"));
 }
+#[test]
+fn test_intersect_gjk_query_intersect_negative_3() {
+    use crate::point::*;
+    use crate::rect_box::*;
+    let a = Point::init(&[-9.9f64, 9.9f64, -10.1f64]);
+    let b = RectBox::init(&[0f64, 0f64, 0f64], 10f64);
+    let ret = query_intersect(&a, &b);
+    assert!(!ret.expect("gjk return unexpected"));
+}
+#[test]
+fn test_intersect_gjk_query_intersect_negative_4() {
+    //minkowski origin lies in the plane of an intermediate triangle but outside of the volume
+    use crate::rect_box::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = RectBox::init(&[0.5f64, 0.5f64, 3f64], 1f64);
+    let ret = query_intersect(&a, &b);
+    assert!(!ret.expect("gjk return unexpected"));
+}
+#[test]
+fn test_intersect_gjk_query_intersect_negative_5() {
+    use crate::rect_box::*;
+    use crate::sphere::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = Sphere::init(&[1.5f64, 1.5f64, 1.5f64], 0.8f64);
+    let ret = query_intersect(&a, &b);
+    assert!(!ret.expect("gjk return unexpected"));
+}
+#[test]
+fn test_intersect_gjk_query_intersect_negative_6() {
+    use crate::sphere::*;
+    use crate::tri_prism::*;
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = Sphere::init(&[0.25f64, 0.75f64, 0.5f64], 0.3f64);
+    let ret = query_intersect(&a, &b);
+    assert!(!ret.expect("gjk return unexpected"));
+}
+#[test]
+fn test_intersect_gjk_query_intersect_unsupported() {
+    use crate::ray::*;
+    use crate::sphere::*;
+    let a = Ray::init(&[0f64, 0f64, 0f64], &[1f64, 0f64, 0f64]);
+    let b = Sphere::init(&[7f64, 0f64, 0f64], 2f64);
+    assert!(query_intersect(&a, &b).is_none());
+}
+#[test]
+fn test_intersect_gjk_query_intersect_box_box_grid() {
+    //compare against the exact test of axis aligned boxes
+    use crate::rect_box::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    for i in -6..7 {
+        for j in -6..7 {
+            for k in -6..7 {
+                let c = [0.37f64 * i as f64, 0.41f64 * j as f64, 0.43f64 * k as f64];
+                let b = RectBox::init(&c, 0.5f64);
+                let expect = c.iter().all(|x| x.abs() < 1.5f64);
+                let ret = query_intersect(&a, &b);
+                assert_eq!(ret.expect("gjk return unexpected"), expect, "{:?}", c);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_intersect_gjk_query_distance_sphere_sphere() {
+    use crate::sphere::*;
+    let a = Sphere::init(&[0f64, 0f64, 0f64], 3f64);
+    let b = Sphere::init(&[10f64, 0f64, 0f64], 2f64);
+    let ret = query_distance(&a, &b, 1e-9).expect("gjk return unexpected");
+    assert!((ret._distance - 5f64).abs() < 1e-6);
+    assert_matrix_approx_eq_float(
+        &ret._normal,
+        &Matrix::from([[1f64, 0f64, 0f64, 0f64]]).t(),
+        1e-6,
+    );
+    assert_matrix_approx_eq_float(
+        &ret._point_a,
+        &Matrix::from([[3f64, 0f64, 0f64, 1f64]]).t(),
+        1e-6,
+    );
+    assert_matrix_approx_eq_float(
+        &ret._point_b,
+        &Matrix::from([[8f64, 0f64, 0f64, 1f64]]).t(),
+        1e-6,
+    );
+}
+#[test]
+fn test_intersect_gjk_query_distance_box_box() {
+    use crate::rect_box::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = RectBox::init(&[4f64, 0.5f64, -0.3f64], 1f64);
+    let ret = query_distance(&a, &b, 1e-9).expect("gjk return unexpected");
+    assert!((ret._distance - 2f64).abs() < 1e-9);
+    assert_matrix_approx_eq_float(
+        &ret._normal,
+        &Matrix::from([[1f64, 0f64, 0f64, 0f64]]).t(),
+        1e-9,
+    );
+    assert!((ret._point_a[[0, 0]] - 1f64).abs() < 1e-9);
+    assert!((ret._point_b[[0, 0]] - 3f64).abs() < 1e-9);
+}
+#[test]
+fn test_intersect_gjk_query_distance_box_point() {
+    use crate::point::*;
+    use crate::rect_box::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = Point::init(&[3f64, 3f64, 0.5f64]);
+    let ret = query_distance(&a, &b, 1e-9).expect("gjk return unexpected");
+    assert!((ret._distance - 8f64.sqrt()).abs() < 1e-9);
+    assert_matrix_approx_eq_float(
+        &ret._point_a,
+        &Matrix::from([[1f64, 1f64, 0.5f64, 1f64]]).t(),
+        1e-9,
+    );
+    assert_matrix_approx_eq_float(&ret._point_b, &b._ori, 1e-9);
+}
+#[test]
+fn test_intersect_gjk_query_distance_triprism_sphere() {
+    use crate::sphere::*;
+    use crate::tri_prism::*;
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = Sphere::init(&[0.75f64, 0.25f64, 3f64], 0.5f64);
+    let ret = query_distance(&a, &b, 1e-9).expect("gjk return unexpected");
+    assert!((ret._distance - 1.5f64).abs() < 1e-6);
+    assert_matrix_approx_eq_float(
+        &ret._normal,
+        &Matrix::from([[0f64, 0f64, 1f64, 0f64]]).t(),
+        1e-6,
+    );
+}
+#[test]
+fn test_intersect_gjk_query_distance_overlap() {
+    use crate::rect_box::*;
+    use crate::sphere::*;
+    let a = RectBox::init(&[0f64, 0f64, 0f64], 1f64);
+    let b = Sphere::init(&[1.5f64, 0f64, 0f64], 0.8f64);
+    let ret = query_distance(&a, &b, 1e-9).expect("gjk return unexpected");
+    assert_eq!(ret._distance, 0f64);
+}
+#[test]
+fn test_intersect_gjk_query_distance_unsupported() {
+    use crate::plane::*;
+    use crate::sphere::*;
+    let a = Plane::init(&[0f64, 0f64, 0f64], &[0f64, 1f64, 0f64]);
+    let b = Sphere::init(&[7f64, 0f64, 0f64], 2f64);
+    assert!(query_distance(&a, &b, 1e-9).is_none());
+}
+#[test]
+fn test_intersect_gjk_try_query_intersect_unsupported() {
+    use crate::plane::*;
+    use crate::sphere::*;
+    let a = Sphere::init(&[7f64, 0f64, 0f64], 2f64);
+    let b = Plane::init(&[0f64, 0f64, 0f64], &[0f64, 1f64, 0f64]);
+    assert_eq!(
+        try_query_intersect(&a, &b),
+        Err(CollideError::UnsupportedPair(
+            ShapeType::Sphere,
+            ShapeType::Plane
+        ))
+    );
+}
@@ -0,0 +1,120 @@
+//! separating axis test for pairs of convex polytopes given by their vertices
+
+use crate::contact::Contact;
+use lightmatrix::matrix::*;
+use num_traits::{Float, NumAssign};
+
+///interval of the vertices projected onto axis
+fn project<T>(verts: &[Matrix<T, 4, 1>], axis: &Matrix<T, 4, 1>) -> (T, T)
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    verts
+        .iter()
+        .fold((T::infinity(), T::neg_infinity()), |(lo, hi), v| {
+            let d = v.inner(axis);
+            (lo.min(d), hi.max(d))
+        })
+}
+
+///minimum overlap of the polytopes over the candidate axes, with the axis oriented from a towards b
+///
+///returns None if any of the axes separates the polytopes. degenerate axes are skipped,
+///the caller is responsible for supplying all face normals and edge cross products required for the pair
+pub fn query_overlap<T>(
+    axes: &[Matrix<T, 4, 1>],
+    verts_a: &[Matrix<T, 4, 1>],
+    verts_b: &[Matrix<T, 4, 1>],
+) -> Option<(T, Matrix<T, 4, 1>)>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let tol = T::from(1e-9).unwrap();
+    let mut best: Option<(T, Matrix<T, 4, 1>)> = None;
+    for axis in axes.iter() {
+        let l = axis.norm_l2();
+        if l <= tol {
+            continue;
+        }
+        let n = *axis / l;
+        let (lo_a, hi_a) = project(verts_a, &n);
+        let (lo_b, hi_b) = project(verts_b, &n);
+        if hi_a < lo_b || hi_b < lo_a {
+            return None;
+        }
+        //overlap when pushing b along the axis or against it
+        let (overlap, n) = if hi_a - lo_b <= hi_b - lo_a {
+            (hi_a - lo_b, n)
+        } else {
+            (hi_b - lo_a, n * -T::one())
+        };
+        match best {
+            Some((d, _)) if d <= overlap => {}
+            _ => best = Some((overlap, n)),
+        }
+    }
+    best
+}
+
+///contact of the polytopes along the axis of minimum overlap,
+///with the deepest vertex of a as contact point of a
+pub fn query_contact<T>(
+    axes: &[Matrix<T, 4, 1>],
+    verts_a: &[Matrix<T, 4, 1>],
+    verts_b: &[Matrix<T, 4, 1>],
+) -> Option<Contact<T>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let (depth, n) = query_overlap(axes, verts_a, verts_b)?;
+    let mut deepest = verts_a[0];
+    for v in verts_a.iter().skip(1) {
+        if v.inner(&n) > deepest.inner(&n) {
+            deepest = *v;
+        }
+    }
+    Some(Contact {
+        _point_a: deepest,
+        _point_b: deepest - n * depth,
+        _normal: n,
+        _depth: depth,
+        _feature: None,
+    })
+}
+
+#[cfg(test)]
+fn cube(c: [f64; 3], s: f64) -> Vec<Matrix<f64, 4, 1>> {
+    let mut v = vec![];
+    for i in 0..8 {
+        let sx = if i & 1 == 0 { -s } else { s };
+        let sy = if i & 2 == 0 { -s } else { s };
+        let sz = if i & 4 == 0 { -s } else { s };
+        v.push(Matrix::from([[c[0] + sx, c[1] + sy, c[2] + sz, 1.]]).t());
+    }
+    v
+}
+
+#[test]
+fn test_sat_cubes() {
+    let axes = [
+        Matrix::from([[1f64, 0., 0., 0.]]).t(),
+        Matrix::from([[0f64, 1., 0., 0.]]).t(),
+        Matrix::from([[0f64, 0., 1., 0.]]).t(),
+    ];
+    let a = cube([0., 0., 0.], 1.);
+    let b = cube([0., 1.8, 0.2], 1.);
+    let c = query_contact(&axes, &a, &b).expect("sat unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-9);
+    assert!((c._point_a[[1, 0]] - 1.).abs() < 1e-9);
+    assert!((c._point_b[[1, 0]] - 0.8).abs() < 1e-9);
+
+    //b on the negative side
+    let b = cube([0., -1.8, 0.2], 1.);
+    let (d, n) = query_overlap(&axes, &a, &b).expect("sat unexpected miss");
+    assert!((d - 0.2).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&n, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-9);
+
+    let b = cube([0., 2.1, 0.], 1.);
+    assert!(query_overlap(&axes, &a, &b).is_none());
+}
@@ -2,15 +2,36 @@ pub use lightmatrix;
 
 pub mod bound;
 pub mod bound_aabb;
+pub mod bound_sphere;
+pub mod bvh;
+pub mod capsule;
+pub mod compound;
+pub mod cone;
+pub mod contact;
+pub mod convex_hull;
+pub mod convex_polygon;
+pub mod cuboid;
+pub mod cylinder;
+pub mod dispatch;
+pub mod ellipsoid;
+pub mod error;
+pub mod frustum;
+pub mod height_field;
+pub mod intersect_epa;
 pub mod intersect_gjk;
+pub mod intersect_sat;
 pub mod line;
+pub mod oriented_box;
 pub mod plane;
 pub mod point;
 pub mod ray;
+pub mod ray_cast;
 pub mod rect_box;
 pub mod shape;
 pub mod sphere;
+pub mod tri_mesh;
 pub mod tri_prism;
+pub mod triangle;
 pub mod vicinity;
 
 mod ray_point_intersect;
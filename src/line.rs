@@ -1,5 +1,15 @@
 use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::cuboid::{axis_unit, intersect_slab};
+use crate::dispatch;
+use crate::error::CollideError;
+use crate::plane::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::ray_cast::*;
+use crate::rect_box::*;
 use crate::shape::*;
+use crate::sphere::*;
 use crate::vicinity::Vicinity;
 use core::any::Any;
 use num_traits::{Float, NumAssign};
@@ -26,9 +36,9 @@ where
         assert!(a.len() == 3);
         assert!(b.len() == 3);
 
-        let xs = vec![a[0], b[0]];
-        let ys = vec![a[1], b[1]];
-        let zs = vec![a[2], b[2]];
+        let xs = [a[0], b[0]];
+        let ys = [a[1], b[1]];
+        let zs = [a[2], b[2]];
 
         use std::cmp::Ordering::*;
 
@@ -66,11 +76,59 @@ where
                 ShapeType::Rect,
                 &[x_min, y_min, z_min, x_max, y_max, z_max],
             ),
-            _vicinity: T::epsilon(),
+            _vicinity: T::from(1e-7).unwrap(),
         }
     }
 }
 
+///closest pair of points between segments p1-q1 and p2-q2
+///see Ericson, Real-Time Collision Detection 5.1.9
+pub fn closest_points_segments<T>(
+    p1: &Matrix<T, 4, 1>,
+    q1: &Matrix<T, 4, 1>,
+    p2: &Matrix<T, 4, 1>,
+    q2: &Matrix<T, 4, 1>,
+) -> (Matrix<T, 4, 1>, Matrix<T, 4, 1>)
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let clamp = |x: T| x.max(T::zero()).min(T::one());
+    let d1 = *q1 - *p1;
+    let d2 = *q2 - *p2;
+    let r = *p1 - *p2;
+    let a = d1.inner(&d1);
+    let e = d2.inner(&d2);
+    let f = d2.inner(&r);
+    let (s, t) = if a == T::zero() && e == T::zero() {
+        (T::zero(), T::zero())
+    } else if a == T::zero() {
+        (T::zero(), clamp(f / e))
+    } else {
+        let c = d1.inner(&r);
+        if e == T::zero() {
+            (clamp(-c / a), T::zero())
+        } else {
+            let b = d1.inner(&d2);
+            let denom = a * e - b * b;
+            //parallel segments pick an arbitrary s
+            let s = if denom != T::zero() {
+                clamp((b * f - c * e) / denom)
+            } else {
+                T::zero()
+            };
+            let t = (b * s + f) / e;
+            if t < T::zero() {
+                (clamp(-c / a), T::zero())
+            } else if t > T::one() {
+                (clamp((b - c) / a), T::one())
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (*p1 + d1 * s, *p2 + d2 * t)
+}
+
 impl<T> AnyBase for Line<T>
 where
     T: NumAssign + Copy + Default + Float + 'static,
@@ -91,20 +149,193 @@ where
         &self._bound
     }
     // this shall test for intersection of bounding shapes first before procedding to test intersection using algorithms of higher complexity
-    fn get_intersect(&self, other: &dyn Shape<T>) -> (bool, Option<Matrix<T, 4, 1>>) {
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
         if !self.get_bound().intersect(other.get_bound()) {
-            return (false, None);
-        } else {
-            match other.get_type() {
-                ShapeType::TriPrism => other.get_intersect(self),
-                _ => {
-                    unimplemented!();
+            return Ok(None);
+        }
+        let dir = self._b - self._a;
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let p = other_point._ori;
+                let (closest, _) = closest_points_segments(&self._a, &self._b, &p, &p);
+                let d = (p - closest).norm_l2();
+                if d > self._vicinity {
+                    return Ok(None);
+                }
+                Ok(Some(Contact {
+                    _point_a: closest,
+                    _point_b: p,
+                    _normal: Matrix::zero(),
+                    _depth: -d,
+                    _feature: None,
+                }))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                let (ori, ray_dir) = (other_ray._ori, other_ray._dir);
+                //no point of the ray beyond the furthest projection of the segment can be closest to it
+                let reach =
+                    (self._a - ori).norm_l2().max((self._b - ori).norm_l2()) / ray_dir.norm_l2();
+                let (p, q) =
+                    closest_points_segments(&self._a, &self._b, &ori, &(ori + ray_dir * reach));
+                if (p - q).norm_l2() > self._vicinity {
+                    return Ok(None);
+                }
+                Ok(Some(Contact::init_point(p, Matrix::zero())))
+            }
+            ShapeType::Line => {
+                let other_line: &Line<T> = downcast(other)?;
+                let (p, q) =
+                    closest_points_segments(&self._a, &self._b, &other_line._a, &other_line._b);
+                if (p - q).norm_l2() > self._vicinity.max(other_line._vicinity) {
+                    return Ok(None);
                 }
+                Ok(Some(Contact::init_point(p, Matrix::zero())))
             }
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
+                let c = other_sphere._ori;
+                let r = other_sphere._radius;
+                let m = self._a - c;
+                let k = m.inner(&m) - r * r;
+                if k <= T::zero() {
+                    //segment starts inside of the sphere
+                    let n = if m.norm_l2() == T::zero() {
+                        Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t()
+                    } else {
+                        m / -m.norm_l2()
+                    };
+                    return Ok(Some(Contact::init_point(self._a, n)));
+                }
+                //first root of |a + dir * s - c| = r within the segment
+                let a = dir.inner(&dir);
+                let b = m.inner(&dir);
+                let disc = b * b - a * k;
+                if a == T::zero() || b > T::zero() || disc < T::zero() {
+                    return Ok(None);
+                }
+                let s = (-b - disc.sqrt()) / a;
+                if s > T::one() {
+                    return Ok(None);
+                }
+                let hit = self._a + dir * s;
+                Ok(Some(Contact::init_point(hit, (c - hit) / r)))
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                let n = other_plane._normal;
+                let d_a = (self._a - other_plane._offset).inner(&n);
+                let d_b = (self._b - other_plane._offset).inner(&n);
+                if (d_a > self._vicinity && d_b > self._vicinity)
+                    || (d_a < -self._vicinity && d_b < -self._vicinity)
+                {
+                    return Ok(None);
+                }
+                //normal points from the side the segment starts on towards the plane
+                let normal = if d_a >= T::zero() { n * -T::one() } else { n };
+                let s = if self.within_vicinity(d_a, T::zero()) || d_a == d_b {
+                    T::zero()
+                } else {
+                    d_a / (d_a - d_b)
+                };
+                Ok(Some(Contact::init_point(self._a + dir * s, normal)))
+            }
+            ShapeType::Box => {
+                let other_box: &RectBox<T> = downcast(other)?;
+                let size = [other_box._size; 3];
+                let rel = self._a - other_box._ori;
+                let rel = [rel[[0, 0]], rel[[1, 0]], rel[[2, 0]]];
+                if (0..3).all(|i| rel[i].abs() <= size[i]) {
+                    //segment starts inside of the box, normal points away from its nearest face
+                    let mut axis = 0;
+                    for i in 1..3 {
+                        if size[i] - rel[i].abs() < size[axis] - rel[axis].abs() {
+                            axis = i;
+                        }
+                    }
+                    let sign = if rel[axis] < T::zero() {
+                        -T::one()
+                    } else {
+                        T::one()
+                    };
+                    return Ok(Some(
+                        Contact::init_point(self._a, axis_unit(axis, -sign))
+                            .with_feature(axis * 2 + (sign > T::zero()) as usize),
+                    ));
+                }
+                match intersect_slab(&rel, &[dir[[0, 0]], dir[[1, 0]], dir[[2, 0]]], &size) {
+                    Some((t, axis, sign)) if t <= T::one() => Ok(Some(
+                        Contact::init_point(self._a + dir * t, axis_unit(axis, -sign))
+                            .with_feature(axis * 2 + (sign > T::zero()) as usize),
+                    )),
+                    _ => Ok(None),
+                }
+            }
+            ShapeType::Rect
+            | ShapeType::OrientedBox
+            | ShapeType::Frustum
+            | ShapeType::Capsule
+            | ShapeType::Cylinder
+            | ShapeType::Cone
+            | ShapeType::Ellipsoid
+            | ShapeType::ConvexHull => dispatch::try_intersect_convex(self, other),
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
         }
     }
-    fn get_support(&self, _v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
-        unimplemented!();
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() {
+            if self._a.inner(v) >= self._b.inner(v) {
+                Some(self._a)
+            } else {
+                Some(self._b)
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> RayCast<T> for Line<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    //a segment has no interior, solid has no effect
+    fn cast_ray(&self, ray: &Ray<T>, t_min: T, t_max: T, _solid: bool) -> Option<RayHit<T>> {
+        if t_min > t_max {
+            return None;
+        }
+        let (ori, dir) = (ray._ori, ray._dir);
+        let start = ori + dir * t_min;
+        //no point of the ray beyond the furthest projection of the segment can be closest to it
+        let reach =
+            (t_max - t_min).min((self._a - start).norm_l2().max((self._b - start).norm_l2()));
+        let (_, q) = closest_points_segments(&self._a, &self._b, &start, &(start + dir * reach));
+        //collinear segments pick an arbitrary closest pair, so the projected endpoints are tested as well
+        let mut toi = T::infinity();
+        for p in [q, self._a, self._b].iter() {
+            let t = (*p - ori).inner(&dir).max(t_min).min(t_max);
+            let (closest, _) =
+                closest_points_segments(&self._a, &self._b, &(ori + dir * t), &(ori + dir * t));
+            if (ori + dir * t - closest).norm_l2() <= self._vicinity && t < toi {
+                toi = t;
+            }
+        }
+        if !toi.is_finite() {
+            return None;
+        }
+        Some(RayHit {
+            _toi: toi,
+            _point: ori + dir * toi,
+            _normal: Matrix::zero(),
+            _feature: None,
+        })
     }
 }
 
@@ -116,10 +347,144 @@ where
         self._vicinity = epsilon.abs();
     }
     fn within_vicinity(&self, a: T, b: T) -> bool {
-        if a + self._vicinity >= b && a - self._vicinity <= b {
-            true
-        } else {
-            false
-        }
+        a + self._vicinity >= b && a - self._vicinity <= b
     }
 }
+
+#[test]
+fn test_line_support() {
+    let a = Line::init(&[0., 0., 0.], &[2., 1., 0.]);
+    let s = a
+        .get_support(&Matrix::from([[1., 0., 0., 0.]]).t())
+        .expect("line support unexpected");
+    assert_eq!(s, a._b);
+    let s = a
+        .get_support(&Matrix::from([[-1., 1., 0., 0.]]).t())
+        .expect("line support unexpected");
+    assert_eq!(s, a._a);
+    assert!(a.get_support(&Matrix::zero()).is_none());
+
+    //segment through a capsule goes through gjk
+    let b = crate::capsule::Capsule::init(&[1., -1., 0.], &[1., 1., 0.], 0.2);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(true));
+    assert!(a.get_intersect(&b).is_some());
+    let a = Line::init(&[0., 0., 1.], &[2., 1., 1.]);
+    assert_eq!(crate::intersect_gjk::query_intersect(&a, &b), Some(false));
+}
+#[test]
+fn test_intersect_line_point_ray_line() {
+    let a = Line::init(&[0., 0., 0.], &[2., 2., 0.]);
+    let c = a
+        .get_intersect(&Point::init(&[0.5, 0.5, 0.]))
+        .expect("line point unexpected miss");
+    assert_eq!(c._point_a, Matrix::from([[0.5, 0.5, 0., 1.]]).t());
+    assert!(Point::init(&[0.5, 0.5, 0.]).get_intersect(&a).is_some());
+    assert!(a.get_intersect(&Point::init(&[0.5, 0.6, 0.])).is_none());
+
+    let b = Ray::init(&[0., 1., 0.], &[1., 0., 0.]);
+    let c = a.get_intersect(&b).expect("line ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[1., 1., 0., 1.]]).t(), 1e-12);
+    assert!(b.get_intersect(&a).is_some());
+    assert!(a
+        .get_intersect(&Ray::init(&[0., 1., 0.], &[-1., 0., 0.]))
+        .is_none());
+
+    let b = Line::init(&[0., 2., 0.], &[2., 0., 0.]);
+    let c = a.get_intersect(&b).expect("line line unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[1., 1., 0., 1.]]).t(), 1e-12);
+    assert!(a
+        .get_intersect(&Line::init(&[0., 2., 0.], &[0.9, 1.1, 0.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_line_sphere() {
+    let a = Line::init(&[-5., 0., 0.], &[5., 0., 0.]);
+    let b = Sphere::init(&[1., 0., 0.], 2.);
+    let c = a.get_intersect(&b).expect("line sphere unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-1., 0., 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("sphere line unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+
+    //first contact is where the segment starts
+    let a = Line::init(&[0., 0., 0.], &[5., 0., 0.]);
+    let c = a.get_intersect(&b).expect("line sphere unexpected miss");
+    assert_eq!(c._point_a, a._a);
+    //ends short of the sphere
+    let a = Line::init(&[-5., 0., 0.], &[-1.5, 0., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_line_fractional() {
+    //shapes built from measured coordinates are off by rounding errors well above machine epsilon
+    let a = Line::init(&[10.1, 20.2, 30.3], &[17.7, 29.9, 30.3]);
+    let p = [10.1 + 7.6 * 0.3 + 1e-9, 20.2 + 9.7 * 0.3 - 1e-9, 30.3];
+    assert!(a.get_intersect(&Point::init(&p)).is_some());
+    let b = Line::init(&[p[0], p[1], 29.3], &[p[0], p[1], 31.3]);
+    let c = a.get_intersect(&b).expect("line line unexpected miss");
+    assert_matrix_approx_eq_float(
+        &c._point_a,
+        &Matrix::from([[p[0], p[1], p[2], 1.]]).t(),
+        1e-8,
+    );
+    let r = Ray::init(&[p[0], p[1], 29.3], &[0., 0., 1.]);
+    assert!(a.get_intersect(&r).is_some());
+    assert!(a
+        .get_intersect(&Point::init(&[p[0], p[1], 30.3001]))
+        .is_none());
+}
+#[test]
+fn test_intersect_line_plane() {
+    let a = Line::init(&[0., 3., 0.], &[0., -1., 0.]);
+    let b = Plane::init(&[0., 1., 0.], &[0., 1., 0.]);
+    let c = a.get_intersect(&b).expect("line plane unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 1., 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("plane line unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+    assert!(Line::init(&[0., 3., 0.], &[0., 1.5, 0.])
+        .get_intersect(&b)
+        .is_none());
+}
+#[test]
+fn test_intersect_line_box() {
+    let b = RectBox::init(&[0., 0., 0.], 1.);
+    let a = Line::init(&[-3., 0.5, 0.], &[3., 0.5, 0.]);
+    let c = a.get_intersect(&b).expect("line box unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-1., 0.5, 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+    assert_eq!(c._feature, Some(0));
+    let c = b.get_intersect(&a).expect("box line unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+
+    //starting inside
+    let a = Line::init(&[0.5, 0.8, 0.], &[3., 0.8, 0.]);
+    let c = a.get_intersect(&b).expect("line box unexpected miss");
+    assert_eq!(c._point_a, a._a);
+    assert_eq!(c._feature, Some(3));
+
+    assert!(Line::init(&[-3., 0.5, 0.], &[-1.5, 0.5, 0.])
+        .get_intersect(&b)
+        .is_none());
+    assert!(Line::init(&[-3., 1.5, 0.], &[3., 0.5, 3.])
+        .get_intersect(&b)
+        .is_none());
+}
+#[test]
+fn test_raycast_line() {
+    let a = Line::init(&[3., -1., 0.], &[3., 1., 0.]);
+    let r = Ray::init(&[0., 0., 0.], &[1., 0., 0.]);
+    let h = a
+        .cast_ray(&r, 0., 10., true)
+        .expect("line cast unexpected miss");
+    assert!((h._toi - 3.).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&h._point, &Matrix::from([[3., 0., 0., 1.]]).t(), 1e-12);
+    assert!(a.cast_ray(&r, 0., 2., true).is_none());
+
+    //collinear, hit at the nearer endpoint
+    let a = Line::init(&[6., 0., 0.], &[4., 0., 0.]);
+    let h = a
+        .cast_ray(&r, 0., 10., true)
+        .expect("line cast unexpected miss");
+    assert!((h._toi - 4.).abs() < 1e-12);
+}
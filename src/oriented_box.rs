@@ -0,0 +1,461 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::cuboid::*;
+use crate::error::CollideError;
+use crate::intersect_sat;
+use crate::plane::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::rect_box::*;
+use crate::shape::*;
+use crate::sphere::*;
+use crate::tri_prism::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use lightmatrix::quatr::QuatR;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+///box with a center, a rotation and half extents along its rotated axes
+#[derive(Debug, Clone)]
+pub struct OrientedBox<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _ori: Matrix<T, 4, 1>,
+    ///half of the length of box edge along each of its local axes
+    pub _size: [T; 3],
+    pub _rot: QuatR<T>,
+    ///local axes rotated into world space
+    pub _axes: [Matrix<T, 4, 1>; 3],
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+impl<T> OrientedBox<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub fn init(origin: &[T], size: &[T], rot: QuatR<T>) -> OrientedBox<T> {
+        assert!(origin.len() == 3);
+        assert!(size.len() == 3);
+        let mut b = OrientedBox {
+            _ori: Matrix::from([[origin[0], origin[1], origin[2], T::one()]]).t(),
+            _size: [size[0], size[1], size[2]],
+            _rot: QuatR::default(),
+            _axes: [Matrix::zero(); 3],
+            _bound: AxisAlignedBBox::default(),
+            _vicinity: T::epsilon(),
+        };
+        b.set_rotation(rot);
+        b
+    }
+
+    ///updates the axes and the enclosing bound
+    pub fn set_rotation(&mut self, rot: QuatR<T>) {
+        let m = rot.to_matrix();
+        for (j, axis) in self._axes.iter_mut().enumerate() {
+            *axis = Matrix::from([[m[[0, j]], m[[1, j]], m[[2, j]], T::zero()]]).t();
+        }
+        self._rot = rot;
+        self.update_bound();
+    }
+
+    ///updates the enclosing bound
+    pub fn set_origin(&mut self, origin: &[T]) {
+        assert!(origin.len() == 3);
+        self._ori = Matrix::from([[origin[0], origin[1], origin[2], T::one()]]).t();
+        self.update_bound();
+    }
+
+    fn update_bound(&mut self) {
+        let mut vals = [T::zero(); 6];
+        for i in 0..3 {
+            let extent = (0..3).fold(T::zero(), |acc, j| {
+                acc + self._axes[j][[i, 0]].abs() * self._size[j]
+            });
+            vals[i] = self._ori[[i, 0]] - extent;
+            vals[i + 3] = self._ori[[i, 0]] + extent;
+        }
+        self._bound = AxisAlignedBBox::new(ShapeType::Rect, &vals);
+    }
+
+    ///coordinates of p relative to the center along the local axes
+    pub fn get_local(&self, p: &Matrix<T, 4, 1>) -> [T; 3] {
+        let d = *p - self._ori;
+        [
+            d.inner(&self._axes[0]),
+            d.inner(&self._axes[1]),
+            d.inner(&self._axes[2]),
+        ]
+    }
+
+    ///point from coordinates along the local axes
+    pub fn get_world(&self, l: &[T; 3]) -> Matrix<T, 4, 1> {
+        self._ori + self._axes[0] * l[0] + self._axes[1] * l[1] + self._axes[2] * l[2]
+    }
+
+    ///the 8 corners of the box
+    pub fn get_corners(&self) -> [Matrix<T, 4, 1>; 8] {
+        let mut corners = [self._ori; 8];
+        for (i, c) in corners.iter_mut().enumerate() {
+            let mut l = self._size;
+            for (axis, x) in l.iter_mut().enumerate() {
+                if i & (1 << axis) == 0 {
+                    *x = -*x;
+                }
+            }
+            *c = self.get_world(&l);
+        }
+        corners
+    }
+
+    ///face with the largest signed distance to the local point l, see Cuboid
+    fn get_nearest_face(&self, l: &[T; 3]) -> (usize, T, T) {
+        let mut best = (0, T::one(), T::neg_infinity());
+        for (i, (x, s)) in l.iter().zip(self._size.iter()).enumerate() {
+            let dist = x.abs() - *s;
+            if i == 0 || dist > best.2 {
+                let sign = if *x < T::zero() { -T::one() } else { T::one() };
+                best = (i, sign, dist);
+            }
+        }
+        best
+    }
+
+    ///contact with a convex polytope using separating axes of the box faces,
+    ///the polytope's face normals and cross products of their edge directions
+    fn intersect_polytope(
+        &self,
+        verts: &[Matrix<T, 4, 1>],
+        normals: &[Matrix<T, 4, 1>],
+        edges: &[Matrix<T, 4, 1>],
+    ) -> Option<Contact<T>> {
+        let mut axes = self._axes.to_vec();
+        axes.extend_from_slice(normals);
+        for a in self._axes.iter() {
+            for e in edges.iter() {
+                axes.push(a.cross(e));
+            }
+        }
+        intersect_sat::query_contact(&axes, &self.get_corners(), verts)
+    }
+}
+
+impl<T> AnyBase for OrientedBox<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for OrientedBox<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::OrientedBox
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._size.iter().any(|s| s.is_nan() || *s < T::zero())
+            || self._axes.iter().any(|a| !a.norm_l2().is_finite())
+        {
+            return Err(CollideError::DegenerateShape(ShapeType::OrientedBox));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        let world_axes = [
+            Matrix::from([[T::one(), T::zero(), T::zero(), T::zero()]]).t(),
+            Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t(),
+            Matrix::from([[T::zero(), T::zero(), T::one(), T::zero()]]).t(),
+        ];
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let l = self.get_local(&other_point._ori);
+                let (axis, sign, dist) = self.get_nearest_face(&l);
+                if dist > T::zero() {
+                    return Ok(None);
+                }
+                let mut l_face = l;
+                l_face[axis] = sign * self._size[axis];
+                Ok(Some(Contact {
+                    _point_a: self.get_world(&l_face),
+                    _point_b: other_point._ori,
+                    _normal: self._axes[axis] * sign,
+                    _depth: -dist,
+                    _feature: Some(axis * 2 + (sign > T::zero()) as usize),
+                }))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                let l = self.get_local(&other_ray._ori);
+                let dir = [
+                    other_ray._dir.inner(&self._axes[0]),
+                    other_ray._dir.inner(&self._axes[1]),
+                    other_ray._dir.inner(&self._axes[2]),
+                ];
+                match intersect_slab(&l, &dir, &self._size) {
+                    Some((t, axis, sign)) => Ok(Some(
+                        Contact::init_point(
+                            other_ray._ori + other_ray._dir * t,
+                            self._axes[axis] * sign,
+                        )
+                        .with_feature(axis * 2 + (sign > T::zero()) as usize),
+                    )),
+                    _ => Ok(None),
+                }
+            }
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
+                let c = other_sphere._ori;
+                let r = other_sphere._radius;
+                let l = self.get_local(&c);
+                let (axis, sign, dist) = self.get_nearest_face(&l);
+                if dist > T::zero() {
+                    //center outside of the box
+                    let mut l_closest = l;
+                    for (x, s) in l_closest.iter_mut().zip(self._size.iter()) {
+                        *x = x.max(-*s).min(*s);
+                    }
+                    let q = self.get_world(&l_closest);
+                    let d = (c - q).norm_l2();
+                    if d > r {
+                        return Ok(None);
+                    }
+                    let n = (c - q) / d;
+                    Ok(Some(Contact {
+                        _point_a: q,
+                        _point_b: c - n * r,
+                        _normal: n,
+                        _depth: r - d,
+                        _feature: None,
+                    }))
+                } else {
+                    //center inside of the box, push out through the nearest face
+                    let n = self._axes[axis] * sign;
+                    let mut l_face = l;
+                    l_face[axis] = sign * self._size[axis];
+                    Ok(Some(Contact {
+                        _point_a: self.get_world(&l_face),
+                        _point_b: c - n * r,
+                        _normal: n,
+                        _depth: r - dist,
+                        _feature: Some(axis * 2 + (sign > T::zero()) as usize),
+                    }))
+                }
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                let n = other_plane._normal;
+                //projected radius of the box onto the plane normal
+                let r = (0..3).fold(T::zero(), |acc, j| {
+                    acc + n.inner(&self._axes[j]).abs() * self._size[j]
+                });
+                let s = (self._ori - other_plane._offset).inner(&n);
+                if s.abs() > r {
+                    return Ok(None);
+                }
+                let (normal, depth) = if s >= T::zero() {
+                    (n * -T::one(), r - s)
+                } else {
+                    (n, r + s)
+                };
+                let mut l = self._size;
+                for (j, x) in l.iter_mut().enumerate() {
+                    if normal.inner(&self._axes[j]) < T::zero() {
+                        *x = -*x;
+                    }
+                }
+                let deepest = self.get_world(&l);
+                Ok(Some(Contact {
+                    _point_a: deepest,
+                    _point_b: deepest - normal * depth,
+                    _normal: normal,
+                    _depth: depth,
+                    _feature: None,
+                }))
+            }
+            ShapeType::OrientedBox => {
+                let other_box: &OrientedBox<T> = downcast(other)?;
+                Ok(self.intersect_polytope(
+                    &other_box.get_corners(),
+                    &other_box._axes,
+                    &other_box._axes,
+                ))
+            }
+            ShapeType::Box => {
+                let other_box: &RectBox<T> = downcast(other)?;
+                Ok(self.intersect_polytope(&other_box.get_corners(), &world_axes, &world_axes))
+            }
+            ShapeType::Rect => {
+                let other_cuboid: &Cuboid<T> = downcast(other)?;
+                Ok(self.intersect_polytope(&other_cuboid.get_corners(), &world_axes, &world_axes))
+            }
+            ShapeType::TriPrism => {
+                let other_prism: &TriPrism<T> = downcast(other)?;
+                let normals: Vec<Matrix<T, 4, 1>> =
+                    other_prism.get_halfspaces().iter().map(|x| x.1).collect();
+                Ok(self.intersect_polytope(
+                    &other_prism.get_vertices(),
+                    &normals,
+                    &other_prism.get_edge_directions(),
+                ))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() {
+            //get a furthest point in the given direction v
+            let mut l = self._size;
+            for (j, x) in l.iter_mut().enumerate() {
+                if v.inner(&self._axes[j]) < T::zero() {
+                    *x = -*x;
+                }
+            }
+            Some(self.get_world(&l))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for OrientedBox<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+#[cfg(test)]
+fn rot_z(degree: f64) -> QuatR<f64> {
+    QuatR::init_from_axis_angle_degree(Matrix::from([[0., 0., 1.]]).t(), degree)
+}
+
+#[test]
+fn test_oriented_box_bound() {
+    let mut a = OrientedBox::init(&[0., 0., 0.], &[1., 1., 1.], rot_z(0.));
+    assert_eq!(a._bound.bound_upper, [1., 1., 1.]);
+    a.set_rotation(rot_z(45.));
+    assert!((a._bound.bound_upper[0] - 2f64.sqrt()).abs() < 1e-9);
+    assert!((a._bound.bound_lower[1] + 2f64.sqrt()).abs() < 1e-9);
+    assert!((a._bound.bound_upper[2] - 1.).abs() < 1e-9);
+
+    let s = a
+        .get_support(&Matrix::from([[1., 0., 0., 0.]]).t())
+        .expect("oriented box support unexpected");
+    assert!((s[[0, 0]] - 2f64.sqrt()).abs() < 1e-9);
+}
+#[test]
+fn test_intersect_oriented_box_point() {
+    let a = OrientedBox::init(&[0., 0., 0.], &[1., 1., 1.], rot_z(45.));
+    //inside of the rotated box but outside of the unrotated one
+    let b = Point::init(&[1.2, 0., 0.]);
+    let c = a
+        .get_intersect(&b)
+        .expect("oriented box point unexpected miss");
+    assert!((c._depth - (2f64.sqrt() - 1.2) / 2f64.sqrt()).abs() < 1e-9);
+
+    let b = Point::init(&[1., 1., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+    assert!(b.get_intersect(&a).is_none());
+}
+#[test]
+fn test_intersect_oriented_box_ray() {
+    let a = OrientedBox::init(&[0., 0., 0.], &[1., 1., 1.], rot_z(45.));
+    let b = Ray::init(&[-5., 0., 0.], &[1., 0., 0.]);
+    let c = a
+        .get_intersect(&b)
+        .expect("oriented box ray unexpected miss");
+    assert_matrix_approx_eq_float(
+        &c._point_a,
+        &Matrix::from([[-2f64.sqrt(), 0., 0., 1.]]).t(),
+        1e-9,
+    );
+    assert!(c._normal[[0, 0]] < 0.);
+
+    let b = Ray::init(&[-5., 1.5, 0.], &[1., 0., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+    let b = Ray::init(&[-5., 1.2, 0.], &[1., 0., 0.]);
+    assert!(b.get_intersect(&a).is_some());
+}
+#[test]
+fn test_intersect_oriented_box_sphere() {
+    let a = OrientedBox::init(&[0., 0., 0.], &[1., 1., 1.], rot_z(45.));
+    let b = Sphere::init(&[2., 0., 0.], 0.7);
+    let c = a
+        .get_intersect(&b)
+        .expect("oriented box sphere unexpected miss");
+    assert!((c._depth - (0.7 - (2. - 2f64.sqrt()))).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-9);
+
+    let b = Sphere::init(&[2., 0., 0.], 0.5);
+    assert!(a.get_intersect(&b).is_none());
+    assert!(b.get_intersect(&a).is_none());
+}
+#[test]
+fn test_intersect_oriented_box_plane() {
+    let a = OrientedBox::init(&[0., 0., 0.], &[1., 1., 1.], rot_z(45.));
+    let b = Plane::init(&[0., -1.3, 0.], &[0., 1., 0.]);
+    let c = a
+        .get_intersect(&b)
+        .expect("oriented box plane unexpected miss");
+    assert!((c._depth - (2f64.sqrt() - 1.3)).abs() < 1e-9);
+    assert!(c._point_a[[0, 0]].abs() < 1e-9);
+    assert!((c._point_a[[1, 0]] + 2f64.sqrt()).abs() < 1e-9);
+
+    let b = Plane::init(&[0., -1.5, 0.], &[0., 1., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_oriented_box_box() {
+    let a = OrientedBox::init(&[0., 0., 0.], &[1., 1., 1.], rot_z(45.));
+    //separated only along an edge cross product or face axis of a
+    let b = OrientedBox::init(&[2.5, 0., 0.], &[1., 1., 1.], rot_z(0.));
+    assert!(a.get_intersect(&b).is_none());
+    let b = OrientedBox::init(&[2.3, 0., 0.], &[1., 1., 1.], rot_z(0.));
+    let c = a
+        .get_intersect(&b)
+        .expect("oriented box box unexpected miss");
+    assert!((c._depth - (2f64.sqrt() + 1. - 2.3)).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-9);
+
+    let b = RectBox::init(&[2.3, 0., 0.], 1.);
+    assert!(b.get_intersect(&a).is_some());
+    let b = Cuboid::init(&[0., 2.5, 0.], &[3., 1., 1.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_oriented_box_triprism() {
+    let a = OrientedBox::init(&[0., 0., 0.], &[1., 1., 1.], rot_z(45.));
+    let b = TriPrism::init(&[1.2, 0., -0.5, 3., 0., -0.5, 3., 1., -0.5], 1.);
+    let c = a
+        .get_intersect(&b)
+        .expect("oriented box triprism unexpected miss");
+    //least overlap is along a face normal of the box rather than the x axis
+    assert!((c._depth - (1. - 1.2 / 2f64.sqrt())).abs() < 1e-9);
+
+    let b = TriPrism::init(&[1.5, 0., -0.5, 3., 0., -0.5, 3., 1., -0.5], 1.);
+    assert!(a.get_intersect(&b).is_none());
+    assert!(b.get_intersect(&a).is_none());
+}
@@ -1,5 +1,9 @@
 use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::error::CollideError;
 use crate::point::*;
+use crate::ray::Ray;
+use crate::ray_cast::*;
 use crate::shape::*;
 use crate::vicinity::Vicinity;
 use core::any::Any;
@@ -35,6 +39,114 @@ where
             _vicinity: T::epsilon(),
         }
     }
+
+    ///signed distance of p from the plane, positive on the side the normal points to
+    pub fn get_distance(&self, p: &Matrix<T, 4, 1>) -> T {
+        (*p - self._offset).inner(&self._normal)
+    }
+
+    ///line of intersection with another plane, given by its point nearest to the offset of self
+    ///and its unit direction, or the relation of the planes if they are parallel
+    pub fn intersect_plane(&self, other: &Plane<T>) -> PlaneIntersection<T> {
+        let dir = self._normal.cross(&other._normal);
+        let l = dir.norm_l2();
+        if l <= T::epsilon() {
+            let d = other.get_distance(&self._offset);
+            return if self.within_vicinity(d, T::zero()) {
+                PlaneIntersection::Coincident
+            } else {
+                PlaneIntersection::Parallel(-d * self._normal.inner(&other._normal).signum())
+            };
+        }
+        //step within self along the part of the other normal perpendicular to self
+        let c = self._normal.inner(&other._normal);
+        let v = other._normal - self._normal * c;
+        let t = -other.get_distance(&self._offset) / (l * l);
+        PlaneIntersection::Line(self._offset + v * t, dir / l)
+    }
+}
+
+///relation of two planes
+#[derive(Debug, Clone)]
+pub enum PlaneIntersection<T: NumAssign + Copy + Default + Float> {
+    ///point on the line of intersection and its unit direction
+    Line(Matrix<T, 4, 1>, Matrix<T, 4, 1>),
+    Coincident,
+    ///signed distance of the other plane along the normal of the first
+    Parallel(T),
+}
+
+///side of a plane a shape lies on, front being the side the normal points to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaneSide {
+    Front,
+    Back,
+    Both,
+}
+
+///placement of a convex shape relative to a plane
+#[derive(Debug, Clone)]
+pub struct PlaneDistance<T: NumAssign + Copy + Default + Float> {
+    ///signed distance of _point, for a shape on both sides that of its lesser part
+    pub _distance: T,
+    ///point of the shape nearest to the plane, or deepest past it for a shape on both sides
+    pub _point: Matrix<T, 4, 1>,
+    pub _side: PlaneSide,
+}
+
+impl<T> Plane<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    ///support points of a convex shape against and along the normal with their signed distances
+    fn get_support_distances(&self, other: &dyn Shape<T>) -> Option<[(Matrix<T, 4, 1>, T); 2]> {
+        let n = self._normal;
+        let lo = other.get_support(&(n * -T::one()))?;
+        let hi = other.get_support(&n)?;
+        Some([(lo, self.get_distance(&lo)), (hi, self.get_distance(&hi))])
+    }
+
+    ///signed distance and side of a convex shape, None if the shape has no support mapping.
+    ///contacts against a plane are only reported for overlaps, this also covers separated shapes
+    pub fn get_signed_distance(&self, other: &dyn Shape<T>) -> Option<PlaneDistance<T>> {
+        let [(lo, d_lo), (hi, d_hi)] = self.get_support_distances(other)?;
+        let (p, d, side) = if d_lo > T::zero() {
+            (lo, d_lo, PlaneSide::Front)
+        } else if d_hi < T::zero() {
+            (hi, d_hi, PlaneSide::Back)
+        } else if -d_lo <= d_hi {
+            (lo, d_lo, PlaneSide::Both)
+        } else {
+            (hi, d_hi, PlaneSide::Both)
+        };
+        Some(PlaneDistance {
+            _distance: d,
+            _point: p,
+            _side: side,
+        })
+    }
+
+    ///contact of a convex shape as shape a against the plane as shape b, only reported while the shape
+    ///touches or crosses the plane. the plane is pushed towards the side holding the lesser part of the shape
+    pub fn intersect_convex(&self, other: &dyn Shape<T>) -> Option<Contact<T>> {
+        let n = self._normal;
+        let [(lo, d_lo), (hi, d_hi)] = self.get_support_distances(other)?;
+        if d_lo > T::zero() || d_hi < T::zero() {
+            return None;
+        }
+        let (p, d, normal) = if -d_lo <= d_hi {
+            (lo, d_lo, n * -T::one())
+        } else {
+            (hi, d_hi, n)
+        };
+        Some(Contact {
+            _point_a: p,
+            _point_b: p - n * d,
+            _normal: normal,
+            _depth: d.abs(),
+            _feature: None,
+        })
+    }
 }
 
 impl<T> AnyBase for Plane<T>
@@ -57,43 +169,65 @@ where
         &self._bound
     }
     // this shall test for intersection of bounding shapes first before procedding to test intersection using algorithms of higher complexity
-    fn get_intersect(&self, other: &dyn Shape<T>) -> (bool, Option<Matrix<T, 4, 1>>) {
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if !self._normal.norm_l2().is_finite() {
+            return Err(CollideError::DegenerateShape(ShapeType::Plane));
+        }
         if !self.get_bound().intersect(other.get_bound()) {
-            return (false, None);
-        } else {
-            match other.get_type() {
-                ShapeType::Plane => {
-                    unimplemented!();
-                }
-                ShapeType::Ray => {
-                    //see Ray3 for ray plane intersection
-                    return other.get_intersect(self);
-                }
-                ShapeType::Sphere => {
-                    //see sphere for sphere plane intersection
-                    return other.get_intersect(self);
-                }
-                ShapeType::Point => {
-                    let other_point: &Point<T> = match other.as_any().downcast_ref::<Point<T>>() {
-                        Some(b) => b,
-                        None => {
-                            panic!("cast to Point failed");
-                        }
-                    };
-                    let b_off = other_point._ori;
-
-                    let k = self._normal.inner(&self._offset);
-                    let c = self._normal.inner(&b_off);
-                    let d = k - c;
-                    if !self.within_vicinity(d, T::zero()) {
-                        return (false, None);
-                    }
-                    return (true, Some(b_off));
-                }
-                _ => {
-                    unimplemented!();
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Plane => {
+                //planes touch along their line of intersection, or everywhere if coincident.
+                //the normal leads from the solid side of self to the solid side of other
+                let other_plane: &Plane<T> = downcast(other)?;
+                let p = match self.intersect_plane(other_plane) {
+                    PlaneIntersection::Line(p, _) => p,
+                    PlaneIntersection::Coincident => self._offset,
+                    PlaneIntersection::Parallel(_) => return Ok(None),
+                };
+                let d = self._normal - other_plane._normal;
+                let n = if d.norm_l2() > T::epsilon() {
+                    d.normalize_l2()
+                } else {
+                    other_plane._normal
+                };
+                Ok(Some(Contact::init_point(p, n)))
+            }
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let b_off = other_point._ori;
+
+                let k = self._normal.inner(&self._offset);
+                let c = self._normal.inner(&b_off);
+                let d = k - c;
+                if !self.within_vicinity(d, T::zero()) {
+                    return Ok(None);
                 }
+                //normal points towards the side the point lies on
+                let n = if d > T::zero() {
+                    self._normal * -T::one()
+                } else {
+                    self._normal
+                };
+                Ok(Some(Contact {
+                    _point_a: b_off + self._normal * d,
+                    _point_b: b_off,
+                    _normal: n,
+                    _depth: -d.abs(),
+                    _feature: None,
+                }))
+            }
+            ShapeType::Cylinder | ShapeType::Cone | ShapeType::ConvexPolygon => {
+                Ok(self.intersect_convex(other).map(|c| c.flip()))
             }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
         }
     }
     fn get_support(&self, _v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
@@ -101,6 +235,30 @@ where
     }
 }
 
+impl<T> RayCast<T> for Plane<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    //the solid plane is the half space behind its normal
+    fn cast_ray(&self, ray: &Ray<T>, t_min: T, t_max: T, solid: bool) -> Option<RayHit<T>> {
+        let n = self._normal;
+        let dist = self.get_distance(&ray._ori);
+        let denom = n.inner(&ray._dir);
+        let (enter, leave) = if denom == T::zero() {
+            //parallel to the plane, either behind it everywhere or nowhere
+            if dist > T::zero() {
+                return None;
+            }
+            (T::neg_infinity(), T::infinity())
+        } else if denom < T::zero() {
+            (-dist / denom, T::infinity())
+        } else {
+            (T::neg_infinity(), -dist / denom)
+        };
+        cast_convex(ray, t_min, t_max, solid, (enter, n, None), (leave, n, None))
+    }
+}
+
 impl<T> Vicinity<T> for Plane<T>
 where
     T: NumAssign + Copy + Default + Float,
@@ -109,11 +267,7 @@ where
         self._vicinity = epsilon.abs();
     }
     fn within_vicinity(&self, a: T, b: T) -> bool {
-        if a + self._vicinity >= b && a - self._vicinity <= b {
-            true
-        } else {
-            false
-        }
+        a + self._vicinity >= b && a - self._vicinity <= b
     }
 }
 
@@ -124,8 +278,9 @@ fn test_intersect_plane_point_0() {
         let a = Point::init(&[2f64, 1f64, 2f64]);
         let b = Plane::init(&[1f64, 1f64, 1f64], &[0f64, 1f64, 0f64]);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, Matrix::from([[2f64, 1f64, 2f64, 1f64]]).t());
+            Some(c) => {
+                assert_eq!(c._point_a, Matrix::from([[2f64, 1f64, 2f64, 1f64]]).t());
+                assert_eq!(c._point_b, Matrix::from([[2f64, 1f64, 2f64, 1f64]]).t());
             }
             _ => panic!("unexpected result for plane point intersection"),
         }
@@ -138,8 +293,9 @@ fn test_intersect_plane_point_1() {
         let a = Point::init(&[2f64, 1f64, 2f64]);
         let b = Plane::init(&[1f64, 1f64, 1f64], &[0f64, 1f64, 0f64]);
         match b.get_intersect(&a) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, Matrix::from([[2f64, 1f64, 2f64, 1f64]]).t());
+            Some(c) => {
+                assert_eq!(c._point_a, Matrix::from([[2f64, 1f64, 2f64, 1f64]]).t());
+                assert_eq!(c._point_b, Matrix::from([[2f64, 1f64, 2f64, 1f64]]).t());
             }
             _ => panic!("unexpected result for plane point intersection"),
         }
@@ -152,7 +308,7 @@ fn test_intersect_plane_point_2() {
         let a = Point::init(&[2f64, 1.05f64, 2f64]);
         let b = Plane::init(&[1f64, 1f64, 1f64], &[0f64, 1f64, 0f64]);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for plane point no intersection"),
         }
     }
@@ -164,8 +320,157 @@ fn test_intersect_plane_point_3() {
         let a = Point::init(&[2f64, 0.99f64, 2f64]);
         let b = Plane::init(&[1f64, 1f64, 1f64], &[0f64, 1f64, 0f64]);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for plane point no intersection"),
         }
     }
 }
+#[test]
+fn test_intersect_plane_plane() {
+    let a = Plane::init(&[0f64, 0f64, 0f64], &[0f64, 1f64, 0f64]);
+    let b = Plane::init(&[1f64, 5f64, 0f64], &[1f64, 0f64, 0f64]);
+    match a.intersect_plane(&b) {
+        PlaneIntersection::Line(p, dir) => {
+            assert_matrix_approx_eq_float(&p, &Matrix::from([[1., 0., 0., 1.]]).t(), 1e-12);
+            assert_matrix_approx_eq_float(&dir, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-12);
+        }
+        x => panic!("unexpected plane plane result {:?}", x),
+    }
+
+    //oblique planes, the point lies on both
+    let b = Plane::init(&[0f64, 2f64, 0f64], &[0f64, 1f64, 1f64]);
+    match a.intersect_plane(&b) {
+        PlaneIntersection::Line(p, dir) => {
+            assert!(a.get_distance(&p).abs() < 1e-12);
+            assert!(b.get_distance(&p).abs() < 1e-12);
+            assert_matrix_approx_eq_float(&dir, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+        }
+        x => panic!("unexpected plane plane result {:?}", x),
+    }
+
+    match a.intersect_plane(&Plane::init(&[0f64, 2f64, 0f64], &[0f64, -1f64, 0f64])) {
+        PlaneIntersection::Parallel(d) => assert!((d - 2.).abs() < 1e-12),
+        x => panic!("unexpected plane plane result {:?}", x),
+    }
+    assert!(matches!(
+        a.intersect_plane(&Plane::init(&[5f64, 0f64, 3f64], &[0f64, -1f64, 0f64])),
+        PlaneIntersection::Coincident
+    ));
+
+    //contacts lie on the line, swapping the planes flips the normal
+    let c = a.get_intersect(&b).expect("plane plane unexpected miss");
+    assert!(a.get_distance(&c._point_a).abs() < 1e-12);
+    assert!(b.get_distance(&c._point_a).abs() < 1e-12);
+    assert_eq!(c._depth, 0.);
+    let s = 2f64.sqrt();
+    let n = Matrix::from([[0., 1. - 1. / s, -1. / s, 0.]])
+        .t()
+        .normalize_l2();
+    assert_matrix_approx_eq_float(&c._normal, &n, 1e-12);
+    let c = b.get_intersect(&a).expect("plane plane unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &(n * -1.), 1e-12);
+
+    assert!(a
+        .get_intersect(&Plane::init(&[0f64, 2f64, 0f64], &[0f64, 1f64, 0f64]))
+        .is_none());
+    let c = a
+        .get_intersect(&Plane::init(&[5f64, 0f64, 3f64], &[0f64, -1f64, 0f64]))
+        .expect("coincident planes unexpected miss");
+    assert_eq!(c._point_a, a._offset);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+}
+#[test]
+fn test_plane_signed_distance() {
+    use crate::rect_box::RectBox;
+    use crate::sphere::Sphere;
+    use crate::tri_prism::TriPrism;
+
+    let a = Plane::init(&[0f64, 1f64, 0f64], &[0f64, 1f64, 0f64]);
+
+    let q = a
+        .get_signed_distance(&Sphere::init(&[0f64, 3f64, 0f64], 0.5f64))
+        .expect("sphere unexpected none");
+    assert_eq!(q._side, PlaneSide::Front);
+    assert!((q._distance - 1.5).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&q._point, &Matrix::from([[0., 2.5, 0., 1.]]).t(), 1e-12);
+
+    let q = a
+        .get_signed_distance(&RectBox::init(&[2f64, -2f64, 0f64], 1f64))
+        .expect("box unexpected none");
+    assert_eq!(q._side, PlaneSide::Back);
+    assert!((q._distance + 2.).abs() < 1e-12);
+    assert!((q._point[[1, 0]] + 1.).abs() < 1e-12);
+
+    //mostly above the plane, the deepest point is the lowest one
+    let b = TriPrism::init(&[0., 0.8, 0., 1., 0.8, 0., 0., 0.8, -1.], 1.);
+    let q = a.get_signed_distance(&b).expect("prism unexpected none");
+    assert_eq!(q._side, PlaneSide::Both);
+    assert!((q._distance + 0.2).abs() < 1e-12);
+    let c = b.get_intersect(&a).expect("prism plane unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+
+    //no support mapping
+    assert!(a.get_signed_distance(&a).is_none());
+}
+#[test]
+fn test_intersect_plane_volumes() {
+    use crate::rect_box::RectBox;
+    use crate::sphere::Sphere;
+    use crate::tri_prism::TriPrism;
+
+    //the plane may come first, the normal then points to the side holding the lesser part of the shape
+    let a = Plane::init(&[0f64, 1f64, 0f64], &[0f64, 1f64, 0f64]);
+    let up = Matrix::from([[0., 1., 0., 0.]]).t();
+    let shapes: [(Box<dyn Shape<f64>>, f64, f64); 3] = [
+        (Box::new(Sphere::init(&[0., 1.3, 0.], 0.5)), 1., 0.8),
+        (Box::new(RectBox::init(&[2., 0.7, 0.], 0.5)), -1., 1.2),
+        (
+            Box::new(TriPrism::init(
+                &[0., 0.8, 0., 1., 0.8, 0., 0., 0.8, -1.],
+                1.,
+            )),
+            1.,
+            0.8,
+        ),
+    ];
+    for (b, side, deepest) in shapes.iter() {
+        let c = a.get_intersect(&**b).expect("plane shape unexpected miss");
+        assert!((c._depth - 0.2).abs() < 1e-12);
+        assert_matrix_approx_eq_float(&c._normal, &(up * *side), 1e-12);
+        assert!((c._point_b[[1, 0]] - deepest).abs() < 1e-12);
+        assert!(a.get_distance(&c._point_a).abs() < 1e-12);
+        let c = b.get_intersect(&a).expect("shape plane unexpected miss");
+        assert!((c._depth - 0.2).abs() < 1e-12);
+        assert_matrix_approx_eq_float(&c._normal, &(up * -*side), 1e-12);
+        assert!((c._point_a[[1, 0]] - deepest).abs() < 1e-12);
+    }
+    assert!(a
+        .get_intersect(&Sphere::init(&[0., 1.6, 0.], 0.5))
+        .is_none());
+}
+#[test]
+fn test_raycast_plane() {
+    let a = Plane::init(&[0., 0., 1.], &[0., 0., 1.]);
+    let r = Ray::init(&[0., 0., 5.], &[0., 0., -1.]);
+    let h = a
+        .cast_ray(&r, 0., 10., true)
+        .expect("plane cast unexpected miss");
+    assert!((h._toi - 4.).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&h._point, &Matrix::from([[0., 0., 1., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&h._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-12);
+
+    //behind the plane
+    let r = Ray::init(&[0., 0., -1.], &[0., 0., 1.]);
+    let h = a
+        .cast_ray(&r, 0., 10., true)
+        .expect("plane cast unexpected miss");
+    assert_eq!(h._toi, 0.);
+    let h = a
+        .cast_ray(&r, 0., 10., false)
+        .expect("plane cast unexpected miss");
+    assert!((h._toi - 2.).abs() < 1e-12);
+    assert!(a.cast_ray(&r, 0., 1., false).is_none());
+
+    let r = Ray::init(&[0., 0., 2.], &[1., 0., 0.]);
+    assert!(a.cast_ray(&r, 0., 10., true).is_none());
+}
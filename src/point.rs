@@ -1,4 +1,8 @@
 use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::error::CollideError;
+use crate::ray::Ray;
+use crate::ray_cast::*;
 use crate::shape::*;
 use crate::vicinity::Vicinity;
 use core::any::Any;
@@ -48,52 +52,36 @@ where
         &self._bound
     }
     // this shall test for intersection of bounding shapes first before procedding to test intersection using algorithms of higher complexity
-    fn get_intersect(&self, other: &dyn Shape<T>) -> (bool, Option<Matrix<T, 4, 1>>) {
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
         if !self.get_bound().intersect(other.get_bound()) {
-            (false, None)
-        } else {
-            match other.get_type() {
-                ShapeType::Point => {
-                    let other_point: &Point<T> = match other.as_any().downcast_ref::<Self>() {
-                        Some(b) => b,
-                        None => {
-                            panic!("cast to Point failed");
-                        }
-                    };
-                    let mut test = true;
-                    for i in 0..3 {
-                        test &= self.within_vicinity(self._ori[[i, 0]], other_point._ori[[i, 0]]);
-                    }
-                    if test {
-                        (true, Some(self._ori))
-                    } else {
-                        (false, None)
-                    }
-                }
-                ShapeType::Ray => {
-                    //see Ray for ray point intersection
-                    other.get_intersect(self)
-                }
-                ShapeType::Sphere => {
-                    //see sphere for sphere point intersection
-                    other.get_intersect(self)
-                }
-                ShapeType::Plane => {
-                    //see plane for plane point intersection
-                    other.get_intersect(self)
-                }
-                ShapeType::Box => {
-                    //see recbox for box point intersection
-                    other.get_intersect(self)
-                }
-                ShapeType::TriPrism => {
-                    //see tri prism for intersection
-                    other.get_intersect(self)
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let mut test = true;
+                for i in 0..3 {
+                    test &= self.within_vicinity(self._ori[[i, 0]], other_point._ori[[i, 0]]);
                 }
-                _ => {
-                    unimplemented!();
+                if test {
+                    Ok(Some(Contact {
+                        _point_a: self._ori,
+                        _point_b: other_point._ori,
+                        _normal: Matrix::zero(),
+                        _depth: -(other_point._ori - self._ori).norm_l2(),
+                        _feature: None,
+                    }))
+                } else {
+                    Ok(None)
                 }
             }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
         }
     }
     fn get_support(&self, _v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
@@ -101,6 +89,28 @@ where
     }
 }
 
+impl<T> RayCast<T> for Point<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    //a point has no interior, solid has no effect
+    fn cast_ray(&self, ray: &Ray<T>, t_min: T, t_max: T, _solid: bool) -> Option<RayHit<T>> {
+        let t = (self._ori - ray._ori).inner(&ray._dir);
+        if t < t_min
+            || t > t_max
+            || (ray._ori + ray._dir * t - self._ori).norm_l2() > self._vicinity
+        {
+            return None;
+        }
+        Some(RayHit {
+            _toi: t,
+            _point: self._ori,
+            _normal: Matrix::zero(),
+            _feature: None,
+        })
+    }
+}
+
 impl<T> Vicinity<T> for Point<T>
 where
     T: NumAssign + Copy + Default + Float,
@@ -120,8 +130,9 @@ fn test_intersect_point_point() {
         let a = Point::init(&[25f64, 5f64, 5f64]);
         let b = Point::init(&[25f64, 5f64, 5f64]);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert!(loc == b._ori);
+            Some(c) => {
+                assert!(c._point_a == a._ori);
+                assert!(c._point_b == b._ori);
             }
             _ => panic!("unexpected result for ray point intersection"),
         }
@@ -131,8 +142,21 @@ fn test_intersect_point_point() {
         let a = Point::init(&[25f64, 5f64, 5f64]);
         let b = Point::init(&[25.1f64, 5f64, 5f64]);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray point intersection"),
         }
     }
 }
+#[test]
+fn test_raycast_point() {
+    let a = Point::init(&[3., 0., 0.]);
+    let r = Ray::init(&[0., 0., 0.], &[1., 0., 0.]);
+    let h = a
+        .cast_ray(&r, 0., 10., true)
+        .expect("point cast unexpected miss");
+    assert!((h._toi - 3.).abs() < 1e-12);
+    assert!(a.cast_ray(&r, 0., 2., true).is_none());
+    assert!(a
+        .cast_ray(&Ray::init(&[0., 0., 0.], &[1., 1., 0.]), 0., 10., true)
+        .is_none());
+}
@@ -1,5 +1,7 @@
 use crate::bound::Bound;
 use crate::bound_aabb::AxisAlignedBBox;
+use crate::contact::Contact;
+use crate::error::CollideError;
 use crate::plane::*;
 use crate::point::*;
 use crate::ray_point_intersect;
@@ -57,36 +59,27 @@ where
         &self._bound
     }
     // this shall test for intersection of bounding shapes first before procedding to test intersection using algorithms of higher complexity
-    fn get_intersect(&self, other: &dyn Shape<T>) -> (bool, Option<Matrix<T, 4, 1>>) {
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if !self._dir.norm_l2().is_finite() {
+            return Err(CollideError::DegenerateShape(ShapeType::Ray));
+        }
         if !self.get_bound().intersect(other.get_bound()) {
-            return (false, None);
+            return Ok(None);
         }
         match other.get_type() {
             ShapeType::Ray => {
-                let other_ray: &Ray<T> = match other.as_any().downcast_ref::<Self>() {
-                    Some(b) => b,
-                    None => {
-                        panic!("cast to Ray failed");
-                    }
-                };
-                ray_ray_intersect::intersect(self, other_ray)
+                let other_ray: &Ray<T> = downcast(other)?;
+                Ok(ray_ray_intersect::intersect(self, other_ray))
             }
             ShapeType::Point => {
-                let other_point: &Point<T> = match other.as_any().downcast_ref::<Point<T>>() {
-                    Some(b) => b,
-                    None => {
-                        panic!("cast to Point failed");
-                    }
-                };
-                ray_point_intersect::intersect(self, other_point)
+                let other_point: &Point<T> = downcast(other)?;
+                Ok(ray_point_intersect::intersect(self, other_point))
             }
             ShapeType::Sphere => {
-                let other_sphere: &Sphere<T> = match other.as_any().downcast_ref::<Sphere<T>>() {
-                    Some(b) => b,
-                    None => {
-                        panic!("cast to Sphere failed");
-                    }
-                };
+                let other_sphere: &Sphere<T> = downcast(other)?;
 
                 let b_off = other_sphere._ori;
                 let b_r = other_sphere._radius;
@@ -104,13 +97,13 @@ where
                 if b > T::zero() && c > T::zero() {
                     //ray is outside of the sphere and points away from sphere
                     //thus no intersection occurs
-                    return (false, None);
+                    return Ok(None);
                 }
 
                 let d = b * b - c;
                 if d < T::zero() {
                     //ray misses sphere
-                    return (false, None);
+                    return Ok(None);
                 }
 
                 let t1 = -b - d.sqrt();
@@ -118,23 +111,17 @@ where
 
                 let t = if t1 < T::zero() {
                     t2
-                } else if t2 < T::zero() {
-                    t1
-                } else if t1 < t2 {
+                } else if t2 < T::zero() || t1 < t2 {
                     t1
                 } else {
                     t2
                 };
 
-                return (true, Some((a_dir * t) + a_off));
+                let hit = (a_dir * t) + a_off;
+                Ok(Some(Contact::init_point(hit, (b_off - hit) / b_r)))
             }
             ShapeType::Plane => {
-                let other_plane: &Plane<T> = match other.as_any().downcast_ref::<Plane<T>>() {
-                    Some(b) => b,
-                    None => {
-                        panic!("cast to Plane failed");
-                    }
-                };
+                let other_plane: &Plane<T> = downcast(other)?;
 
                 let b_off = other_plane._offset;
                 let b_nor = other_plane._normal;
@@ -152,23 +139,27 @@ where
                 if denominator == T::zero() {
                     //ray direction is colplaner to the plane
                     if constant == self._ori.inner(&b_nor) {
-                        return (true, Some(self._ori.clone()));
+                        return Ok(Some(Contact::init_point(self._ori, b_nor * -T::one())));
                     } else {
-                        return (false, None);
+                        return Ok(None);
                     }
                 } else if denominator > T::zero() {
                     //ray direction is not facing plane normal
-                    return (false, None);
+                    return Ok(None);
                 }
                 let t = numerator / denominator;
                 if t < T::zero() {
-                    return (false, None);
+                    return Ok(None);
                 }
-                return (true, Some((self._dir * t) + self._ori));
-            }
-            _ => {
-                unimplemented!();
+                Ok(Some(Contact::init_point(
+                    (self._dir * t) + self._ori,
+                    b_nor * -T::one(),
+                )))
             }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
         }
     }
     fn get_support(&self, _v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
@@ -196,7 +187,7 @@ fn test_intersect_ray_ray() {
         let b = Ray::init(&[25f64, 0f64, 0f64], &[1f64, 1f64, 1f64]);
 
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for parallel rays, no intersection"),
         }
     }
@@ -207,8 +198,8 @@ fn test_intersect_ray_ray() {
         let b = Ray::init(&[22f64, 2f64, 2f64], &[1f64, 1f64, 1f64]);
 
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert!(loc == b._ori);
+            Some(c) => {
+                assert!(c._point_a == b._ori);
             }
             _ => panic!("unexpected result for parallel rays, no intersection"),
         }
@@ -220,8 +211,8 @@ fn test_intersect_ray_ray() {
         let b = Ray::init(&[22f64, 2f64, 2f64], &[1f64, 1f64, 1f64]);
 
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert!(loc == a._ori);
+            Some(c) => {
+                assert!(c._point_a == a._ori);
             }
             _ => panic!("unexpected result for parallel rays, no intersection"),
         }
@@ -233,8 +224,8 @@ fn test_intersect_ray_ray() {
         let b = Ray::init(&[0f64, 0f64, 0f64], &[0f64, 1f64, 0f64]);
 
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert!(loc == Matrix::from([[0f64, 5f64, 0f64, 1f64]]).t());
+            Some(c) => {
+                assert!(c._point_a == Matrix::from([[0f64, 5f64, 0f64, 1f64]]).t());
             }
             _ => panic!("unexpected result for ray intersection"),
         }
@@ -246,7 +237,7 @@ fn test_intersect_ray_ray() {
         let b = Ray::init(&[5f64, 5f64, 0f64], &[1f64, 1f64, 0f64]);
 
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray intersection"),
         }
     }
@@ -259,8 +250,8 @@ fn test_intersect_ray_point() {
         let a = Ray::init(&[20f64, 0f64, 0f64], &[1f64, 1f64, 1f64]);
         let b = Point::init(&[25f64, 5f64, 5f64]);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert!(loc == b._ori);
+            Some(c) => {
+                assert!(c._point_a == b._ori);
             }
             _ => panic!("unexpected result for ray point intersection"),
         }
@@ -270,7 +261,7 @@ fn test_intersect_ray_point() {
         let a = Ray::init(&[20f64, 0f64, 0f64], &[1f64, 1f64, 1f64]);
         let b = Point::init(&[15f64, -5f64, -5f64]);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray point no intersection, point behind ray"),
         }
     }
@@ -279,7 +270,7 @@ fn test_intersect_ray_point() {
         let a = Ray::init(&[20f64, 0f64, 0f64], &[1f64, 1f64, 1f64]);
         let b = Point::init(&[25f64, 5f64, 5.1f64]);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray point no intersection"),
         }
     }
@@ -290,8 +281,8 @@ fn test_intersect_ray_point() {
         let a = Ray::init(&[20f64, 0f64, 0f64], &[1f64, 1f64, 1f64]);
         let b = Point::init(&[25f64, 5f64, 5f64]);
         match b.get_intersect(&a) {
-            (true, Some(loc)) => {
-                assert!(loc == b._ori);
+            Some(c) => {
+                assert!(c._point_a == b._ori);
             }
             _ => panic!("unexpected result for ray point intersection"),
         }
@@ -301,7 +292,7 @@ fn test_intersect_ray_point() {
         let a = Ray::init(&[20f64, 0f64, 0f64], &[1f64, 1f64, 1f64]);
         let b = Point::init(&[15f64, -5f64, -5f64]);
         match b.get_intersect(&a) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray point no intersection, point behind ray"),
         }
     }
@@ -310,8 +301,19 @@ fn test_intersect_ray_point() {
         let a = Ray::init(&[20f64, 0f64, 0f64], &[1f64, 1f64, 1f64]);
         let b = Point::init(&[25f64, 5f64, 5.1f64]);
         match b.get_intersect(&a) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray point no intersection"),
         }
     }
 }
+
+#[test]
+fn test_try_intersect_ray_degenerate() {
+    use crate::error::CollideError;
+    let a = Ray::init(&[20f64, 0f64, 0f64], &[0f64, 0f64, 0f64]);
+    let b = Point::init(&[25f64, 5f64, 5f64]);
+    assert_eq!(
+        a.try_intersect(&b).err(),
+        Some(CollideError::DegenerateShape(ShapeType::Ray))
+    );
+}
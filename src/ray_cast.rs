@@ -0,0 +1,97 @@
+use crate::ray::Ray;
+use lightmatrix::matrix::*;
+use num_traits::{Float, NumAssign};
+
+///result of casting a ray against a shape
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit<T: NumAssign + Copy + Default + Float> {
+    ///time of impact, the hit point is ray origin + ray direction * toi
+    pub _toi: T,
+    ///hit point on the shape
+    pub _point: Matrix<T, 4, 1>,
+    ///unit surface normal pointing out of the shape, zero if undefined or the ray starts inside of a solid shape
+    pub _normal: Matrix<T, 4, 1>,
+    ///optional sub feature of the shape that is hit, eg: facet index
+    pub _feature: Option<usize>,
+}
+
+pub trait RayCast<T: NumAssign + Copy + Default + Float> {
+    //returns the first hit of the ray with toi in [t_min, t_max]
+    //a solid shape containing the ray at t_min is hit there, a hollow shape is only hit on its surface,
+    //eg: where the ray leaves the shape
+    fn cast_ray(&self, ray: &Ray<T>, t_min: T, t_max: T, solid: bool) -> Option<RayHit<T>>;
+}
+
+///toi, outward unit normal and feature where a ray crosses the surface of a shape
+pub(crate) type Crossing<T> = (T, Matrix<T, 4, 1>, Option<usize>);
+
+///first hit of a ray on a convex shape given the crossings where it enters and leaves the shape
+pub(crate) fn cast_convex<T>(
+    ray: &Ray<T>,
+    t_min: T,
+    t_max: T,
+    solid: bool,
+    enter: Crossing<T>,
+    leave: Crossing<T>,
+) -> Option<RayHit<T>>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    let hit = |(toi, normal, feature): Crossing<T>| RayHit {
+        _toi: toi,
+        _point: ray._ori + ray._dir * toi,
+        _normal: normal,
+        _feature: feature,
+    };
+    let in_range = |t: T| t.is_finite() && t >= t_min && t <= t_max;
+    if t_min > t_max || enter.0 > leave.0 {
+        None
+    } else if in_range(enter.0) {
+        Some(hit(enter))
+    } else if enter.0 < t_min && leave.0 >= t_min {
+        //ray is inside of the shape at t_min
+        if solid {
+            Some(hit((t_min, Matrix::zero(), None)))
+        } else if in_range(leave.0) {
+            Some(hit(leave))
+        } else {
+            None
+        }
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+fn cast_interval(solid: bool, t_min: f64, t_max: f64) -> Option<RayHit<f64>> {
+    let r = Ray::init(&[0., 0., 0.], &[1., 0., 0.]);
+    let n = Matrix::from([[1., 0., 0., 0.]]).t();
+    cast_convex(
+        &r,
+        t_min,
+        t_max,
+        solid,
+        (1., n * -1., Some(0)),
+        (3., n, Some(1)),
+    )
+}
+
+#[test]
+fn test_cast_convex() {
+    let h = cast_interval(true, 0., 10.).expect("cast unexpected miss");
+    assert_eq!(h._toi, 1.);
+    assert_eq!(h._feature, Some(0));
+    assert_eq!(h._point, Matrix::from([[1., 0., 0., 1.]]).t());
+
+    //starting inside
+    let h = cast_interval(true, 2., 10.).expect("cast unexpected miss");
+    assert_eq!(h._toi, 2.);
+    assert_eq!(h._normal, Matrix::zero());
+    let h = cast_interval(false, 2., 10.).expect("cast unexpected miss");
+    assert_eq!(h._toi, 3.);
+    assert_eq!(h._feature, Some(1));
+
+    assert!(cast_interval(false, 2., 2.5).is_none());
+    assert!(cast_interval(true, 0., 0.5).is_none());
+    assert!(cast_interval(true, 4., 10.).is_none());
+}
@@ -1,10 +1,11 @@
+use crate::contact::Contact;
 use crate::point::*;
 use crate::ray::*;
 use crate::vicinity::*;
 use lightmatrix::matrix::*;
 use num_traits::{Float, NumAssign};
 
-pub(crate) fn intersect<T>(a: &Ray<T>, b: &Point<T>) -> (bool, Option<Matrix<T, 4, 1>>)
+pub(crate) fn intersect<T>(a: &Ray<T>, b: &Point<T>) -> Option<Contact<T>>
 where
     T: NumAssign + Copy + Default + Float,
 {
@@ -16,11 +17,17 @@ where
     //t = (b_off - a_off) / a_dir
     let t = (b_off - a_off) / a_dir;
     if !a.within_vicinity(t[[0, 0]], t[[1, 0]]) || !a.within_vicinity(t[[1, 0]], t[[2, 0]]) {
-        (false, None)
+        None
     } else if t[[0, 0]] >= T::zero() {
-        (true, Some((a_dir * t[[0, 0]]) + a_off))
+        Some(Contact {
+            _point_a: (a_dir * t[[0, 0]]) + a_off,
+            _point_b: b_off,
+            _normal: Matrix::zero(),
+            _depth: T::zero(),
+            _feature: None,
+        })
     } else {
         //the point is behind the ray origin and direction
-        (false, None)
+        None
     }
 }
@@ -1,9 +1,10 @@
+use crate::contact::Contact;
 use crate::ray::*;
 use crate::vicinity::Vicinity;
 use lightmatrix::matrix::*;
 use num_traits::{Float, NumAssign};
 
-pub(crate) fn intersect<T>(ray_a: &Ray<T>, ray_b: &Ray<T>) -> (bool, Option<Matrix<T, 4, 1>>)
+pub(crate) fn intersect<T>(ray_a: &Ray<T>, ray_b: &Ray<T>) -> Option<Contact<T>>
 where
     T: NumAssign + Copy + Default + Float,
 {
@@ -19,7 +20,7 @@ where
     let dot_v_c = v.inner(&c);
     if !ray_a.within_vicinity(dot_v_c, T::zero()) {
         //they are not in the same place, so no intersection occurs
-        return (false, None);
+        return None;
     }
     //test for colinearity
     let d = b_off - a_off;
@@ -33,7 +34,7 @@ where
         if !ray_a.within_vicinity(triangle_area, T::zero()) {
             //no overlap
             // println!( "parallel but non-overlapping lines" );
-            (false, None)
+            None
         } else {
             //lines are colinear
             let direction = if d.inner(&a_dir) < T::zero() {
@@ -45,10 +46,13 @@ where
             // println!( "colinear lines, distance: {}", distance );
             if distance < T::zero() {
                 //intersection at offset of ray a, so clamp t to 0
-                (true, Some(a_off))
+                Some(Contact::init_point(a_off, Matrix::zero()))
             } else {
                 //intersection at offset of ray b
-                (true, Some(ray_a._dir * distance + ray_a._ori))
+                Some(Contact::init_point(
+                    ray_a._dir * distance + ray_a._ori,
+                    Matrix::zero(),
+                ))
             }
         }
     } else {
@@ -56,9 +60,12 @@ where
         let numerator = d.cross(&b_dir);
         let t = numerator.norm_l2() / v.norm_l2();
         if t < T::zero() {
-            (false, None)
+            None
         } else {
-            (true, Some(ray_a._dir * t + ray_a._ori))
+            Some(Contact::init_point(
+                ray_a._dir * t + ray_a._ori,
+                Matrix::zero(),
+            ))
         }
     }
 }
@@ -1,6 +1,13 @@
 use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::cuboid::*;
+use crate::error::CollideError;
+use crate::plane::*;
 use crate::point::*;
+use crate::ray::*;
+use crate::ray_cast::*;
 use crate::shape::*;
+use crate::sphere::*;
 use crate::vicinity::Vicinity;
 use core::any::Any;
 use num_traits::{Float, NumAssign};
@@ -32,6 +39,24 @@ where
             _vicinity: T::epsilon(),
         }
     }
+
+    ///the 8 corners of the box
+    pub fn get_corners(&self) -> [Matrix<T, 4, 1>; 8] {
+        box_corners(&self._ori, &[self._size; 3])
+    }
+
+    ///point on or inside of the box closest to p
+    pub fn get_closest_point(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        box_closest_point(&self._ori, &[self._size; 3], p)
+    }
+
+    ///entry and exit parameters of ray ori + dir * t through the box, clipped to t >= 0
+    pub fn intersect_ray(&self, ori: &Matrix<T, 4, 1>, dir: &Matrix<T, 4, 1>) -> Option<(T, T)> {
+        self._bound.intersect_ray(
+            &[ori[[0, 0]], ori[[1, 0]], ori[[2, 0]]],
+            &[dir[[0, 0]], dir[[1, 0]], dir[[2, 0]]],
+        )
+    }
 }
 
 impl<T> AnyBase for RectBox<T>
@@ -54,40 +79,68 @@ where
         &self._bound
     }
     // this shall test for intersection of bounding shapes first before procedding to test intersection using algorithms of higher complexity
-    fn get_intersect(&self, other: &dyn Shape<T>) -> (bool, Option<Matrix<T, 4, 1>>) {
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._size.is_nan() || self._size < T::zero() {
+            return Err(CollideError::DegenerateShape(ShapeType::Box));
+        }
         if !self.get_bound().intersect(other.get_bound()) {
-            return (false, None);
-        } else {
-            match other.get_type() {
-                ShapeType::Point => {
-                    //covered by bbox test
-                    let other_point: &Point<T> = match other.as_any().downcast_ref::<Point<T>>() {
-                        Some(b) => b,
-                        None => {
-                            panic!("cast to Point failed");
-                        }
-                    };
-                    let b_off = other_point._ori;
-                    return (true, Some(b_off));
-                }
-                _ => {
-                    unimplemented!();
-                }
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                Ok(contact_box_point(
+                    &self._ori,
+                    &[self._size; 3],
+                    &other_point._ori,
+                ))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                Ok(contact_box_ray(&self._ori, &[self._size; 3], other_ray))
             }
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
+                Ok(contact_box_sphere(
+                    &self._ori,
+                    &[self._size; 3],
+                    other_sphere,
+                ))
+            }
+            ShapeType::Box => {
+                let other_box: &RectBox<T> = downcast(other)?;
+                Ok(intersect_aabb_aabb(
+                    &self._ori,
+                    &[self._size; 3],
+                    &other_box._ori,
+                    &[other_box._size; 3],
+                ))
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                Ok(contact_box_plane(&self._ori, &[self._size; 3], other_plane))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
         }
     }
     fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
         if v.norm_l2() != T::zero() {
             //get a furthest point in the given direction v
             let points = [
-                Matrix::from([[self._size, self._size, self._size, T::one()]]).t(),
-                Matrix::from([[-self._size, self._size, self._size, T::one()]]).t(),
-                Matrix::from([[self._size, -self._size, self._size, T::one()]]).t(),
-                Matrix::from([[-self._size, -self._size, self._size, T::one()]]).t(),
-                Matrix::from([[self._size, self._size, -self._size, T::one()]]).t(),
-                Matrix::from([[-self._size, self._size, -self._size, T::one()]]).t(),
-                Matrix::from([[self._size, -self._size, -self._size, T::one()]]).t(),
-                Matrix::from([[-self._size, -self._size, -self._size, T::one()]]).t(),
+                Matrix::from([[self._size, self._size, self._size, T::zero()]]).t(),
+                Matrix::from([[-self._size, self._size, self._size, T::zero()]]).t(),
+                Matrix::from([[self._size, -self._size, self._size, T::zero()]]).t(),
+                Matrix::from([[-self._size, -self._size, self._size, T::zero()]]).t(),
+                Matrix::from([[self._size, self._size, -self._size, T::zero()]]).t(),
+                Matrix::from([[-self._size, self._size, -self._size, T::zero()]]).t(),
+                Matrix::from([[self._size, -self._size, -self._size, T::zero()]]).t(),
+                Matrix::from([[-self._size, -self._size, -self._size, T::zero()]]).t(),
             ];
 
             let furthest = points
@@ -105,6 +158,28 @@ where
     }
 }
 
+impl<T> RayCast<T> for RectBox<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn cast_ray(&self, ray: &Ray<T>, t_min: T, t_max: T, solid: bool) -> Option<RayHit<T>> {
+        let (rel, dir) = (ray._ori - self._ori, ray._dir);
+        let (near, far) = clip_slab(
+            &[rel[[0, 0]], rel[[1, 0]], rel[[2, 0]]],
+            &[dir[[0, 0]], dir[[1, 0]], dir[[2, 0]]],
+            &[self._size; 3],
+        )?;
+        let crossing = |(t, axis, sign): (T, usize, T)| {
+            (
+                t,
+                axis_unit(axis, sign),
+                Some(axis * 2 + (sign > T::zero()) as usize),
+            )
+        };
+        cast_convex(ray, t_min, t_max, solid, crossing(near), crossing(far))
+    }
+}
+
 impl<T> Vicinity<T> for RectBox<T>
 where
     T: NumAssign + Copy + Default + Float,
@@ -113,11 +188,7 @@ where
         self._vicinity = epsilon.abs();
     }
     fn within_vicinity(&self, a: T, b: T) -> bool {
-        if a + self._vicinity >= b && a - self._vicinity <= b {
-            true
-        } else {
-            false
-        }
+        a + self._vicinity >= b && a - self._vicinity <= b
     }
 }
 
@@ -128,11 +199,9 @@ fn test_intersect_rectbox_point_0() {
         let a = Point::init(&[-9.9, 9.9, 9.9]);
         let b = RectBox::init(&[0., 0., 0.], 10.);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                println!("{:?}", loc);
-                println!("{:?}", a._ori);
-                assert_eq!(loc, a._ori);
-                //     assert!(loc.is_equal(&a._ori, 0.0001f64).unwrap());
+            Some(c) => {
+                assert_eq!(c._point_a, a._ori);
+                assert!((c._depth - 0.1).abs() < 1e-12);
             }
             _ => panic!("unexpected result for ray point intersection"),
         }
@@ -145,8 +214,118 @@ fn test_intersect_rectbox_point_1() {
         let a = Point::init(&[-9.9, 9.9, -10.1]);
         let b = RectBox::init(&[0., 0., 0.], 10.);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray point intersection"),
         }
     }
 }
+#[test]
+fn test_intersect_rectbox_plane() {
+    let a = RectBox::init(&[0., 0., 0.], 1.);
+    let b = Plane::init(&[0., 0.5, 0.], &[0., 1., 0.]);
+    let c = a.get_intersect(&b).expect("rectbox plane unexpected miss");
+    assert!((c._depth - 0.5).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 1., 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_b, &Matrix::from([[0., 0.5, 0., 1.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("plane rectbox unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+
+    //tilted plane cuts off the corner
+    let b = Plane::init(&[0.5, 0.5, 0.], &[1., 1., 0.]);
+    let c = a.get_intersect(&b).expect("rectbox plane unexpected miss");
+    assert!((c._depth - 0.5f64.sqrt()).abs() < 1e-12);
+    assert_eq!(c._point_a[[0, 0]], 1.);
+    assert_eq!(c._point_a[[1, 0]], 1.);
+
+    assert!(a
+        .get_intersect(&Plane::init(&[0., -1.1, 0.], &[0., 1., 0.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_rectbox_sphere() {
+    let a = RectBox::init(&[0., 0., 0.], 1.);
+    let b = Sphere::init(&[2., 2., 0.], 1.5);
+    let c = a.get_intersect(&b).expect("rectbox sphere unexpected miss");
+    assert!((c._depth - (1.5 - 2f64.sqrt())).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[1., 1., 0., 1.]]).t(), 1e-12);
+    let n = Matrix::from([[1., 1., 0., 0.]]).t().normalize_l2();
+    assert_matrix_approx_eq_float(&c._normal, &n, 1e-12);
+    let c = b.get_intersect(&a).expect("sphere rectbox unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &(n * -1.), 1e-12);
+
+    //center inside of the box
+    let b = Sphere::init(&[0., 0., 0.8], 0.5);
+    let c = a.get_intersect(&b).expect("rectbox sphere unexpected miss");
+    assert!((c._depth - 0.7).abs() < 1e-12);
+    assert_eq!(c._feature, Some(5));
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-12);
+
+    //overlaps the bound at the corner only
+    assert!(a.get_intersect(&Sphere::init(&[2., 2., 0.], 1.2)).is_none());
+}
+#[test]
+fn test_intersect_rectbox_ray() {
+    let a = RectBox::init(&[0., 0., 0.], 1.);
+    let ori = Matrix::from([[-3., 0.5, 0., 1.]]).t();
+    let dir = Matrix::from([[1., 0., 0., 0.]]).t();
+    assert_eq!(a.intersect_ray(&ori, &dir), Some((2., 4.)));
+
+    let b = Ray::init(&[-3., 0.5, 0.], &[1., 0., 0.]);
+    let c = a.get_intersect(&b).expect("rectbox ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[-1., 0.5, 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+    assert_eq!(c._feature, Some(0));
+    let c = b.get_intersect(&a).expect("ray rectbox unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+
+    //starting inside hits on its way out
+    let b = Ray::init(&[0., 0., 0.], &[0., 1., 0.]);
+    let c = a.get_intersect(&b).expect("rectbox ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., 1., 0., 1.]]).t(), 1e-12);
+
+    assert!(a
+        .get_intersect(&Ray::init(&[-3., 1.5, 0.], &[1., 0., 0.]))
+        .is_none());
+    assert!(a
+        .get_intersect(&Ray::init(&[-3., 0.5, 0.], &[1., 1., 0.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_rectbox_rectbox() {
+    let a = RectBox::init(&[0., 0., 0.], 1.);
+    let b = RectBox::init(&[1.5, 0.2, 0.], 0.6);
+    let c = a
+        .get_intersect(&b)
+        .expect("rectbox rectbox unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+    assert!((c._point_a[[0, 0]] - 1.).abs() < 1e-12);
+    assert!((c._point_b[[0, 0]] - 0.9).abs() < 1e-12);
+    let c = b
+        .get_intersect(&a)
+        .expect("rectbox rectbox unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+
+    assert!(a
+        .get_intersect(&RectBox::init(&[1.7, 0.2, 0.], 0.6))
+        .is_none());
+}
+#[test]
+fn test_raycast_rectbox() {
+    let a = RectBox::init(&[5., 0., 0.], 1.);
+    let r = Ray::init(&[0., 0.5, 0.], &[1., 0., 0.]);
+    let h = a
+        .cast_ray(&r, 0., 10., true)
+        .expect("rectbox cast unexpected miss");
+    assert!((h._toi - 4.).abs() < 1e-12);
+    assert_eq!(h._feature, Some(0));
+    assert_matrix_approx_eq_float(&h._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+
+    let h = a
+        .cast_ray(&r, 5., 10., false)
+        .expect("rectbox cast unexpected miss");
+    assert!((h._toi - 6.).abs() < 1e-12);
+    assert_eq!(h._feature, Some(1));
+    assert!(a.cast_ray(&r, 7., 10., true).is_none());
+}
@@ -1,4 +1,7 @@
 use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::dispatch;
+use crate::error::CollideError;
 use crate::vicinity::Vicinity;
 use core::any::Any;
 use lightmatrix::matrix::*;
@@ -8,7 +11,7 @@ pub trait AnyBase {
     fn as_any(&self) -> &dyn Any;
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ShapeType {
     //primitive shapes
     Point,
@@ -20,19 +23,73 @@ pub enum ShapeType {
     Rect,
     TriPrism, //5 facets, 2 triangles, 3 rectangles
     Line,
-    //todo
+    OrientedBox,
     Frustum,
+    Capsule,
+    Cylinder,
+    Cone,
+    Ellipsoid,
+    ConvexPolygon,
+    ConvexHull,
+    TriMesh,
+    HeightField,
     Complex, //custom shapes
 }
 
-pub trait Shape<T>: Vicinity<T> + AnyBase
+///upcast to a shape trait object, implemented for every sized shape
+pub trait AsShape<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_shape(&self) -> &dyn Shape<T>;
+}
+
+impl<T, S> AsShape<T> for S
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+    S: Shape<T>,
+{
+    fn as_shape(&self) -> &dyn Shape<T> {
+        self
+    }
+}
+
+pub trait Shape<T>: Vicinity<T> + AnyBase + AsShape<T>
 where
     T: NumAssign + Copy + Default + Float + 'static,
 {
     fn get_type(&self) -> ShapeType;
+    //bounding volume used for early rejection, shapes may expose either an AxisAlignedBBox or a BoundingSphere
     fn get_bound(&self) -> &dyn Bound<T>;
-    //optionally returns a location of intersection of bounding shapes, preferrably closest of such locations
-    fn get_intersect(&self, other: &dyn Shape<T>) -> (bool, Option<Matrix<T, 4, 1>>);
+    //returns the contact of the intersection, preferrably the closest of such locations, with self as shape a
+    //panics if the pair is not supported, see try_intersect for a fallible version
+    fn get_intersect(&self, other: &dyn Shape<T>) -> Option<Contact<T>> {
+        match self.try_intersect(other) {
+            Ok(o) => o,
+            Err(e) => panic!("{}", e),
+        }
+    }
+    //pairs are handled in either order, with a gjk fallback for convex shapes, see dispatch::try_intersect
+    fn try_intersect(&self, other: &dyn Shape<T>) -> Result<Option<Contact<T>>, CollideError> {
+        dispatch::try_intersect(self.as_shape(), other)
+    }
+    //only pairs with an algorithm of self's type are handled, others give UnsupportedPair
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError>;
     //required for gjk intersection test
     fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>>;
 }
+
+///casts other to its concrete shape implementation
+pub(crate) fn downcast<S, T>(other: &dyn Shape<T>) -> Result<&S, CollideError>
+where
+    S: 'static,
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    other
+        .as_any()
+        .downcast_ref::<S>()
+        .ok_or_else(|| CollideError::DowncastFailed(other.get_type()))
+}
@@ -1,8 +1,12 @@
 use crate::bound::Bound;
 use crate::bound_aabb::AxisAlignedBBox;
+use crate::bound_sphere::BoundingSphere;
+use crate::contact::Contact;
+use crate::error::CollideError;
+use crate::plane::*;
 use crate::point::*;
-#[cfg(test)]
 use crate::ray::*;
+use crate::ray_cast::*;
 use crate::shape::*;
 use crate::vicinity::Vicinity;
 use core::any::Any;
@@ -14,6 +18,8 @@ pub struct Sphere<T: NumAssign + Copy + Default + Float> {
     pub _ori: Matrix<T, 4, 1>,
     pub _radius: T,
     pub _bound: AxisAlignedBBox<T>,
+    ///tighter bound used for early rejection
+    pub _bound_sphere: BoundingSphere<T>,
     pub _vicinity: T,
 }
 
@@ -27,6 +33,7 @@ where
             _ori: Matrix::from([[origin[0], origin[1], origin[2], T::one()]]).t(),
             _radius: r,
             _bound: AxisAlignedBBox::new(ShapeType::Sphere, &[&origin[0..3], &[r]].concat()),
+            _bound_sphere: BoundingSphere::new(ShapeType::Sphere, &[&origin[0..3], &[r]].concat()),
             _vicinity: T::from(1e-7).unwrap(),
         }
     }
@@ -49,96 +56,93 @@ where
         ShapeType::Sphere
     }
     fn get_bound(&self) -> &dyn Bound<T> {
-        &self._bound
+        &self._bound_sphere
     }
     // this shall test for intersection of bounding shapes first before procedding to test intersection using algorithms of higher complexity
-    fn get_intersect(&self, other: &dyn Shape<T>) -> (bool, Option<Matrix<T, 4, 1>>) {
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._radius.is_nan() || self._radius < T::zero() {
+            return Err(CollideError::DegenerateShape(ShapeType::Sphere));
+        }
         if !self.get_bound().intersect(other.get_bound()) {
-            return (false, None);
-        } else {
-            match other.get_type() {
-                ShapeType::Sphere => {
-                    let other_sphere: &Sphere<T> = match other.as_any().downcast_ref::<Self>() {
-                        Some(b) => b,
-                        None => {
-                            panic!("cast to Sphere failed");
-                        }
-                    };
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
 
-                    let b_off = other_sphere._ori;
-                    let a_r = self._radius;
-                    let b_r = other_sphere._radius;
+                let b_off = other_sphere._ori;
+                let a_r = self._radius;
+                let b_r = other_sphere._radius;
 
-                    let a_off = self._ori;
-                    let c = b_off - a_off;
-                    let d = c.norm_l2();
-                    if d > b_r + a_r {
-                        return (false, None);
+                let a_off = self._ori;
+                let c = b_off - a_off;
+                let d = c.norm_l2();
+                if d > b_r + a_r {
+                    Ok(None)
+                } else {
+                    //pick an arbitrary normal for concentric spheres
+                    let n = if d == T::zero() {
+                        Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t()
                     } else {
-                        //calculate a mid point average
-                        let f = a_r / (a_r + b_r);
-                        let g = c * f;
-                        return (true, Some(a_off + g));
-                    }
-                }
-                ShapeType::Ray => {
-                    //see Ray for ray sphere intersection
-                    return other.get_intersect(self);
-                }
-                ShapeType::Point => {
-                    let other_point: &Point<T> = match other.as_any().downcast_ref::<Point<T>>() {
-                        Some(b) => b,
-                        None => {
-                            panic!("cast to Point failed");
-                        }
+                        c / d
                     };
-                    let b_off = other_point._ori;
-                    let d = b_off - self._ori;
-                    for i in 0..3 {
-                        if d[[i, 0]] > self._radius {
-                            return (false, None);
-                        }
-                    }
-                    return (true, Some(b_off));
+                    Ok(Some(Contact {
+                        _point_a: a_off + n * a_r,
+                        _point_b: b_off - n * b_r,
+                        _normal: n,
+                        _depth: a_r + b_r - d,
+                        _feature: None,
+                    }))
                 }
-                // ShapeType::Plane => {
-                //     let other_shape_data = other.get_shape_data();
-                //     let b_off = Mat3x1 {
-                //         _val: [
-                //             other_shape_data[0],
-                //             other_shape_data[1],
-                //             other_shape_data[2],
-                //         ],
-                //     };
-                //     let b_nor = Mat3x1 {
-                //         _val: [
-                //             other_shape_data[3],
-                //             other_shape_data[4],
-                //             other_shape_data[5],
-                //         ],
-                //     };
-                //     //x = -plane_normal * t + sphere_center
-                //     //dot( plane_normal, x ) = dot( plane_normal, plane_offset ) = k
-                //     //substitution:
-                //     //dot( plane_normal, -plane_normal * t + sphere_center ) = k
-                //     //-t + dot( plane_normal, sphere_center ) = k
-                //     //t = dot( plane_normal, sphere_center ) - k
-
-                //     let k = b_nor.dot(&b_off).unwrap();
-                //     let t = b_nor.dot(&self._ori).unwrap() - k;
-                //     if t > self._radius {
-                //         return (false, None);
-                //     } else {
-                //         return (
-                //             true,
-                //             Some(b_nor.scale(-t).unwrap().plus(&self._ori).unwrap()),
-                //         );
-                //     }
-                // }
-                _ => {
-                    unimplemented!();
+            }
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let b_off = other_point._ori;
+                let c = b_off - self._ori;
+                let d = c.norm_l2();
+                if d > self._radius {
+                    return Ok(None);
+                }
+                //push a point at the center out through an arbitrary direction
+                let n = if d == T::zero() {
+                    Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t()
+                } else {
+                    c / d
+                };
+                Ok(Some(Contact {
+                    _point_a: self._ori + n * self._radius,
+                    _point_b: b_off,
+                    _normal: n,
+                    _depth: self._radius - d,
+                    _feature: None,
+                }))
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                let n = other_plane._normal;
+                let s = other_plane.get_distance(&self._ori);
+                if s.abs() > self._radius {
+                    return Ok(None);
                 }
+                //the plane is pushed towards the side holding the lesser part of the sphere
+                let normal = if s >= T::zero() { n * -T::one() } else { n };
+                let deepest = self._ori + normal * self._radius;
+                let depth = self._radius - s.abs();
+                Ok(Some(Contact {
+                    _point_a: deepest,
+                    _point_b: deepest - normal * depth,
+                    _normal: normal,
+                    _depth: depth,
+                    _feature: None,
+                }))
             }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
         }
     }
     fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
@@ -152,6 +156,32 @@ where
     }
 }
 
+impl<T> RayCast<T> for Sphere<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn cast_ray(&self, ray: &Ray<T>, t_min: T, t_max: T, solid: bool) -> Option<RayHit<T>> {
+        //roots of |ray origin + ray direction * t - center| = radius
+        let m = ray._ori - self._ori;
+        let b = m.inner(&ray._dir);
+        let c = m.inner(&m) - self._radius * self._radius;
+        let d = b * b - c;
+        if d < T::zero() {
+            return None;
+        }
+        let (t0, t1) = (-b - d.sqrt(), -b + d.sqrt());
+        let normal = |t: T| (ray._ori + ray._dir * t - self._ori) / self._radius;
+        cast_convex(
+            ray,
+            t_min,
+            t_max,
+            solid,
+            (t0, normal(t0), None),
+            (t1, normal(t1), None),
+        )
+    }
+}
+
 impl<T> Vicinity<T> for Sphere<T>
 where
     T: NumAssign + Copy + Default + Float,
@@ -160,14 +190,19 @@ where
         self._vicinity = epsilon.abs();
     }
     fn within_vicinity(&self, a: T, b: T) -> bool {
-        if a + self._vicinity >= b && a - self._vicinity <= b {
-            true
-        } else {
-            false
-        }
+        a + self._vicinity >= b && a - self._vicinity <= b
     }
 }
 
+#[test]
+fn test_sphere_bounds() {
+    use crate::bound::BoundType;
+    let a = Sphere::init(&[1f64, 2f64, 3f64], 0.5f64);
+    assert_eq!(a._bound.bound_lower, [0.5f64, 1.5f64, 2.5f64]);
+    assert_eq!(a._bound.bound_upper, [1.5f64, 2.5f64, 3.5f64]);
+    assert_eq!(a.get_bound().get_type(), BoundType::Sphere);
+    assert_eq!(a._bound_sphere.radius, 0.5f64);
+}
 #[test]
 fn test_intersect_sphere_sphere_0() {
     //Sphere Sphere intersection
@@ -175,8 +210,11 @@ fn test_intersect_sphere_sphere_0() {
         let a = Sphere::init(&[10f64, 0f64, 0f64], 5f64);
         let b = Sphere::init(&[20f64, 0f64, 0f64], 5f64);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, Matrix::from([[15f64, 0f64, 0f64, 1f64]]).t());
+            Some(c) => {
+                assert_eq!(c._point_a, Matrix::from([[15f64, 0f64, 0f64, 1f64]]).t());
+                assert_eq!(c._point_b, Matrix::from([[15f64, 0f64, 0f64, 1f64]]).t());
+                assert_eq!(c._normal, Matrix::from([[1f64, 0f64, 0f64, 0f64]]).t());
+                assert_eq!(c._depth, 0f64);
             }
             _ => panic!("unexpected result for ray sphere intersection"),
         }
@@ -189,9 +227,23 @@ fn test_intersect_sphere_sphere_1() {
         let a = Sphere::init(&[10f64, 0f64, 0f64], 5f64);
         let b = Sphere::init(&[13f64, 4f64, 0f64], 5f64);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                println!("loc: {:?}", loc);
-                assert_eq!(loc, Matrix::from([[11.5f64, 2f64, 0f64, 1f64]]).t());
+            Some(c) => {
+                assert_matrix_approx_eq_float(
+                    &c._point_a,
+                    &Matrix::from([[13f64, 4f64, 0f64, 1f64]]).t(),
+                    1e-12,
+                );
+                assert_matrix_approx_eq_float(
+                    &c._point_b,
+                    &Matrix::from([[10f64, 0f64, 0f64, 1f64]]).t(),
+                    1e-12,
+                );
+                assert_matrix_approx_eq_float(
+                    &c._normal,
+                    &Matrix::from([[0.6f64, 0.8f64, 0f64, 0f64]]).t(),
+                    1e-12,
+                );
+                assert_eq!(c._depth, 5f64);
             }
             _ => panic!("unexpected result for ray sphere intersection"),
         }
@@ -204,7 +256,7 @@ fn test_intersect_sphere_sphere_2() {
         let a = Sphere::init(&[10f64, 0f64, 0f64], 5f64);
         let b = Sphere::init(&[20f64, 0.1f64, 0f64], 5f64);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray sphere intersection"),
         }
     }
@@ -217,8 +269,9 @@ fn test_intersect_sphere_point_0() {
         let a = Sphere::init(&[10f64, 0f64, 0f64], 5f64);
         let b = Point::init(&[8f64, 2f64, 3f64]);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, Matrix::from([[8f64, 2f64, 3f64, 1f64]]).t());
+            Some(c) => {
+                assert_eq!(c._point_b, Matrix::from([[8f64, 2f64, 3f64, 1f64]]).t());
+                assert!((c._depth - (5f64 - 17f64.sqrt())).abs() < 1e-12);
             }
             _ => panic!("unexpected result for ray point intersection"),
         }
@@ -231,8 +284,11 @@ fn test_intersect_sphere_point_1() {
         let a = Sphere::init(&[10f64, 0f64, 0f64], 5f64);
         let b = Point::init(&[10f64, 5f64, 0f64]);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, Matrix::from([[10f64, 5f64, 0f64, 1f64]]).t());
+            Some(c) => {
+                assert_eq!(c._point_a, Matrix::from([[10f64, 5f64, 0f64, 1f64]]).t());
+                assert_eq!(c._point_b, Matrix::from([[10f64, 5f64, 0f64, 1f64]]).t());
+                assert_eq!(c._normal, Matrix::from([[0f64, 1f64, 0f64, 0f64]]).t());
+                assert_eq!(c._depth, 0f64);
             }
             _ => panic!("unexpected result for ray point intersection"),
         }
@@ -245,7 +301,7 @@ fn test_intersect_sphere_point_2() {
         let a = Sphere::init(&[10f64, 0f64, 0f64], 5f64);
         let b = Point::init(&[0f64, 5.1f64, 0f64]);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray point no intersection"),
         }
     }
@@ -258,8 +314,9 @@ fn test_intersect_ray_sphere_0() {
         let a = Ray::init(&[5f64, 0f64, 0f64], &[1f64, 0f64, 0f64]);
         let b = Sphere::init(&[20f64, 0f64, 0f64], 5f64);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, Matrix::from([[15f64, 0f64, 0f64, 1f64]]).t());
+            Some(c) => {
+                assert_eq!(c._point_a, Matrix::from([[15f64, 0f64, 0f64, 1f64]]).t());
+                assert_eq!(c._normal, Matrix::from([[1f64, 0f64, 0f64, 0f64]]).t());
             }
             _ => panic!("unexpected result for ray sphere intersection"),
         }
@@ -272,7 +329,7 @@ fn test_intersect_ray_sphere_1() {
         let a = Ray::init(&[5f64, 0f64, 0f64], &[-1f64, 0f64, 0f64]);
         let b = Sphere::init(&[20f64, 0f64, 0f64], 5f64);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray sphere no intersection"),
         }
     }
@@ -284,8 +341,9 @@ fn test_intersect_ray_sphere_2() {
         let a = Ray::init(&[30f64, 10f64, 10f64], &[-1f64, 0f64, 0f64]);
         let b = Sphere::init(&[20f64, 10f64, 10f64], 5f64);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, Matrix::from([[25f64, 10f64, 10f64, 1f64]]).t());
+            Some(c) => {
+                assert_eq!(c._point_a, Matrix::from([[25f64, 10f64, 10f64, 1f64]]).t());
+                assert_eq!(c._normal, Matrix::from([[-1f64, 0f64, 0f64, 0f64]]).t());
             }
             _ => panic!("unexpected result for ray sphere intersection"),
         }
@@ -298,12 +356,11 @@ fn test_intersect_ray_sphere_3() {
         let a = Ray::init(&[30f64, 10f64, 10f64], &[-1f64, -1f64, -1f64]);
         let b = Sphere::init(&[20f64, 0f64, 0f64], 5f64);
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
+            Some(contact) => {
                 let c = (25f64 / 3f64).sqrt();
-                println!("loc: {:?}", loc);
 
                 assert_matrix_approx_eq_float(
-                    &loc,
+                    &contact._point_a,
                     &Matrix::from([[20f64 + c, 0f64 + c, 0f64 + c, 1f64]]).t(),
                     1e-7,
                 );
@@ -319,8 +376,87 @@ fn test_intersect_ray_sphere_4() {
         let a = Ray::init(&[30f64, 10f64, 10f64], &[-1f64, 0f64, -1f64]);
         let b = Sphere::init(&[20f64, 0f64, 0f64], 5f64);
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for ray sphere no intersection"),
         }
     }
 }
+
+#[test]
+fn test_intersect_sphere_plane() {
+    let a = Sphere::init(&[0f64, 0.5f64, 0f64], 1f64);
+    let b = Plane::init(&[0f64, 0f64, 0f64], &[0f64, 1f64, 0f64]);
+    let c = a.get_intersect(&b).expect("sphere plane unexpected miss");
+    assert!((c._depth - 0.5).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., -1., 0., 0.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0., -0.5, 0., 1.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_b, &Matrix::from([[0., 0., 0., 1.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("plane sphere unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+
+    //center below the plane
+    let a = Sphere::init(&[3f64, -0.25f64, 0f64], 1f64);
+    let c = a.get_intersect(&b).expect("sphere plane unexpected miss");
+    assert!((c._depth - 0.75).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+
+    assert!(Sphere::init(&[0f64, 1.5f64, 0f64], 1f64)
+        .get_intersect(&b)
+        .is_none());
+}
+#[test]
+fn test_try_intersect_sphere_unsupported() {
+    use crate::cylinder::*;
+    use crate::error::CollideError;
+    let a = Sphere::init(&[10f64, 0f64, 0f64], 5f64);
+    let b = Cylinder::init(&[8f64, 0f64, 0f64], &[0f64, 0f64, 1f64], 1f64, 1f64);
+    assert_eq!(
+        a.try_intersect_native(&b).err(),
+        Some(CollideError::UnsupportedPair(
+            ShapeType::Sphere,
+            ShapeType::Cylinder
+        ))
+    );
+    //the public query falls back to gjk in either order
+    assert!(a
+        .try_intersect(&b)
+        .expect("sphere cylinder unexpected error")
+        .is_some());
+    assert!(b
+        .try_intersect(&a)
+        .expect("cylinder sphere unexpected error")
+        .is_some());
+}
+#[test]
+fn test_try_intersect_sphere_degenerate() {
+    use crate::error::CollideError;
+    let a = Sphere::init(&[10f64, 0f64, 0f64], -5f64);
+    let b = Point::init(&[10f64, 5f64, 0f64]);
+    assert_eq!(
+        a.try_intersect(&b).err(),
+        Some(CollideError::DegenerateShape(ShapeType::Sphere))
+    );
+}
+#[test]
+fn test_raycast_sphere() {
+    let a = Sphere::init(&[5., 0., 0.], 1.);
+    let r = Ray::init(&[0., 0., 0.], &[1., 0., 0.]);
+    let h = a
+        .cast_ray(&r, 0., 10., true)
+        .expect("sphere cast unexpected miss");
+    assert!((h._toi - 4.).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&h._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-12);
+    assert!(a.cast_ray(&r, 0., 3.5, true).is_none());
+
+    //starting inside
+    let h = a
+        .cast_ray(&r, 5., 10., true)
+        .expect("sphere cast unexpected miss");
+    assert!((h._toi - 5.).abs() < 1e-12);
+    assert_eq!(h._normal, Matrix::zero());
+    let h = a
+        .cast_ray(&r, 5., 10., false)
+        .expect("sphere cast unexpected miss");
+    assert!((h._toi - 6.).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&h._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+}
@@ -0,0 +1,295 @@
+use crate::bound::Bound;
+use crate::bound_aabb::AxisAlignedBBox;
+use crate::bvh::Bvh;
+use crate::contact::Contact;
+use crate::dispatch;
+use crate::error::CollideError;
+use crate::ray::*;
+use crate::shape::*;
+use crate::triangle::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+
+use lightmatrix::matrix::*;
+
+#[cfg(test)]
+use crate::line::*;
+#[cfg(test)]
+use crate::point::*;
+#[cfg(test)]
+use crate::rect_box::*;
+#[cfg(test)]
+use crate::sphere::*;
+#[cfg(test)]
+use crate::tri_prism::*;
+
+///static triangle soup with a bounding volume hierarchy over its triangles
+#[derive(Debug, Clone)]
+pub struct TriMesh<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    pub _vertices: Vec<Matrix<T, 4, 1>>,
+    pub _indices: Vec<[usize; 3]>,
+    pub _triangles: Vec<Triangle<T>>,
+    pub _bvh: Bvh<T>,
+    pub _bound: AxisAlignedBBox<T>,
+    pub _vicinity: T,
+}
+
+impl<T> TriMesh<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///builds the mesh from a flat list of xyz coordinates and triangle vertex indices
+    pub fn init(vertices: &[T], indices: &[usize]) -> TriMesh<T> {
+        assert_eq!(vertices.len() % 3, 0);
+        assert_eq!(indices.len() % 3, 0);
+        let n = vertices.len() / 3;
+        assert!(indices.iter().all(|i| *i < n));
+
+        let verts: Vec<Matrix<T, 4, 1>> = vertices
+            .chunks(3)
+            .map(|p| Matrix::from([[p[0], p[1], p[2], T::one()]]).t())
+            .collect();
+        let faces: Vec<[usize; 3]> = indices.chunks(3).map(|f| [f[0], f[1], f[2]]).collect();
+        let triangles: Vec<Triangle<T>> = faces
+            .iter()
+            .map(|f| {
+                let mut v = [T::zero(); 9];
+                for (k, i) in f.iter().enumerate() {
+                    v[k * 3..k * 3 + 3].copy_from_slice(&vertices[i * 3..i * 3 + 3]);
+                }
+                Triangle::init(&v)
+            })
+            .collect();
+
+        let bounds: Vec<AxisAlignedBBox<T>> = triangles.iter().map(|t| t._bound).collect();
+        let bvh = Bvh::init(&bounds);
+        TriMesh {
+            _vertices: verts,
+            _indices: faces,
+            _triangles: triangles,
+            _bound: bvh.get_bound().cloned().unwrap_or_default(),
+            _bvh: bvh,
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+
+    ///indices of triangles whose bounds intersect the given bound
+    pub fn query_bound(&self, bound: &dyn Bound<T>) -> Vec<usize> {
+        self._bvh.query_bound(bound)
+    }
+
+    ///nearest hit of ray ori + dir * t, returns t, triangle index and barycentric coordinates of the hit
+    pub fn intersect_ray(
+        &self,
+        ori: &Matrix<T, 4, 1>,
+        dir: &Matrix<T, 4, 1>,
+    ) -> Option<(T, usize, [T; 3])> {
+        let o = [ori[[0, 0]], ori[[1, 0]], ori[[2, 0]]];
+        let d = [dir[[0, 0]], dir[[1, 0]], dir[[2, 0]]];
+        self._bvh
+            .query_ray(&o, &d, |i| self._triangles[i].intersect_ray(ori, dir))
+    }
+}
+
+impl<T> AnyBase for TriMesh<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for TriMesh<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::TriMesh
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    //the contact's feature is the index of the triangle hit
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if self._triangles.is_empty() {
+            //nothing to hit
+            return Ok(None);
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some((_, i, _)) => Ok(dispatch::try_intersect(&self._triangles[i], other)?
+                        .map(|c| c.with_feature(i))),
+                    _ => Ok(None),
+                }
+            }
+            _ => {
+                //deepest contact among triangles overlapping the other shape's bound
+                let mut best: Option<Contact<T>> = None;
+                for i in self.query_bound(other.get_bound()) {
+                    if let Some(c) = dispatch::try_intersect(&self._triangles[i], other)? {
+                        match best {
+                            Some(ref b) if b._depth >= c._depth => {}
+                            _ => best = Some(c.with_feature(i)),
+                        }
+                    }
+                }
+                Ok(best)
+            }
+        }
+    }
+    fn get_support(&self, _v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        //not convex
+        None
+    }
+}
+
+impl<T> Vicinity<T> for TriMesh<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+        for t in self._triangles.iter_mut() {
+            t.set_vicinity(epsilon);
+        }
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+///n by n grid of unit quads on the y = 0 plane, 2 triangles per quad
+#[cfg(test)]
+fn grid_mesh(n: usize) -> TriMesh<f64> {
+    let mut verts = vec![];
+    for z in 0..=n {
+        for x in 0..=n {
+            verts.extend_from_slice(&[x as f64, 0., z as f64]);
+        }
+    }
+    let mut indices = vec![];
+    for z in 0..n {
+        for x in 0..n {
+            let i = z * (n + 1) + x;
+            indices.extend_from_slice(&[i, i + n + 1, i + 1, i + 1, i + n + 1, i + n + 2]);
+        }
+    }
+    TriMesh::init(&verts, &indices)
+}
+
+#[test]
+fn test_tri_mesh_bvh() {
+    let a = grid_mesh(10);
+    assert_eq!(a._triangles.len(), 200);
+    assert_eq!(a._bound.bound_lower, [0., 0., 0.]);
+    assert_eq!(a._bound.bound_upper, [10., 0., 10.]);
+    assert!(a._bvh._nodes.len() > 1);
+
+    let b = Sphere::init(&[2.5, 0., 2.5], 0.2);
+    let mut found = a.query_bound(b.get_bound());
+    found.sort();
+    assert_eq!(found.len(), 2);
+}
+#[test]
+fn test_intersect_tri_mesh_ray() {
+    let a = grid_mesh(10);
+    let ori = Matrix::from([[3.25, 5., 7.5, 1.]]).t();
+    let dir = Matrix::from([[0., -1., 0., 0.]]).t();
+    let (t, hit, bary) = a
+        .intersect_ray(&ori, &dir)
+        .expect("mesh ray unexpected miss");
+    assert!((t - 5.).abs() < 1e-12);
+    let tri = &a._triangles[hit];
+    let p = tri._v[0] * bary[0] + tri._v[1] * bary[1] + tri._v[2] * bary[2];
+    assert_matrix_approx_eq_float(&p, &Matrix::from([[3.25, 0., 7.5, 1.]]).t(), 1e-12);
+
+    //agrees with a brute force search
+    let dir = Matrix::from([[0.3, -1., 0.2, 0.]]).t().normalize_l2();
+    let (t, i, _) = a
+        .intersect_ray(&ori, &dir)
+        .expect("mesh ray unexpected miss");
+    let brute = a
+        ._triangles
+        .iter()
+        .enumerate()
+        .filter_map(|(k, tri)| tri.intersect_ray(&ori, &dir).map(|x| (x.0, k)))
+        .fold(
+            (f64::INFINITY, 0),
+            |acc, x| if x.0 < acc.0 { x } else { acc },
+        );
+    assert!((t - brute.0).abs() < 1e-12);
+    assert_eq!(i, brute.1);
+
+    let b = Ray::init(&[3.25, 5., 7.5], &[0., -1., 0.]);
+    let c = a.get_intersect(&b).expect("mesh ray unexpected miss");
+    assert_eq!(c._feature, Some(hit));
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 1., 0., 0.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("ray mesh unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[3.25, 0., 7.5, 1.]]).t(), 1e-12);
+
+    assert!(a
+        .get_intersect(&Ray::init(&[3.25, 5., 7.5], &[0., 1., 0.]))
+        .is_none());
+    assert!(a
+        .get_intersect(&Ray::init(&[11., 5., 7.5], &[0., -1., 0.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_tri_mesh_shapes() {
+    let a = grid_mesh(10);
+
+    let c = a
+        .get_intersect(&Point::init(&[4.2, 0., 4.7]))
+        .expect("mesh point unexpected miss");
+    assert!(c._feature.is_some());
+    assert!(a.get_intersect(&Point::init(&[4.2, 0.1, 4.7])).is_none());
+
+    let b = Sphere::init(&[4.5, 0.3, 4.5], 0.5);
+    let c = a.get_intersect(&b).expect("mesh sphere unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    let c = b.get_intersect(&a).expect("sphere mesh unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert!(a
+        .get_intersect(&Sphere::init(&[4.5, 0.6, 4.5], 0.5))
+        .is_none());
+
+    let b = RectBox::init(&[5., 0.8, 5.], 1.);
+    let c = a.get_intersect(&b).expect("mesh box unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-9);
+    assert!(a
+        .get_intersect(&RectBox::init(&[5., 1.2, 5.], 1.))
+        .is_none());
+
+    let b = Line::init(&[2.5, 1., 2.5], &[2.5, -1., 2.6]);
+    let c = a.get_intersect(&b).expect("mesh line unexpected miss");
+    assert!((c._point_a[[0, 0]] - 2.5).abs() < 1e-12);
+    assert!((c._point_a[[2, 0]] - 2.55).abs() < 1e-12);
+    assert!(a
+        .get_intersect(&Line::init(&[2.5, 1., 2.5], &[2.5, 0.1, 2.5]))
+        .is_none());
+
+    let b = TriPrism::init(&[2., -0.5, 2., 2., -0.5, 3., 3., -0.5, 2.], 1.);
+    assert!(a.get_intersect(&b).is_some());
+    let b = TriPrism::init(&[2., 0.5, 2., 2., 0.5, 3., 3., 0.5, 2.], 1.);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_tri_mesh_empty() {
+    let a: TriMesh<f64> = TriMesh::init(&[], &[]);
+    assert!(a.get_intersect(&Point::init(&[0., 0., 0.])).is_none());
+    assert!(a.get_intersect(&Sphere::init(&[0., 0., 0.], 1.)).is_none());
+    assert!(Sphere::init(&[0., 0., 0.], 1.).get_intersect(&a).is_none());
+}
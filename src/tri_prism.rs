@@ -1,9 +1,16 @@
 use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::error::CollideError;
+use crate::intersect_gjk::closest_triangle;
+use crate::intersect_sat;
 use crate::line::*;
 use crate::plane::*;
 use crate::point::*;
 use crate::ray::*;
+use crate::ray_cast::*;
+use crate::rect_box::*;
 use crate::shape::*;
+use crate::sphere::*;
 use crate::vicinity::Vicinity;
 use core::any::Any;
 use num_traits::{Float, NumAssign};
@@ -125,6 +132,155 @@ where
     }
 }
 
+impl<T> TriPrism<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///base vertices followed by the offset vertices
+    pub fn get_vertices(&self) -> [Matrix<T, 4, 1>; 6] {
+        [
+            self._tri_base[0],
+            self._tri_base[1],
+            self._tri_base[2],
+            self._tri_base2[0],
+            self._tri_base2[1],
+            self._tri_base2[2],
+        ]
+    }
+
+    ///vertex on and outward normal of each of the 5 facets, normals are not normalized
+    pub fn get_halfspaces(&self) -> [(Matrix<T, 4, 1>, Matrix<T, 4, 1>); 5] {
+        let n = self._normal_height;
+        [
+            (self._tri_base[0], n * -T::one()),
+            (self._tri_base2[0], n),
+            (
+                self._tri_base[0],
+                (self._tri_base[1] - self._tri_base[0]).cross(&n),
+            ),
+            (
+                self._tri_base[1],
+                (self._tri_base[2] - self._tri_base[1]).cross(&n),
+            ),
+            (
+                self._tri_base[2],
+                (self._tri_base[0] - self._tri_base[2]).cross(&n),
+            ),
+        ]
+    }
+
+    ///directions of the 3 base edges followed by the height, spanning all 9 edges of the tri_prism
+    pub fn get_edge_directions(&self) -> [Matrix<T, 4, 1>; 4] {
+        let b = &self._tri_base;
+        [b[1] - b[0], b[2] - b[1], b[0] - b[2], self._normal_height]
+    }
+
+    ///point on the surface of the tri_prism closest to p, with the side facets split into triangles
+    pub fn get_closest_point(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        let (b, t) = (&self._tri_base, &self._tri_base2);
+        let trigs = [
+            [b[0], b[1], b[2]],
+            [t[0], t[1], t[2]],
+            [b[0], b[1], t[1]],
+            [b[0], t[1], t[0]],
+            [b[1], b[2], t[2]],
+            [b[1], t[2], t[1]],
+            [b[2], b[0], t[0]],
+            [b[2], t[0], t[2]],
+        ];
+        let mut closest = (T::infinity(), *p);
+        for v in trigs.iter() {
+            let l = closest_triangle(&(v[0] - *p), &(v[1] - *p), &(v[2] - *p));
+            let q = v[0] * l[0] + v[1] * l[1] + v[2] * l[2];
+            let d = (q - *p).norm_l2();
+            if d < closest.0 {
+                closest = (d, q);
+            }
+        }
+        closest.1
+    }
+
+    ///entering and leaving parameters and facets of ray ori + dir * t clipped by the facet planes
+    pub fn intersect_ray(
+        &self,
+        ori: &Matrix<T, 4, 1>,
+        dir: &Matrix<T, 4, 1>,
+    ) -> Option<((T, usize), (T, usize))> {
+        let mut t_near = (T::neg_infinity(), 0);
+        let mut t_far = (T::infinity(), 0);
+        for (i, (vert, normal)) in self.get_halfspaces().iter().enumerate() {
+            let dist = (*ori - *vert).inner(normal);
+            let denom = normal.inner(dir);
+            if denom == T::zero() {
+                if dist > T::zero() {
+                    return None;
+                }
+                continue;
+            }
+            let t = -dist / denom;
+            if denom < T::zero() {
+                if t > t_near.0 {
+                    t_near = (t, i);
+                }
+            } else if t < t_far.0 {
+                t_far = (t, i);
+            }
+            if t_near.0 > t_far.0 {
+                return None;
+            }
+        }
+        Some((t_near, t_far))
+    }
+
+    ///test point aginst 5 half spaces from facets of the tri_prism to determine if point is inside the tri_prism
+    pub fn contains(&self, p: &Matrix<T, 4, 1>) -> bool {
+        self.get_halfspaces()
+            .iter()
+            .all(|(vert, normal)| (*p - *vert).inner(normal) <= T::zero())
+    }
+
+    ///contact of a point inside of the tri_prism, pushed out through the nearest facet
+    fn get_contact_inside(&self, p: &Matrix<T, 4, 1>) -> Option<Contact<T>> {
+        let mut nearest: Option<(usize, Matrix<T, 4, 1>, T)> = None;
+        for (i, (vert, normal)) in self.get_halfspaces().iter().enumerate() {
+            let n = normal.normalize_l2();
+            let d = (*p - *vert).inner(&n);
+            if d > T::zero() {
+                return None;
+            }
+            match nearest {
+                Some((_, _, d_min)) if d_min >= d => {}
+                _ => nearest = Some((i, n, d)),
+            }
+        }
+        nearest.map(|(i, n, d)| Contact {
+            _point_a: *p - n * d,
+            _point_b: *p,
+            _normal: n,
+            _depth: -d,
+            _feature: Some(i),
+        })
+    }
+
+    ///contact with a convex polytope using separating axes of the 5 facets,
+    ///the polytope's face normals and cross products of their edge directions
+    fn intersect_polytope(
+        &self,
+        verts: &[Matrix<T, 4, 1>],
+        normals: &[Matrix<T, 4, 1>],
+        edges: &[Matrix<T, 4, 1>],
+    ) -> Option<Contact<T>> {
+        let mut axes: Vec<Matrix<T, 4, 1>> = self.get_halfspaces().iter().map(|x| x.1).collect();
+        axes.extend_from_slice(normals);
+        for a in self.get_edge_directions().iter() {
+            for e in edges.iter() {
+                axes.push(a.cross(e));
+            }
+        }
+        intersect_sat::query_contact(&axes, &self.get_vertices(), verts)
+    }
+}
+
 impl<T> AnyBase for TriPrism<T>
 where
     T: NumAssign + Copy + Default + Float + 'static,
@@ -145,202 +301,177 @@ where
         &self._bound
     }
     // this shall test for intersection of bounding shapes first before procedding to test intersection using algorithms of higher complexity
-    fn get_intersect(&self, other: &dyn Shape<T>) -> (bool, Option<Matrix<T, 4, 1>>) {
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if !self._normal_height.norm_l2().is_finite() {
+            return Err(CollideError::DegenerateShape(ShapeType::TriPrism));
+        }
         if !self.get_bound().intersect(other.get_bound()) {
-            return (false, None);
-        } else {
-            match other.get_type() {
-                ShapeType::Point => {
-                    let other_point: &Point<T> = match other.as_any().downcast_ref::<Point<T>>() {
-                        Some(b) => b,
-                        None => {
-                            panic!("cast to Point failed");
-                        }
-                    };
-
-                    let other_point = other_point._ori;
-
-                    //test point aginst 5 half spaces from facets of the tri_prism to determine if point is inside the tri_prism
-
-                    let n = self._normal_height;
-
-                    let tests = vec![
-                        (self._tri_base[0], n * -T::one()),
-                        (self._tri_base2[0], n),
-                        (
-                            self._tri_base[0],
-                            (self._tri_base[1] - self._tri_base[0]).cross(&n),
-                        ),
-                        (
-                            self._tri_base[1],
-                            (self._tri_base[2] - self._tri_base[1]).cross(&n),
-                        ),
-                        (
-                            self._tri_base[2],
-                            (self._tri_base[0] - self._tri_base[2]).cross(&n),
-                        ),
-                    ];
-
-                    let is_inside = tests
-                        .iter()
-                        .all(|(vert, normal)| !((other_point - *vert).inner(normal) > T::zero()));
-
-                    if is_inside {
-                        (true, Some(other_point))
-                    } else {
-                        (false, None)
-                    }
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+
+                let other_point = other_point._ori;
+
+                Ok(self.get_contact_inside(&other_point))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                let (near, far) = match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some(x) => x,
+                    _ => return Ok(None),
+                };
+                if far.0 < T::zero() {
+                    return Ok(None);
                 }
-                ShapeType::Line => {
-                    let other_point: &Line<T> = match other.as_any().downcast_ref::<Line<T>>() {
-                        Some(b) => b,
-                        None => {
-                            panic!("cast to Point failed");
-                        }
-                    };
-
-                    let a = other_point._a;
-                    let b = other_point._b;
-
-                    //test points aginst 5 half spaces from facets of the tri_prism to determine if point is inside the tri_prism
-
-                    let n = self._normal_height;
-
-                    let tests = vec![
-                        (self._tri_base[0], n * -T::one()),
-                        (self._tri_base2[0], n),
-                        (
-                            self._tri_base[0],
-                            (self._tri_base[1] - self._tri_base[0]).cross(&n),
-                        ),
-                        (
-                            self._tri_base[1],
-                            (self._tri_base[2] - self._tri_base[1]).cross(&n),
-                        ),
-                        (
-                            self._tri_base[2],
-                            (self._tri_base[0] - self._tri_base[2]).cross(&n),
-                        ),
-                    ];
-
-                    let a_is_inside = tests
-                        .iter()
-                        .all(|(vert, normal)| !((a - *vert).inner(normal) > T::zero()));
-                    let b_is_inside = tests
-                        .iter()
-                        .all(|(vert, normal)| !((b - *vert).inner(normal) > T::zero()));
-
-                    if a_is_inside {
-                        return (true, Some(a));
-                    } else if b_is_inside {
-                        return (true, Some(b));
-                    }
+                //ray starting inside of the tri_prism hits on its way out
+                let (t, i) = if near.0 >= T::zero() { near } else { far };
+                let normal = self.get_halfspaces()[i].1.normalize_l2();
+                Ok(Some(
+                    Contact::init_point(other_ray._ori + other_ray._dir * t, normal)
+                        .with_feature(i),
+                ))
+            }
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
+                let c = other_sphere._ori;
+                let r = other_sphere._radius;
+                if let Some(inside) = self.get_contact_inside(&c) {
+                    //center inside of the tri_prism, push out through the nearest facet
+                    let n = inside._normal;
+                    return Ok(Some(Contact {
+                        _point_a: inside._point_a,
+                        _point_b: c - n * r,
+                        _normal: n,
+                        _depth: inside._depth + r,
+                        _feature: inside._feature,
+                    }));
+                }
+                let q = self.get_closest_point(&c);
+                let d = (c - q).norm_l2();
+                if d > r {
+                    return Ok(None);
+                }
+                let n = (c - q) / d;
+                Ok(Some(Contact {
+                    _point_a: q,
+                    _point_b: c - n * r,
+                    _normal: n,
+                    _depth: r - d,
+                    _feature: None,
+                }))
+            }
+            ShapeType::Box => {
+                let other_box: &RectBox<T> = downcast(other)?;
+                let world_axes = [
+                    Matrix::from([[T::one(), T::zero(), T::zero(), T::zero()]]).t(),
+                    Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t(),
+                    Matrix::from([[T::zero(), T::zero(), T::one(), T::zero()]]).t(),
+                ];
+                Ok(self.intersect_polytope(&other_box.get_corners(), &world_axes, &world_axes))
+            }
+            ShapeType::TriPrism => {
+                let other_prism: &TriPrism<T> = downcast(other)?;
+                let normals = other_prism.get_halfspaces().map(|x| x.1);
+                Ok(self.intersect_polytope(
+                    &other_prism.get_vertices(),
+                    &normals,
+                    &other_prism.get_edge_directions(),
+                ))
+            }
+            ShapeType::Line => {
+                let other_line: &Line<T> = downcast(other)?;
 
-                    //continue test using ray plane intersection
+                let a = other_line._a;
+                let b = other_line._b;
 
-                    let v = b - a;
-                    let mag = v.norm_l2();
+                if let Some(c) = self.get_contact_inside(&a) {
+                    return Ok(Some(c));
+                } else if let Some(c) = self.get_contact_inside(&b) {
+                    return Ok(Some(c));
+                }
 
-                    let r = Ray::init(
-                        &[a[[0, 0]], a[[1, 0]], a[[2, 0]]],
-                        &[v[[0, 0]], v[[1, 0]], v[[2, 0]]],
-                    );
+                //continue test using ray plane intersection
 
-                    let n1 = self._normal_height;
-                    let n0 = n1 * -T::one();
-                    let n2 = (self._tri_base[1] - self._tri_base[0]).cross(&n1);
-                    let n3 = (self._tri_base[2] - self._tri_base[1]).cross(&n1);
-                    let n4 = (self._tri_base[0] - self._tri_base[2]).cross(&n1);
-
-                    let facets = vec![
-                        Plane::init(
-                            &[
-                                self._tri_base[0][[0, 0]] as T,
-                                self._tri_base[0][[1, 0]] as T,
-                                self._tri_base[0][[2, 0]] as T,
-                            ],
-                            &[n0[[0, 0]] as T, n0[[1, 0]] as T, n0[[2, 0]] as T],
-                        ),
-                        Plane::init(
-                            &[
-                                self._tri_base2[0][[0, 0]] as T,
-                                self._tri_base2[0][[1, 0]] as T,
-                                self._tri_base2[0][[2, 0]] as T,
-                            ],
-                            &[n1[[0, 0]] as T, n1[[1, 0]] as T, n1[[2, 0]] as T],
-                        ),
-                        Plane::init(
-                            &[
-                                self._tri_base[0][[0, 0]] as T,
-                                self._tri_base[0][[1, 0]] as T,
-                                self._tri_base[0][[2, 0]] as T,
-                            ],
-                            &[n2[[0, 0]] as T, n2[[1, 0]] as T, n2[[2, 0]] as T],
-                        ),
-                        Plane::init(
-                            &[
-                                self._tri_base[1][[0, 0]] as T,
-                                self._tri_base[1][[1, 0]] as T,
-                                self._tri_base[1][[2, 0]] as T,
-                            ],
-                            &[n3[[0, 0]] as T, n3[[1, 0]] as T, n3[[2, 0]] as T],
-                        ),
-                        Plane::init(
-                            &[
-                                self._tri_base[2][[0, 0]] as T,
-                                self._tri_base[2][[1, 0]] as T,
-                                self._tri_base[2][[2, 0]] as T,
-                            ],
-                            &[n4[[0, 0]] as T, n4[[1, 0]] as T, n4[[2, 0]] as T],
-                        ),
-                    ];
-
-                    let mut intersect_point = None;
-                    let mut is_inside = false;
-                    for i in facets.iter() {
-                        let res = r.get_intersect(i);
-                        if res.0 {
-                            let collide_point = res.1.unwrap();
-                            let mag2 = (collide_point - a).norm_l2();
-
-                            //one more check necesary for the candidate collision point
-                            let is_point_inside = tests.iter().all(|(vert, normal)| {
-                                !((collide_point - *vert).inner(normal) > T::zero())
-                            });
-
-                            if !is_point_inside || mag2 > mag {
-                                continue;
-                            } else {
-                                is_inside = true;
-                                intersect_point = res.1;
-                                break;
-                            }
+                let v = b - a;
+                let mag = v.norm_l2();
+                if mag == T::zero() {
+                    return Ok(None);
+                }
+
+                let r = Ray::init(
+                    &[a[[0, 0]], a[[1, 0]], a[[2, 0]]],
+                    &[v[[0, 0]], v[[1, 0]], v[[2, 0]]],
+                );
+
+                for (i, (vert, normal)) in self.get_halfspaces().iter().enumerate() {
+                    let facet = Plane::init(
+                        &[vert[[0, 0]], vert[[1, 0]], vert[[2, 0]]],
+                        &[normal[[0, 0]], normal[[1, 0]], normal[[2, 0]]],
+                    );
+                    if let Some(c) = r.try_intersect(&facet)? {
+                        let collide_point = c._point_a;
+                        let mag2 = (collide_point - a).norm_l2();
+
+                        //one more check necesary for the candidate collision point
+                        if self.contains(&collide_point) && mag2 <= mag {
+                            return Ok(Some(
+                                Contact::init_point(collide_point, normal.normalize_l2())
+                                    .with_feature(i),
+                            ));
                         }
                     }
+                }
 
-                    if is_inside {
-                        (true, intersect_point)
-                    } else {
-                        (false, None)
+                Ok(None)
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                let verts = self.get_vertices();
+                let dists = verts.map(|v| other_plane.get_distance(&v));
+                let (mut i_min, mut i_max) = (0, 0);
+                for (i, d) in dists.iter().enumerate() {
+                    if *d < dists[i_min] {
+                        i_min = i;
+                    }
+                    if *d > dists[i_max] {
+                        i_max = i;
                     }
                 }
-                _ => {
-                    unimplemented!();
+                let (d_min, d_max) = (dists[i_min], dists[i_max]);
+                if d_min > T::zero() || d_max < T::zero() {
+                    return Ok(None);
                 }
+                //the plane is pushed towards the side holding the lesser part of the prism
+                let n = other_plane._normal;
+                let (i, normal) = if -d_min <= d_max {
+                    (i_min, n * -T::one())
+                } else {
+                    (i_max, n)
+                };
+                let p = verts[i];
+                Ok(Some(Contact {
+                    _point_a: p,
+                    _point_b: p - n * dists[i],
+                    _normal: normal,
+                    _depth: dists[i].abs(),
+                    _feature: None,
+                }))
             }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
         }
     }
     fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
         if v.norm_l2() != T::zero() {
             //get a furthest point in the given direction v
-            let points = [
-                self._tri_base[0],
-                self._tri_base[1],
-                self._tri_base[2],
-                self._tri_base2[0],
-                self._tri_base2[1],
-                self._tri_base2[2],
-            ];
+            let points = self.get_vertices();
 
             let furthest = points
                 .iter()
@@ -349,7 +480,7 @@ where
                 .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
                 .unwrap();
 
-            let o = points[furthest.0].clone();
+            let o = points[furthest.0];
 
             Some(o)
         } else {
@@ -358,6 +489,18 @@ where
     }
 }
 
+impl<T> RayCast<T> for TriPrism<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn cast_ray(&self, ray: &Ray<T>, t_min: T, t_max: T, solid: bool) -> Option<RayHit<T>> {
+        let (near, far) = self.intersect_ray(&ray._ori, &ray._dir)?;
+        let halfspaces = self.get_halfspaces();
+        let crossing = |(t, i): (T, usize)| (t, halfspaces[i].1.normalize_l2(), Some(i));
+        cast_convex(ray, t_min, t_max, solid, crossing(near), crossing(far))
+    }
+}
+
 impl<T> Vicinity<T> for TriPrism<T>
 where
     T: NumAssign + Copy + Default + Float,
@@ -366,11 +509,7 @@ where
         self._vicinity = epsilon.abs();
     }
     fn within_vicinity(&self, a: T, b: T) -> bool {
-        if a + self._vicinity >= b && a - self._vicinity <= b {
-            true
-        } else {
-            false
-        }
+        a + self._vicinity >= b && a - self._vicinity <= b
     }
 }
 
@@ -383,7 +522,7 @@ fn test_intersect_triprism_line_0() {
         let b = Line::init(&[0.25, 0.25, 0.], &[1., 1., 0.]);
 
         match a.get_intersect(&b) {
-            (true, Some(_loc)) => {}
+            Some(_c) => {}
             _ => panic!("unexpected result for triprism line intersection"),
         }
     }
@@ -397,7 +536,7 @@ fn test_intersect_triprism_line_1() {
         let b = Line::init(&[0.5, -50., 0.], &[0.5, 50., 0.]);
 
         match a.get_intersect(&b) {
-            (true, Some(_loc)) => {}
+            Some(_c) => {}
             _ => panic!("unexpected result for triprism line intersection"),
         }
     }
@@ -411,7 +550,7 @@ fn test_intersect_triprism_line_2() {
         let b = Line::init(&[0.25, 0.25, 0.5], &[0.26, 0.26, 0.5]);
 
         match a.get_intersect(&b) {
-            (true, Some(_loc)) => {}
+            Some(_c) => {}
             _ => panic!("unexpected result for triprism line intersection"),
         }
     }
@@ -425,7 +564,7 @@ fn test_intersect_triprism_line_3() {
         let b = Line::init(&[0.25, 0.25, 0.], &[1., 1., 0.]);
 
         match b.get_intersect(&a) {
-            (true, Some(_loc)) => {}
+            Some(_c) => {}
             _ => panic!("unexpected result for triprism line intersection"),
         }
     }
@@ -439,7 +578,7 @@ fn test_intersect_triprism_line_4() {
         let b = Line::init(&[0.5, -50., 0.], &[0.5, 50., 0.]);
 
         match b.get_intersect(&a) {
-            (true, Some(_loc)) => {}
+            Some(_c) => {}
             _ => panic!("unexpected result for triprism line intersection"),
         }
     }
@@ -453,7 +592,7 @@ fn test_intersect_triprism_line_5() {
         let b = Line::init(&[0.25, 0.25, 0.5], &[0.26, 0.26, 0.5]);
 
         match b.get_intersect(&a) {
-            (true, Some(_loc)) => {}
+            Some(_c) => {}
             _ => panic!("unexpected result for triprism line intersection"),
         }
     }
@@ -466,11 +605,8 @@ fn test_intersect_triprism_line_6() {
 
         let b = Line::init(&[0.25, 0.25, 1.5], &[1., 1., 1.5]);
 
-        match a.get_intersect(&b) {
-            (true, Some(_loc)) => {
-                panic!("unexpected result for triprism line intersection");
-            }
-            _ => {}
+        if let Some(_c) = a.get_intersect(&b) {
+            panic!("unexpected result for triprism line intersection");
         }
     }
 }
@@ -482,11 +618,8 @@ fn test_intersect_triprism_line_7() {
 
         let b = Line::init(&[0., -5., 0.5], &[50., 45., 0.5]);
 
-        match a.get_intersect(&b) {
-            (true, Some(_loc)) => {
-                panic!("unexpected result for triprism line intersection");
-            }
-            _ => {}
+        if let Some(_c) = a.get_intersect(&b) {
+            panic!("unexpected result for triprism line intersection");
         }
     }
 }
@@ -500,9 +633,8 @@ fn test_intersect_triprism_point_0() {
         let b = Point::init(&[0.25, 0.25, 0.]);
 
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, b._ori);
-                // assert!(loc.is_equal(&b._ori, 0.0001f64).unwrap());
+            Some(c) => {
+                assert_eq!(c._point_b, b._ori);
             }
             _ => panic!("unexpected result for triprism point intersection"),
         }
@@ -517,9 +649,8 @@ fn test_intersect_triprism_point_1() {
         let b = Point::init(&[0.25, 0.25, 0.5]);
 
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, b._ori);
-                // assert!(loc.is_equal(&b._ori, 0.0001f64).unwrap());
+            Some(c) => {
+                assert_eq!(c._point_b, b._ori);
             }
             _ => panic!("unexpected result for triprism point intersection"),
         }
@@ -534,9 +665,8 @@ fn test_intersect_triprism_point_2() {
         let b = Point::init(&[0.25, 0.25, 1.]);
 
         match a.get_intersect(&b) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, b._ori);
-                // assert!(loc.is_equal(&b._ori, 0.0001f64).unwrap());
+            Some(c) => {
+                assert_eq!(c._point_b, b._ori);
             }
             _ => panic!("unexpected result for triprism point intersection"),
         }
@@ -551,9 +681,8 @@ fn test_intersect_triprism_point_3() {
         let b = Point::init(&[0.25, 0.25, 0.]);
 
         match b.get_intersect(&a) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, b._ori);
-                // assert!(loc.is_equal(&b._ori, 0.0001f64).unwrap());
+            Some(c) => {
+                assert_eq!(c._point_b, b._ori);
             }
             _ => panic!("unexpected result for triprism point intersection"),
         }
@@ -568,9 +697,8 @@ fn test_intersect_triprism_point_4() {
         let b = Point::init(&[0.25, 0.25, 0.5]);
 
         match b.get_intersect(&a) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, b._ori);
-                // assert!(loc.is_equal(&b._ori, 0.0001f64).unwrap());
+            Some(c) => {
+                assert_eq!(c._point_b, b._ori);
             }
             _ => panic!("unexpected result for triprism point intersection"),
         }
@@ -585,9 +713,8 @@ fn test_intersect_triprism_point_5() {
         let b = Point::init(&[0.25, 0.25, 1.]);
 
         match b.get_intersect(&a) {
-            (true, Some(loc)) => {
-                assert_eq!(loc, b._ori);
-                // assert!(loc.is_equal(&b._ori, 0.0001f64).unwrap());
+            Some(c) => {
+                assert_eq!(c._point_b, b._ori);
             }
             _ => panic!("unexpected result for triprism point intersection"),
         }
@@ -602,7 +729,7 @@ fn test_intersect_triprism_point_6() {
         let b = Point::init(&[0.25, 0.25, 1.001]);
 
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for triprism point no intersection"),
         }
     }
@@ -616,8 +743,157 @@ fn test_intersect_triprism_point_7() {
         let b = Point::init(&[0.5, 0.55, 0.5]);
 
         match a.get_intersect(&b) {
-            (false, None) => (),
+            None => (),
             _ => panic!("unexpected result for triprism point no intersection"),
         }
     }
 }
+#[test]
+fn test_intersect_triprism_point_contact() {
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = Point::init(&[0.75, 0.25, 0.9]);
+    let c = a
+        .get_intersect(&b)
+        .expect("unexpected result for triprism point contact");
+    assert!((c._depth - 0.1).abs() < 1e-9);
+    assert_eq!(c._feature, Some(1));
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-9);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.75, 0.25, 1., 1.]]).t(), 1e-9);
+}
+#[test]
+fn test_intersect_triprism_plane() {
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = Plane::init(&[0., 0., 0.3], &[0., 0., 1.]);
+    let c = a.get_intersect(&b).expect("triprism plane unexpected miss");
+    assert!((c._depth - 0.3).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-12);
+    assert_matrix_approx_eq_float(&c._point_b, &Matrix::from([[0., 0., 0.3, 1.]]).t(), 1e-12);
+    let c = b.get_intersect(&a).expect("plane triprism unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-12);
+
+    //mostly below the plane
+    let b = Plane::init(&[0., 0., 0.8], &[0., 0., 1.]);
+    let c = a.get_intersect(&b).expect("triprism plane unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-12);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-12);
+
+    assert!(a
+        .get_intersect(&Plane::init(&[0., 0., 1.2], &[0., 0., 1.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_triprism_sphere() {
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = Sphere::init(&[0.75, 0.25, 1.3], 0.5);
+    let c = a
+        .get_intersect(&b)
+        .expect("triprism sphere unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-9);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.75, 0.25, 1., 1.]]).t(), 1e-9);
+    let c = b
+        .get_intersect(&a)
+        .expect("sphere triprism unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-9);
+
+    //center inside, pushed out through the top facet
+    let b = Sphere::init(&[0.75, 0.25, 0.9], 0.2);
+    let c = a
+        .get_intersect(&b)
+        .expect("triprism sphere unexpected miss");
+    assert!((c._depth - 0.3).abs() < 1e-9);
+    assert_eq!(c._feature, Some(1));
+
+    assert!(a
+        .get_intersect(&Sphere::init(&[0.75, 0.25, 1.6], 0.5))
+        .is_none());
+}
+#[test]
+fn test_intersect_triprism_ray() {
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = Ray::init(&[0.75, 0.25, 5.], &[0., 0., -1.]);
+    let c = a.get_intersect(&b).expect("triprism ray unexpected miss");
+    assert_eq!(c._feature, Some(1));
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-9);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.75, 0.25, 1., 1.]]).t(), 1e-9);
+
+    //entering through the side facet at x = 1
+    let b = Ray::init(&[2., 0.25, 0.5], &[-1., 0., 0.]);
+    let c = b.get_intersect(&a).expect("ray triprism unexpected miss");
+    assert_eq!(c._feature, Some(3));
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-9);
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[1., 0.25, 0.5, 1.]]).t(), 1e-9);
+
+    //starting inside, hits on the way out
+    let b = Ray::init(&[0.75, 0.25, 0.5], &[1., 0., 0.]);
+    let c = a.get_intersect(&b).expect("triprism ray unexpected miss");
+    assert_eq!(c._feature, Some(3));
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[1., 0.25, 0.5, 1.]]).t(), 1e-9);
+
+    assert!(a
+        .get_intersect(&Ray::init(&[0.75, 0.25, 5.], &[0., 0., 1.]))
+        .is_none());
+}
+#[test]
+fn test_intersect_triprism_rectbox() {
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = RectBox::init(&[1.4, 0.25, 0.5], 0.5);
+    let c = a
+        .get_intersect(&b)
+        .expect("triprism rectbox unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-9);
+    let c = b
+        .get_intersect(&a)
+        .expect("rectbox triprism unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[-1., 0., 0., 0.]]).t(), 1e-9);
+
+    assert!(a
+        .get_intersect(&RectBox::init(&[2.6, 0.25, 0.5], 0.5))
+        .is_none());
+}
+#[test]
+fn test_intersect_triprism_triprism() {
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let b = TriPrism::init(&[0., 0., 0.8, 1., 0., 0.8, 1., 1., 0.8], 1.);
+    let c = a
+        .get_intersect(&b)
+        .expect("triprism triprism unexpected miss");
+    assert!((c._depth - 0.2).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-9);
+
+    //overlapping corners, separated the least along the diagonal facet
+    let b = TriPrism::init(&[0.9, 0., 0., 1.9, 0., 0., 1.9, 1., 0.], 1.);
+    let c = a
+        .get_intersect(&b)
+        .expect("triprism triprism unexpected miss");
+    let s = 0.5f64.sqrt();
+    assert!((c._depth - 0.1 * s).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[s, -s, 0., 0.]]).t(), 1e-9);
+
+    assert!(a
+        .get_intersect(&TriPrism::init(
+            &[0., 0., 1.5, 1., 0., 1.5, 1., 1., 1.5],
+            1.
+        ))
+        .is_none());
+}
+#[test]
+fn test_raycast_triprism() {
+    let a = TriPrism::init(&[0., 0., 0., 1., 0., 0., 1., 1., 0.], 1.);
+    let r = Ray::init(&[2., 0.25, 0.5], &[-1., 0., 0.]);
+    let h = a
+        .cast_ray(&r, 0., 10., true)
+        .expect("triprism cast unexpected miss");
+    assert!((h._toi - 1.).abs() < 1e-12);
+    assert_eq!(h._feature, Some(3));
+    assert_matrix_approx_eq_float(&h._normal, &Matrix::from([[1., 0., 0., 0.]]).t(), 1e-12);
+
+    //leaving through the diagonal facet
+    let h = a
+        .cast_ray(&r, 1.5, 10., false)
+        .expect("triprism cast unexpected miss");
+    assert!((h._toi - 1.75).abs() < 1e-12);
+    assert_eq!(h._feature, Some(4));
+    assert!(a.cast_ray(&r, 0., 0.5, true).is_none());
+}
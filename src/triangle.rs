@@ -0,0 +1,496 @@
+use crate::bound::Bound;
+use crate::contact::Contact;
+use crate::cuboid::*;
+use crate::error::CollideError;
+use crate::intersect_epa::barycentric;
+use crate::intersect_gjk::closest_triangle;
+use crate::intersect_sat;
+use crate::line::*;
+use crate::oriented_box::*;
+use crate::plane::*;
+use crate::point::*;
+use crate::ray::*;
+use crate::rect_box::*;
+use crate::shape::*;
+use crate::sphere::*;
+use crate::vicinity::Vicinity;
+use core::any::Any;
+use num_traits::{Float, NumAssign};
+
+use crate::bound_aabb::AxisAlignedBBox;
+use lightmatrix::matrix::*;
+
+#[derive(Debug, Clone)]
+pub struct Triangle<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    ///vertices in ccw order
+    pub _v: [Matrix<T, 4, 1>; 3],
+
+    ///unit normal following the ccw winding of the vertices
+    pub _normal: Matrix<T, 4, 1>,
+
+    pub _bound: AxisAlignedBBox<T>,
+
+    pub _vicinity: T,
+}
+
+impl<T> Triangle<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    /// initialize with vertices in ccw order
+    pub fn init(verts: &[T]) -> Triangle<T> {
+        assert!(verts.len() == 9);
+
+        let v = [
+            Matrix::from([[verts[0], verts[1], verts[2], T::one()]]).t(),
+            Matrix::from([[verts[3], verts[4], verts[5], T::one()]]).t(),
+            Matrix::from([[verts[6], verts[7], verts[8], T::one()]]).t(),
+        ];
+        let normal = (v[1] - v[0]).cross(&(v[2] - v[0])).normalize_l2();
+
+        let mut lower = [T::infinity(); 3];
+        let mut upper = [T::neg_infinity(); 3];
+        for p in v.iter() {
+            for i in 0..3 {
+                lower[i] = lower[i].min(p[[i, 0]]);
+                upper[i] = upper[i].max(p[[i, 0]]);
+            }
+        }
+
+        Triangle {
+            _v: v,
+            _normal: normal,
+            _bound: AxisAlignedBBox::new(ShapeType::Rect, &[lower, upper].concat()),
+            _vicinity: T::from(1e-7).unwrap(),
+        }
+    }
+
+    pub fn get_edges(&self) -> [Matrix<T, 4, 1>; 3] {
+        [
+            self._v[1] - self._v[0],
+            self._v[2] - self._v[1],
+            self._v[0] - self._v[2],
+        ]
+    }
+
+    ///barycentric coordinates of p projected onto the plane of the triangle
+    pub fn get_barycentric(&self, p: &Matrix<T, 4, 1>) -> [T; 3] {
+        barycentric(p, &self._v[0], &self._v[1], &self._v[2])
+    }
+
+    ///point on the triangle closest to p
+    pub fn get_closest_point(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        let l = closest_triangle(&(self._v[0] - *p), &(self._v[1] - *p), &(self._v[2] - *p));
+        self._v[0] * l[0] + self._v[1] * l[1] + self._v[2] * l[2]
+    }
+
+    ///test if p lies on the triangle within vicinity
+    pub fn contains(&self, p: &Matrix<T, 4, 1>) -> bool {
+        if (*p - self._v[0]).inner(&self._normal).abs() > self._vicinity {
+            return false;
+        }
+        self.get_barycentric(p)
+            .iter()
+            .all(|x| *x >= -self._vicinity)
+    }
+
+    ///möller-trumbore intersection of ray ori + dir * t with the triangle,
+    ///returns t and barycentric coordinates of the hit, None if missed or parallel
+    pub fn intersect_ray(
+        &self,
+        ori: &Matrix<T, 4, 1>,
+        dir: &Matrix<T, 4, 1>,
+    ) -> Option<(T, [T; 3])> {
+        let e1 = self._v[1] - self._v[0];
+        let e2 = self._v[2] - self._v[0];
+        let p = dir.cross(&e2);
+        let det = e1.inner(&p);
+        if det.abs() <= T::epsilon() * dir.norm_l2() * e1.cross(&e2).norm_l2() {
+            return None;
+        }
+        let inv = T::one() / det;
+        let s = *ori - self._v[0];
+        let u = s.inner(&p) * inv;
+        if u < T::zero() || u > T::one() {
+            return None;
+        }
+        let q = s.cross(&e1);
+        let v = dir.inner(&q) * inv;
+        if v < T::zero() || u + v > T::one() {
+            return None;
+        }
+        let t = e2.inner(&q) * inv;
+        if t < T::zero() {
+            return None;
+        }
+        Some((t, [T::one() - u - v, u, v]))
+    }
+
+    ///normal of the triangle facing towards p
+    fn get_normal_towards(&self, p: &Matrix<T, 4, 1>) -> Matrix<T, 4, 1> {
+        if (*p - self._v[0]).inner(&self._normal) < T::zero() {
+            self._normal * -T::one()
+        } else {
+            self._normal
+        }
+    }
+}
+
+impl<T> AnyBase for Triangle<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<T> Shape<T> for Triangle<T>
+where
+    T: NumAssign + Copy + Default + Float + 'static,
+{
+    fn get_type(&self) -> ShapeType {
+        ShapeType::Trig
+    }
+    fn get_bound(&self) -> &dyn Bound<T> {
+        &self._bound
+    }
+    fn try_intersect_native(
+        &self,
+        other: &dyn Shape<T>,
+    ) -> Result<Option<Contact<T>>, CollideError> {
+        if !self._normal.norm_l2().is_finite() {
+            return Err(CollideError::DegenerateShape(ShapeType::Trig));
+        }
+        if !self.get_bound().intersect(other.get_bound()) {
+            return Ok(None);
+        }
+        match other.get_type() {
+            ShapeType::Point => {
+                let other_point: &Point<T> = downcast(other)?;
+                let p = other_point._ori;
+                let closest = self.get_closest_point(&p);
+                let d = (p - closest).norm_l2();
+                if d > self._vicinity {
+                    return Ok(None);
+                }
+                Ok(Some(Contact {
+                    _point_a: closest,
+                    _point_b: p,
+                    _normal: self.get_normal_towards(&p),
+                    _depth: -d,
+                    _feature: None,
+                }))
+            }
+            ShapeType::Ray => {
+                let other_ray: &Ray<T> = downcast(other)?;
+                match self.intersect_ray(&other_ray._ori, &other_ray._dir) {
+                    Some((t, _)) => Ok(Some(Contact::init_point(
+                        other_ray._ori + other_ray._dir * t,
+                        self.get_normal_towards(&other_ray._ori),
+                    ))),
+                    _ => Ok(None),
+                }
+            }
+            ShapeType::Line => {
+                let other_line: &Line<T> = downcast(other)?;
+                let (a, b) = (other_line._a, other_line._b);
+                let dir = b - a;
+                if let Some((t, _)) = self.intersect_ray(&a, &dir) {
+                    if t <= T::one() {
+                        return Ok(Some(Contact::init_point(
+                            a + dir * t,
+                            self.get_normal_towards(&a),
+                        )));
+                    }
+                    return Ok(None);
+                }
+                let coplanar = (a - self._v[0]).inner(&self._normal).abs() <= self._vicinity
+                    && (b - self._v[0]).inner(&self._normal).abs() <= self._vicinity;
+                if !coplanar {
+                    return Ok(None);
+                }
+                //segment lies in the plane of the triangle
+                for p in [a, b].iter() {
+                    if self.contains(p) {
+                        return Ok(Some(Contact::init_point(*p, self._normal)));
+                    }
+                }
+                for i in 0..3 {
+                    let (p, q) =
+                        closest_points_segments(&self._v[i], &self._v[(i + 1) % 3], &a, &b);
+                    if (p - q).norm_l2() <= self._vicinity {
+                        return Ok(Some(Contact::init_point(p, self._normal).with_feature(i)));
+                    }
+                }
+                Ok(None)
+            }
+            ShapeType::Sphere => {
+                let other_sphere: &Sphere<T> = downcast(other)?;
+                let c = other_sphere._ori;
+                let r = other_sphere._radius;
+                let closest = self.get_closest_point(&c);
+                let d = (c - closest).norm_l2();
+                if d > r {
+                    return Ok(None);
+                }
+                let n = if d == T::zero() {
+                    self._normal
+                } else {
+                    (c - closest) / d
+                };
+                Ok(Some(Contact {
+                    _point_a: closest,
+                    _point_b: c - n * r,
+                    _normal: n,
+                    _depth: r - d,
+                    _feature: None,
+                }))
+            }
+            ShapeType::Plane => {
+                let other_plane: &Plane<T> = downcast(other)?;
+                let n = other_plane._normal;
+                let d = [
+                    (self._v[0] - other_plane._offset).inner(&n),
+                    (self._v[1] - other_plane._offset).inner(&n),
+                    (self._v[2] - other_plane._offset).inner(&n),
+                ];
+                let (mut i_min, mut i_max) = (0, 0);
+                for i in 1..3 {
+                    if d[i] < d[i_min] {
+                        i_min = i;
+                    }
+                    if d[i] > d[i_max] {
+                        i_max = i;
+                    }
+                }
+                if d[i_min] > self._vicinity || d[i_max] < -self._vicinity {
+                    return Ok(None);
+                }
+                //the plane is pushed towards the side holding the lesser part of the triangle
+                let (i, normal) = if -d[i_min] <= d[i_max] {
+                    (i_min, n * -T::one())
+                } else {
+                    (i_max, n)
+                };
+                Ok(Some(Contact {
+                    _point_a: self._v[i],
+                    _point_b: self._v[i] - n * d[i],
+                    _normal: normal,
+                    _depth: d[i].abs(),
+                    _feature: Some(i),
+                }))
+            }
+            ShapeType::Box | ShapeType::Rect | ShapeType::OrientedBox => {
+                let world_axes = [
+                    Matrix::from([[T::one(), T::zero(), T::zero(), T::zero()]]).t(),
+                    Matrix::from([[T::zero(), T::one(), T::zero(), T::zero()]]).t(),
+                    Matrix::from([[T::zero(), T::zero(), T::one(), T::zero()]]).t(),
+                ];
+                let (corners, box_axes) = match other.get_type() {
+                    ShapeType::Box => (downcast::<RectBox<T>, T>(other)?.get_corners(), world_axes),
+                    ShapeType::Rect => (downcast::<Cuboid<T>, T>(other)?.get_corners(), world_axes),
+                    _ => {
+                        let other_box: &OrientedBox<T> = downcast(other)?;
+                        (other_box.get_corners(), other_box._axes)
+                    }
+                };
+                let mut axes = vec![self._normal];
+                axes.extend(box_axes.iter().cloned());
+                for e in self.get_edges().iter() {
+                    for b in box_axes.iter() {
+                        axes.push(e.cross(b));
+                    }
+                }
+                Ok(intersect_sat::query_contact(&axes, &self._v, &corners))
+            }
+            ShapeType::Trig => {
+                let other_trig: &Triangle<T> = downcast(other)?;
+                let mut axes = vec![self._normal, other_trig._normal];
+                for e in self.get_edges().iter() {
+                    for f in other_trig.get_edges().iter() {
+                        axes.push(e.cross(f));
+                    }
+                    //in-plane axes for coplanar triangles
+                    axes.push(e.cross(&self._normal));
+                }
+                for f in other_trig.get_edges().iter() {
+                    axes.push(f.cross(&other_trig._normal));
+                }
+                Ok(intersect_sat::query_contact(
+                    &axes,
+                    &self._v,
+                    &other_trig._v,
+                ))
+            }
+            _ => Err(CollideError::UnsupportedPair(
+                self.get_type(),
+                other.get_type(),
+            )),
+        }
+    }
+    fn get_support(&self, v: &Matrix<T, 4, 1>) -> Option<Matrix<T, 4, 1>> {
+        if v.norm_l2() != T::zero() {
+            //get a furthest point in the given direction v
+            let furthest = self
+                ._v
+                .iter()
+                .map(|x| x.inner(v))
+                .enumerate()
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap();
+            Some(self._v[furthest.0])
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Vicinity<T> for Triangle<T>
+where
+    T: NumAssign + Copy + Default + Float,
+{
+    fn set_vicinity(&mut self, epsilon: T) {
+        self._vicinity = epsilon.abs();
+    }
+    fn within_vicinity(&self, a: T, b: T) -> bool {
+        a + self._vicinity >= b && a - self._vicinity <= b
+    }
+}
+
+#[test]
+fn test_triangle_barycentric() {
+    let a = Triangle::init(&[0., 0., 0., 1., 0., 0., 0., 1., 0.]);
+    let l = a.get_barycentric(&Matrix::from([[0.25, 0.5, 3., 1.]]).t());
+    assert!((l[0] - 0.25).abs() < 1e-9);
+    assert!((l[1] - 0.25).abs() < 1e-9);
+    assert!((l[2] - 0.5).abs() < 1e-9);
+    assert!(a.contains(&Matrix::from([[0.25, 0.5, 0., 1.]]).t()));
+    assert!(!a.contains(&Matrix::from([[0.25, 0.5, 0.1, 1.]]).t()));
+    assert!(!a.contains(&Matrix::from([[0.75, 0.5, 0., 1.]]).t()));
+}
+#[test]
+fn test_intersect_triangle_point() {
+    let a = Triangle::init(&[0., 0., 0., 1., 0., 0., 0., 1., 0.]);
+    let b = Point::init(&[0.2, 0.2, 0.]);
+    let c = a.get_intersect(&b).expect("triangle point unexpected miss");
+    assert_eq!(c._point_b, b._ori);
+    assert!(a.get_intersect(&Point::init(&[0.6, 0.6, 0.])).is_none());
+    assert!(b.get_intersect(&a).is_some());
+}
+#[test]
+fn test_intersect_triangle_ray() {
+    let a = Triangle::init(&[0., 0., 0., 1., 0., 0., 0., 1., 0.]);
+    let b = Ray::init(&[0.2, 0.3, -5.], &[0., 0., 1.]);
+    let c = a.get_intersect(&b).expect("triangle ray unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.2, 0.3, 0., 1.]]).t(), 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-9);
+
+    //from the ray's side, normal is the negated outward normal of the hit face
+    let c = b.get_intersect(&a).expect("ray triangle unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-9);
+
+    let b = Ray::init(&[0.2, 0.3, -5.], &[0., 0., -1.]);
+    assert!(a.get_intersect(&b).is_none());
+    let b = Ray::init(&[0.7, 0.7, -5.], &[0., 0., 1.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_triangle_line() {
+    let a = Triangle::init(&[0., 0., 0., 1., 0., 0., 0., 1., 0.]);
+    let b = Line::init(&[0.2, 0.3, -1.], &[0.2, 0.3, 1.]);
+    let c = a.get_intersect(&b).expect("triangle line unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.2, 0.3, 0., 1.]]).t(), 1e-9);
+
+    let b = Line::init(&[0.2, 0.3, 0.5], &[0.2, 0.3, 1.]);
+    assert!(a.get_intersect(&b).is_none());
+
+    //coplanar segment crossing an edge
+    let b = Line::init(&[0.5, -1., 0.], &[0.5, 0.25, 0.]);
+    let c = b.get_intersect(&a).expect("line triangle unexpected miss");
+    assert_matrix_approx_eq_float(&c._point_a, &Matrix::from([[0.5, 0.25, 0., 1.]]).t(), 1e-9);
+
+    let b = Line::init(&[2., -1., 0.], &[2., 1., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_triangle_sphere() {
+    let a = Triangle::init(&[0., 0., 0., 1., 0., 0., 0., 1., 0.]);
+    let b = Sphere::init(&[0.2, 0.2, 0.5], 1.);
+    let c = a
+        .get_intersect(&b)
+        .expect("triangle sphere unexpected miss");
+    assert!((c._depth - 0.5).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-9);
+    assert_matrix_approx_eq_float(&c._point_b, &Matrix::from([[0.2, 0.2, -0.5, 1.]]).t(), 1e-9);
+
+    let c = b
+        .get_intersect(&a)
+        .expect("sphere triangle unexpected miss");
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-9);
+
+    //closest feature is a vertex
+    let b = Sphere::init(&[-1., -1., 0.], 1.);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_triangle_plane() {
+    let a = Triangle::init(&[0., 0., 0., 1., 0., 1., 0., 1., 3.]);
+    let b = Plane::init(&[0., 0., 0.5], &[0., 0., 1.]);
+    let c = a.get_intersect(&b).expect("triangle plane unexpected miss");
+    assert!((c._depth - 0.5).abs() < 1e-9);
+    assert_eq!(c._feature, Some(0));
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-9);
+    assert_matrix_approx_eq_float(&c._point_b, &Matrix::from([[0., 0., 0.5, 1.]]).t(), 1e-9);
+
+    let b = Plane::init(&[0., 0., -0.5], &[0., 0., 1.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_intersect_triangle_box() {
+    let a = Triangle::init(&[-2., -2., 0.9, 2., -2., 0.9, 0., 2., 0.9]);
+    let b = RectBox::init(&[0., 0., 0.], 1.);
+    let c = a.get_intersect(&b).expect("triangle box unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., -1., 0.]]).t(), 1e-9);
+
+    //slanted triangle overlaps the bounding box of the box but its plane separates them
+    let a = Triangle::init(&[1.5, 0., 0., 0., 1.5, 0., 1.5, 1.5, 2.5]);
+    let b = RectBox::init(&[0., 0., 0.], 0.5);
+    assert!(a.get_intersect(&b).is_none());
+
+    let a = Triangle::init(&[-2., -2., 0.9, 2., -2., 0.9, 0., 2., 0.9]);
+    let b = Cuboid::init(&[0., 0., 0.], &[3., 3., 1.]);
+    let c = b
+        .get_intersect(&a)
+        .expect("cuboid triangle unexpected miss");
+    assert!((c._depth - 0.1).abs() < 1e-9);
+    assert_matrix_approx_eq_float(&c._normal, &Matrix::from([[0., 0., 1., 0.]]).t(), 1e-9);
+}
+#[test]
+fn test_intersect_triangle_triangle() {
+    let a = Triangle::init(&[0., 0., 0., 1., 0., 0., 0., 1., 0.]);
+    let b = Triangle::init(&[0.2, 0.2, -1., 0.2, 0.2, 1., 0.3, -1., 0.]);
+    assert!(a.get_intersect(&b).is_some());
+
+    let b = Triangle::init(&[0.2, 0.2, 0.1, 0.8, 0.2, 0.1, 0.2, 0.8, 1.]);
+    assert!(a.get_intersect(&b).is_none());
+
+    //coplanar, overlapping and disjoint
+    let b = Triangle::init(&[0.4, 0.4, 0., 2., 0.4, 0., 0.4, 2., 0.]);
+    assert!(a.get_intersect(&b).is_some());
+    let b = Triangle::init(&[0.6, 0.6, 0., 2., 0.6, 0., 0.6, 2., 0.]);
+    assert!(a.get_intersect(&b).is_none());
+}
+#[test]
+fn test_try_intersect_triangle_degenerate() {
+    let a = Triangle::init(&[0., 0., 0., 1., 0., 0., 2., 0., 0.]);
+    let b = Point::init(&[0.5, 0., 0.]);
+    assert_eq!(
+        a.try_intersect(&b).err(),
+        Some(CollideError::DegenerateShape(ShapeType::Trig))
+    );
+}